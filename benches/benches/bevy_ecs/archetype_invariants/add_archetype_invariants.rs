@@ -0,0 +1,108 @@
+use bevy_ecs::{
+    archetype_invariants::{
+        ComponentIdSet, Severity, UntypedArchetypeInvariant, UntypedArchetypeStatement,
+    },
+    prelude::*,
+    world::EntityMut,
+};
+use criterion::*;
+
+#[derive(Component)]
+struct C0;
+#[derive(Component)]
+struct C1;
+#[derive(Component)]
+struct C2;
+#[derive(Component)]
+struct C3;
+#[derive(Component)]
+struct C4;
+#[derive(Component)]
+struct C5;
+#[derive(Component)]
+struct C6;
+#[derive(Component)]
+struct C7;
+#[derive(Component)]
+struct C8;
+#[derive(Component)]
+struct C9;
+
+const COMPONENT_COUNT: usize = 10;
+const INVARIANT_COUNT: usize = 100;
+
+fn insert_component(entity: &mut EntityMut, index: usize) {
+    match index {
+        0 => entity.insert(C0),
+        1 => entity.insert(C1),
+        2 => entity.insert(C2),
+        3 => entity.insert(C3),
+        4 => entity.insert(C4),
+        5 => entity.insert(C5),
+        6 => entity.insert(C6),
+        7 => entity.insert(C7),
+        8 => entity.insert(C8),
+        _ => entity.insert(C9),
+    };
+}
+
+/// Creates a world with an archetype for every combination of the components, 1024 in total.
+fn setup() -> World {
+    let mut world = World::new();
+    for mask in 0..1_usize << COMPONENT_COUNT {
+        let mut entity = world.spawn_empty();
+        for index in (0..COMPONENT_COUNT).filter(|index| mask & (1 << index) != 0) {
+            insert_component(&mut entity, index);
+        }
+    }
+    world
+}
+
+/// Creates invariants which every archetype upholds, so that every archetype is checked against all of them.
+fn invariants(world: &World) -> Vec<UntypedArchetypeInvariant> {
+    let component_ids: ComponentIdSet = world.components().iter().map(|info| info.id()).collect();
+    (0..INVARIANT_COUNT)
+        .map(|n| UntypedArchetypeInvariant {
+            predicate: UntypedArchetypeStatement::AtLeastNOf(component_ids.clone(), n % 3),
+            consequence: UntypedArchetypeStatement::AtMostNOf(
+                component_ids.clone(),
+                COMPONENT_COUNT + n,
+            ),
+            severity: Severity::Error,
+        })
+        .collect()
+}
+
+pub fn add_archetype_invariants(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("add_archetype_invariants");
+    group.warm_up_time(std::time::Duration::from_millis(500));
+    group.measurement_time(std::time::Duration::from_secs(4));
+
+    let invariants = invariants(&setup());
+
+    group.bench_function("individually", |bencher| {
+        bencher.iter_batched(
+            || (setup(), invariants.clone()),
+            |(mut world, invariants)| {
+                for invariant in invariants {
+                    world.add_untyped_archetype_invariant(invariant);
+                }
+                world
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    group.bench_function("batched", |bencher| {
+        bencher.iter_batched(
+            || (setup(), invariants.clone()),
+            |(mut world, invariants)| {
+                world.add_archetype_invariants(invariants);
+                world
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    group.finish();
+}
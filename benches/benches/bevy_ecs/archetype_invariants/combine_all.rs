@@ -0,0 +1,105 @@
+use bevy_ecs::{prelude::*, world::EntityMut};
+use criterion::*;
+
+#[derive(Component)]
+struct C0;
+#[derive(Component)]
+struct C1;
+#[derive(Component)]
+struct C2;
+#[derive(Component)]
+struct C3;
+#[derive(Component)]
+struct C4;
+#[derive(Component)]
+struct C5;
+#[derive(Component)]
+struct C6;
+#[derive(Component)]
+struct C7;
+#[derive(Component)]
+struct C8;
+#[derive(Component)]
+struct C9;
+
+type Components = (C0, C1, C2, C3, C4, C5, C6, C7, C8, C9);
+
+const COMPONENT_COUNT: usize = 10;
+const RULE_COUNT: usize = 16;
+
+fn insert_component(entity: &mut EntityMut, index: usize) {
+    match index {
+        0 => entity.insert(C0),
+        1 => entity.insert(C1),
+        2 => entity.insert(C2),
+        3 => entity.insert(C3),
+        4 => entity.insert(C4),
+        5 => entity.insert(C5),
+        6 => entity.insert(C6),
+        7 => entity.insert(C7),
+        8 => entity.insert(C8),
+        _ => entity.insert(C9),
+    };
+}
+
+/// Creates a world with an archetype for every combination of the components, 1024 in total.
+fn setup() -> World {
+    let mut world = World::new();
+    for mask in 0..1_usize << COMPONENT_COUNT {
+        let mut entity = world.spawn_empty();
+        for index in (0..COMPONENT_COUNT).filter(|index| mask & (1 << index) != 0) {
+            insert_component(&mut entity, index);
+        }
+    }
+    world
+}
+
+/// Rules with the same predicate, which every archetype upholds, so that all of them are always evaluated.
+fn consequences() -> Vec<ArchetypeStatement<Components>> {
+    (0..RULE_COUNT)
+        .map(|n| ArchetypeStatement::at_most_n_of(COMPONENT_COUNT + n))
+        .collect()
+}
+
+pub fn combine_all(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("archetype_invariants_combine_all");
+    group.warm_up_time(std::time::Duration::from_millis(500));
+    group.measurement_time(std::time::Duration::from_secs(4));
+
+    group.bench_function("separate", |bencher| {
+        bencher.iter_batched(
+            setup,
+            |mut world| {
+                let invariants: Vec<_> = consequences()
+                    .into_iter()
+                    .map(|consequence| {
+                        ArchetypeInvariant {
+                            predicate: ArchetypeStatement::<Components>::at_least_one_of(),
+                            consequence,
+                        }
+                        .into_untyped(&mut world)
+                    })
+                    .collect();
+                world.add_archetype_invariants(invariants);
+                world
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    group.bench_function("combined", |bencher| {
+        bencher.iter_batched(
+            setup,
+            |mut world| {
+                world.add_archetype_invariant(ArchetypeInvariant::combine_all(
+                    ArchetypeStatement::<Components>::at_least_one_of(),
+                    consequences(),
+                ));
+                world
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    group.finish();
+}
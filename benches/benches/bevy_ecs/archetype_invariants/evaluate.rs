@@ -0,0 +1,272 @@
+use bevy_ecs::{
+    archetype::ArchetypeId,
+    archetype_invariants::{
+        ComponentIdSet, Severity, UntypedArchetypeInvariant, UntypedArchetypeStatement,
+    },
+    component::ComponentId,
+};
+use bevy_utils::HashSet;
+use criterion::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+const ARCHETYPE_COUNT: usize = 10_000;
+const COMPONENT_COUNT: usize = 32;
+
+/// Generates the sorted component lists of `ARCHETYPE_COUNT` archetypes with 1 to 8 components each.
+fn archetypes() -> Vec<ComponentIdSet> {
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    (0..ARCHETYPE_COUNT)
+        .map(|_| {
+            let component_count = rng.gen_range(1..=8);
+            (0..component_count)
+                .map(|_| ComponentId::new(rng.gen_range(0..COMPONENT_COUNT)))
+                .collect()
+        })
+        .collect()
+}
+
+fn ids(indices: &[usize]) -> ComponentIdSet {
+    indices.iter().copied().map(ComponentId::new).collect()
+}
+
+/// A typical set of invariants, each referring to 1 to 3 components.
+fn invariants() -> Vec<UntypedArchetypeInvariant> {
+    vec![
+        UntypedArchetypeInvariant {
+            predicate: UntypedArchetypeStatement::AtLeastOneOf(ids(&[0, 1, 2])),
+            consequence: UntypedArchetypeStatement::AtMostNOf(ids(&[0, 1, 2]), 3),
+            severity: Severity::Error,
+        },
+        UntypedArchetypeInvariant {
+            predicate: UntypedArchetypeStatement::AllOf(ids(&[3])),
+            consequence: UntypedArchetypeStatement::NoneOf(ids(&[4, 5])),
+            severity: Severity::Error,
+        },
+        UntypedArchetypeInvariant {
+            predicate: UntypedArchetypeStatement::AllOf(ids(&[6, 7])),
+            consequence: UntypedArchetypeStatement::AtMostOneOf(ids(&[8, 9, 10])),
+            severity: Severity::Error,
+        },
+        UntypedArchetypeInvariant {
+            predicate: UntypedArchetypeStatement::AtLeastOneOf(ids(&[11])),
+            consequence: UntypedArchetypeStatement::Not(Box::new(
+                UntypedArchetypeStatement::AllOf(ids(&[12, 13])),
+            )),
+            severity: Severity::Error,
+        },
+    ]
+}
+
+/// Evaluates `statement` the way archetype statements were evaluated when stored as a [`HashSet`].
+fn evaluate_hashed(statement: &HashedStatement, component_ids: &HashSet<ComponentId>) -> bool {
+    let count = |set: &HashSet<ComponentId>| set.intersection(component_ids).count();
+    match statement {
+        HashedStatement::AllOf(set) => set.is_subset(component_ids),
+        HashedStatement::AtLeastOneOf(set) => !set.is_disjoint(component_ids),
+        HashedStatement::NoneOf(set) => set.is_disjoint(component_ids),
+        HashedStatement::AtMostOneOf(set) => count(set) <= 1,
+        HashedStatement::ExactlyNOf(set, n) => count(set) == *n,
+        HashedStatement::AtLeastNOf(set, n) => count(set) >= *n,
+        HashedStatement::AtMostNOf(set, n) => count(set) <= *n,
+        HashedStatement::Exactly(set) => set == component_ids,
+        HashedStatement::ComponentCount { min, max } => {
+            (*min..=*max).contains(&component_ids.len())
+        }
+        HashedStatement::Constant(value) => *value,
+        HashedStatement::And(statements) => statements
+            .iter()
+            .all(|statement| evaluate_hashed(statement, component_ids)),
+        HashedStatement::Or(statements) => statements
+            .iter()
+            .any(|statement| evaluate_hashed(statement, component_ids)),
+        HashedStatement::Not(statement) => !evaluate_hashed(statement, component_ids),
+    }
+}
+
+enum HashedStatement {
+    AllOf(HashSet<ComponentId>),
+    AtLeastOneOf(HashSet<ComponentId>),
+    NoneOf(HashSet<ComponentId>),
+    AtMostOneOf(HashSet<ComponentId>),
+    ExactlyNOf(HashSet<ComponentId>, usize),
+    AtLeastNOf(HashSet<ComponentId>, usize),
+    AtMostNOf(HashSet<ComponentId>, usize),
+    Exactly(HashSet<ComponentId>),
+    ComponentCount { min: usize, max: usize },
+    Constant(bool),
+    And(Vec<HashedStatement>),
+    Or(Vec<HashedStatement>),
+    Not(Box<HashedStatement>),
+}
+
+impl From<&UntypedArchetypeStatement> for HashedStatement {
+    /// Converts every kind of statement, evaluating them like [`UntypedArchetypeStatement::evaluate`]:
+    /// without any resources, and with unresolved component groups treated as empty.
+    fn from(statement: &UntypedArchetypeStatement) -> Self {
+        let hashed = |set: &ComponentIdSet| set.iter().collect();
+        let all = |statements: &[UntypedArchetypeStatement]| {
+            statements.iter().map(HashedStatement::from).collect()
+        };
+        match statement {
+            UntypedArchetypeStatement::AllOf(set) => HashedStatement::AllOf(hashed(set)),
+            UntypedArchetypeStatement::AtLeastOneOf(set) => {
+                HashedStatement::AtLeastOneOf(hashed(set))
+            }
+            UntypedArchetypeStatement::NoneOf(set) => HashedStatement::NoneOf(hashed(set)),
+            UntypedArchetypeStatement::ExactlyOneOf(set) => {
+                HashedStatement::ExactlyNOf(hashed(set), 1)
+            }
+            UntypedArchetypeStatement::AtMostOneOf(set) => {
+                HashedStatement::AtMostOneOf(hashed(set))
+            }
+            UntypedArchetypeStatement::ExactlyNOf(set, n) => {
+                HashedStatement::ExactlyNOf(hashed(set), *n)
+            }
+            UntypedArchetypeStatement::AtLeastNOf(set, n) => {
+                HashedStatement::AtLeastNOf(hashed(set), *n)
+            }
+            UntypedArchetypeStatement::AtMostNOf(set, n) => {
+                HashedStatement::AtMostNOf(hashed(set), *n)
+            }
+            UntypedArchetypeStatement::Exactly(set) => HashedStatement::Exactly(hashed(set)),
+            UntypedArchetypeStatement::SubsetOf { relevant, allowed } => HashedStatement::NoneOf(
+                relevant
+                    .iter()
+                    .filter(|&id| !allowed.contains(id))
+                    .collect(),
+            ),
+            UntypedArchetypeStatement::ComponentCount { min, max } => {
+                HashedStatement::ComponentCount {
+                    min: min.unwrap_or(0),
+                    max: max.unwrap_or(usize::MAX),
+                }
+            }
+            UntypedArchetypeStatement::ResourcePresent(_) => HashedStatement::Constant(false),
+            UntypedArchetypeStatement::HasParent(id)
+            | UntypedArchetypeStatement::HasChildren(id) => {
+                HashedStatement::AllOf(HashSet::from_iter([*id]))
+            }
+            UntypedArchetypeStatement::StorageType { mismatched, .. } => {
+                HashedStatement::NoneOf(hashed(mismatched))
+            }
+            UntypedArchetypeStatement::GroupAllOf(_) | UntypedArchetypeStatement::Always => {
+                HashedStatement::Constant(true)
+            }
+            UntypedArchetypeStatement::GroupAtLeastOneOf(_) => HashedStatement::Constant(false),
+            UntypedArchetypeStatement::Empty => HashedStatement::Exactly(HashSet::default()),
+            UntypedArchetypeStatement::And(statements) => HashedStatement::And(all(statements)),
+            UntypedArchetypeStatement::Or(statements) => HashedStatement::Or(all(statements)),
+            UntypedArchetypeStatement::Not(statement) => {
+                HashedStatement::Not(Box::new(statement.as_ref().into()))
+            }
+        }
+    }
+}
+
+pub fn evaluate(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("archetype_invariants_evaluate");
+    group.warm_up_time(std::time::Duration::from_millis(500));
+    group.measurement_time(std::time::Duration::from_secs(4));
+
+    let archetypes = archetypes();
+    let invariants = invariants();
+
+    group.bench_function("sorted_10000_archetypes", |bencher| {
+        bencher.iter(|| {
+            for archetype in &archetypes {
+                // Archetypes list their components unsorted, so each one is collected into a set first
+                let component_ids: ComponentIdSet = archetype.iter().collect();
+                for invariant in &invariants {
+                    black_box(invariant.evaluate(&component_ids));
+                }
+            }
+        });
+    });
+
+    let hashed_invariants: Vec<(HashedStatement, HashedStatement)> = invariants
+        .iter()
+        .map(|invariant| {
+            (
+                (&invariant.predicate).into(),
+                (&invariant.consequence).into(),
+            )
+        })
+        .collect();
+    group.bench_function("hash_set_10000_archetypes", |bencher| {
+        bencher.iter(|| {
+            for archetype in &archetypes {
+                let component_ids: HashSet<ComponentId> = archetype.iter().collect();
+                for (predicate, consequence) in &hashed_invariants {
+                    black_box(
+                        !evaluate_hashed(predicate, &component_ids)
+                            || evaluate_hashed(consequence, &component_ids),
+                    );
+                }
+            }
+        });
+    });
+
+    group.finish();
+}
+
+/// Compares evaluating the archetypes of a world directly against collecting their components first.
+pub fn evaluate_against_archetype(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("archetype_invariants_evaluate_against_archetype");
+    group.warm_up_time(std::time::Duration::from_millis(500));
+    group.measurement_time(std::time::Duration::from_secs(4));
+
+    let world = super::parallel_checks::setup();
+    let archetypes: Vec<_> = world
+        .archetypes()
+        .iter()
+        .filter(|archetype| archetype.id() != ArchetypeId::RESOURCE)
+        .collect();
+    let invariants = invariants();
+
+    group.bench_function("archetype_lookups", |bencher| {
+        bencher.iter(|| {
+            for archetype in &archetypes {
+                for invariant in &invariants {
+                    black_box(invariant.evaluate_against_archetype(archetype));
+                }
+            }
+        });
+    });
+
+    group.bench_function("collect_sorted_set", |bencher| {
+        bencher.iter(|| {
+            for archetype in &archetypes {
+                let component_ids: ComponentIdSet = archetype.components().collect();
+                for invariant in &invariants {
+                    black_box(invariant.evaluate(&component_ids));
+                }
+            }
+        });
+    });
+
+    let hashed_invariants: Vec<(HashedStatement, HashedStatement)> = invariants
+        .iter()
+        .map(|invariant| {
+            (
+                (&invariant.predicate).into(),
+                (&invariant.consequence).into(),
+            )
+        })
+        .collect();
+    group.bench_function("collect_hash_set", |bencher| {
+        bencher.iter(|| {
+            for archetype in &archetypes {
+                let component_ids: HashSet<ComponentId> = archetype.components().collect();
+                for (predicate, consequence) in &hashed_invariants {
+                    black_box(
+                        !evaluate_hashed(predicate, &component_ids)
+                            || evaluate_hashed(consequence, &component_ids),
+                    );
+                }
+            }
+        });
+    });
+
+    group.finish();
+}
@@ -0,0 +1,41 @@
+use bevy_ecs::{archetype_invariants::UntypedArchetypeInvariant, prelude::*};
+use criterion::*;
+
+#[derive(Component)]
+struct A;
+#[derive(Component)]
+struct B;
+#[derive(Component)]
+struct C;
+#[derive(Component)]
+struct D;
+#[derive(Component)]
+struct E;
+
+fn lower_invariant(world: &mut World) -> UntypedArchetypeInvariant {
+    ArchetypeInvariant::<(A, B, C, D, E)>::full_bundle().into_untyped(world)
+}
+
+pub fn into_untyped(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("archetype_invariants_into_untyped");
+    group.warm_up_time(std::time::Duration::from_millis(500));
+    group.measurement_time(std::time::Duration::from_secs(4));
+
+    // Every iteration starts with a fresh world, so the bundle's components must be looked up first.
+    group.bench_function("cold", |bencher| {
+        bencher.iter_batched(
+            World::new,
+            |mut world| lower_invariant(&mut world),
+            BatchSize::SmallInput,
+        );
+    });
+
+    // The bundle's components have already been looked up in this world.
+    group.bench_function("warm", |bencher| {
+        let mut world = World::new();
+        lower_invariant(&mut world);
+        bencher.iter(|| lower_invariant(&mut world));
+    });
+
+    group.finish();
+}
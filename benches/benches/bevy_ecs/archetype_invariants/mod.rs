@@ -0,0 +1,23 @@
+use criterion::criterion_group;
+
+mod add_archetype_invariants;
+mod combine_all;
+mod evaluate;
+mod into_untyped;
+mod parallel_checks;
+
+use add_archetype_invariants::*;
+use combine_all::*;
+use evaluate::*;
+use into_untyped::*;
+use parallel_checks::*;
+
+criterion_group!(
+    archetype_invariants_benches,
+    add_archetype_invariants,
+    combine_all,
+    evaluate,
+    evaluate_against_archetype,
+    into_untyped,
+    parallel_checks
+);
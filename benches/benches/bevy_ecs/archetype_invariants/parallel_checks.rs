@@ -0,0 +1,110 @@
+use bevy_ecs::{
+    archetype_invariants::{
+        ComponentIdSet, Severity, UntypedArchetypeInvariant, UntypedArchetypeStatement,
+    },
+    prelude::*,
+    world::EntityMut,
+};
+use criterion::*;
+
+#[derive(Component)]
+struct C0;
+#[derive(Component)]
+struct C1;
+#[derive(Component)]
+struct C2;
+#[derive(Component)]
+struct C3;
+#[derive(Component)]
+struct C4;
+#[derive(Component)]
+struct C5;
+#[derive(Component)]
+struct C6;
+#[derive(Component)]
+struct C7;
+#[derive(Component)]
+struct C8;
+#[derive(Component)]
+struct C9;
+#[derive(Component)]
+struct C10;
+#[derive(Component)]
+struct C11;
+#[derive(Component)]
+struct C12;
+#[derive(Component)]
+struct C13;
+#[derive(Component)]
+struct C14;
+#[derive(Component)]
+struct C15;
+
+const COMPONENT_COUNT: usize = 16;
+const ARCHETYPE_COUNT: usize = 50_000;
+
+fn insert_component(entity: &mut EntityMut, index: usize) {
+    match index {
+        0 => entity.insert(C0),
+        1 => entity.insert(C1),
+        2 => entity.insert(C2),
+        3 => entity.insert(C3),
+        4 => entity.insert(C4),
+        5 => entity.insert(C5),
+        6 => entity.insert(C6),
+        7 => entity.insert(C7),
+        8 => entity.insert(C8),
+        9 => entity.insert(C9),
+        10 => entity.insert(C10),
+        11 => entity.insert(C11),
+        12 => entity.insert(C12),
+        13 => entity.insert(C13),
+        14 => entity.insert(C14),
+        _ => entity.insert(C15),
+    };
+}
+
+/// Creates a world with at least `ARCHETYPE_COUNT` archetypes, each holding one entity.
+///
+/// The components are registered in order, so that `C0` has the [`ComponentId`](bevy_ecs::component::ComponentId) 0.
+pub(super) fn setup() -> World {
+    let mut world = World::new();
+    for mask in 0..ARCHETYPE_COUNT {
+        let mut entity = world.spawn_empty();
+        for index in (0..COMPONENT_COUNT).filter(|index| mask & (1 << index) != 0) {
+            insert_component(&mut entity, index);
+        }
+    }
+    world
+}
+
+/// Creates an invariant which every archetype upholds, so that every archetype is fully evaluated.
+fn invariant(world: &World) -> UntypedArchetypeInvariant {
+    let component_ids: ComponentIdSet = world.components().iter().map(|info| info.id()).collect();
+    UntypedArchetypeInvariant {
+        predicate: UntypedArchetypeStatement::AtLeastOneOf(component_ids.clone()),
+        consequence: UntypedArchetypeStatement::AtMostNOf(component_ids, COMPONENT_COUNT),
+        severity: Severity::Error,
+    }
+}
+
+pub fn parallel_checks(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("parallel_archetype_invariant_checks");
+    group.warm_up_time(std::time::Duration::from_millis(500));
+    group.measurement_time(std::time::Duration::from_secs(4));
+
+    let mut world = setup();
+    let invariant = invariant(&world);
+    for (name, threshold) in [("sequential", usize::MAX), ("parallel", 1)] {
+        world.set_archetype_invariant_parallel_check_threshold(threshold);
+        group.bench_function(name, |bencher| {
+            bencher.iter(|| {
+                // Adding the invariant checks every existing archetype against it
+                let id = world.add_untyped_archetype_invariant(invariant.clone());
+                world.remove_archetype_invariant(id);
+            });
+        });
+    }
+
+    group.finish();
+}
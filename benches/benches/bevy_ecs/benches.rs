@@ -1,11 +1,13 @@
 use criterion::criterion_main;
 
+mod archetype_invariants;
 mod components;
 mod iteration;
 mod scheduling;
 mod world;
 
 criterion_main!(
+    archetype_invariants::archetype_invariants_benches,
     iteration::iterations_benches,
     components::components_benches,
     scheduling::scheduling_benches,
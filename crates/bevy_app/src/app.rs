@@ -2,6 +2,9 @@ use crate::{CoreStage, Plugin, PluginGroup, PluginGroupBuilder, StartupSchedule,
 pub use bevy_derive::AppLabel;
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::{
+    archetype_invariants::{ArchetypeInvariant, ArchetypeInvariantId, UntypedArchetypeInvariant},
+    bundle::Bundle,
+    component::Component,
     event::{Event, Events},
     prelude::FromWorld,
     schedule::{
@@ -748,6 +751,173 @@ impl App {
         self
     }
 
+    /// Adds the archetype invariants declared by the `#[component(requires(...))]` and
+    /// `#[component(forbids(...))]` attributes of the component `C` to the [`World`].
+    ///
+    /// This is typically called in the [`Plugin::build`] of the plugin that defines the component.
+    /// See [`World::register_component_invariants`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bevy_app::prelude::*;
+    /// # use bevy_ecs::prelude::*;
+    /// #
+    /// #[derive(Component)]
+    /// struct Transform;
+    ///
+    /// #[derive(Component)]
+    /// #[component(requires(Transform))]
+    /// struct Collider;
+    ///
+    /// App::new()
+    ///     .register_component_invariants::<Collider>();
+    /// ```
+    pub fn register_component_invariants<C: Component>(&mut self) -> &mut Self {
+        self.world.register_component_invariants::<C>();
+        self
+    }
+
+    /// Adds an [`ArchetypeInvariant`] to the [`World`], converting it into an [`UntypedArchetypeInvariant`].
+    ///
+    /// As with [`World::add_archetype_invariant`], existing archetypes are checked against the invariant immediately
+    /// if the `archetype_invariants` feature of `bevy_ecs` is enabled.
+    /// To remove the invariant later, call [`World::add_archetype_invariant`] directly instead, which returns its id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bevy_app::prelude::*;
+    /// # use bevy_ecs::{archetype_invariants::ArchetypeInvariant, prelude::*};
+    /// #
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Health;
+    /// #[derive(Component)]
+    /// struct Enemy;
+    ///
+    /// App::new()
+    ///     .add_archetype_invariant(ArchetypeInvariant::<(Player,), (Health,)>::requires())
+    ///     .add_archetype_invariant(ArchetypeInvariant::<(Player,), (Enemy,)>::forbids());
+    /// ```
+    #[track_caller]
+    pub fn add_archetype_invariant<B1: Bundle, B2: Bundle>(
+        &mut self,
+        archetype_invariant: ArchetypeInvariant<B1, B2>,
+    ) -> &mut Self {
+        self.world.add_archetype_invariant(archetype_invariant);
+        self
+    }
+
+    /// Adds an [`UntypedArchetypeInvariant`] to the [`World`].
+    ///
+    /// See [`App::add_archetype_invariant`].
+    #[track_caller]
+    pub fn add_untyped_archetype_invariant(
+        &mut self,
+        archetype_invariant: UntypedArchetypeInvariant,
+    ) -> &mut Self {
+        self.world
+            .add_untyped_archetype_invariant(archetype_invariant);
+        self
+    }
+
+    /// Adds an [`ArchetypeInvariant`] to the [`World`] in debug builds only,
+    /// as with [`App::add_archetype_invariant`].
+    ///
+    /// In builds without `debug_assertions`, such as release builds, this does nothing:
+    /// the registration is removed at compile time, so the invariant is never stored or checked.
+    /// Note that this depends on whether `debug_assertions` are enabled for `bevy_app`,
+    /// which only differs from the rest of the app if they are overridden per package in a Cargo profile.
+    /// This differs from disabling an invariant at runtime with [`World::set_archetype_invariant_enabled`],
+    /// which keeps it registered. Use this for expensive or purely diagnostic rules
+    /// that should not cost anything once the game ships.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bevy_app::prelude::*;
+    /// # use bevy_ecs::{archetype_invariants::ArchetypeInvariant, prelude::*};
+    /// #
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Health;
+    ///
+    /// // Only checked while developing
+    /// App::new()
+    ///     .add_debug_archetype_invariant(ArchetypeInvariant::<(Player,), (Health,)>::requires());
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn add_debug_archetype_invariant<B1: Bundle, B2: Bundle>(
+        &mut self,
+        archetype_invariant: ArchetypeInvariant<B1, B2>,
+    ) -> &mut Self {
+        #[cfg(debug_assertions)]
+        self.add_archetype_invariant(archetype_invariant);
+        #[cfg(not(debug_assertions))]
+        let _ = archetype_invariant;
+        self
+    }
+
+    /// Adds an [`ArchetypeInvariant`] to the [`World`] that is only enforced once the startup systems have run.
+    ///
+    /// The invariant is added disabled using [`World::add_disabled_archetype_invariant`],
+    /// and is enabled at the start of [`CoreStage::First`] in the first update, after every [`StartupStage`] has run.
+    /// Use this for rules that are temporarily broken while startup systems build entities over several stages.
+    /// See [`World::add_disabled_archetype_invariant`] for invariants tied to other phases of an app.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use bevy_app::prelude::*;
+    /// # use bevy_ecs::{archetype_invariants::ArchetypeInvariant, prelude::*};
+    /// #
+    /// #[derive(Component)]
+    /// struct Node;
+    /// #[derive(Component)]
+    /// struct Layout;
+    ///
+    /// fn spawn_ui(mut commands: Commands) {
+    ///     commands.spawn(Node);
+    /// }
+    ///
+    /// fn compute_layout(mut commands: Commands, nodes: Query<Entity, Without<Layout>>) {
+    ///     for node in &nodes {
+    ///         commands.entity(node).insert(Layout);
+    ///     }
+    /// }
+    ///
+    /// App::new()
+    ///     .add_archetype_invariant_after_startup(ArchetypeInvariant::<(Node,), (Layout,)>::requires())
+    ///     .add_startup_system(spawn_ui)
+    ///     .add_startup_system_to_stage(StartupStage::PostStartup, compute_layout)
+    ///     .update();
+    /// ```
+    #[track_caller]
+    pub fn add_archetype_invariant_after_startup<B1: Bundle, B2: Bundle>(
+        &mut self,
+        archetype_invariant: ArchetypeInvariant<B1, B2>,
+    ) -> &mut Self {
+        let id = self
+            .world
+            .add_disabled_archetype_invariant(archetype_invariant);
+        if !self.world.contains_resource::<StartupArchetypeInvariants>() {
+            self.init_resource::<StartupArchetypeInvariants>();
+            self.add_system_to_stage(
+                CoreStage::First,
+                enable_startup_archetype_invariants.at_start(),
+            );
+        }
+        self.world
+            .resource_mut::<StartupArchetypeInvariants>()
+            .0
+            .push(id);
+        self
+    }
+
     /// Sets the function that will be called when the app is run.
     ///
     /// The runner function `run_fn` is called only once by [`App::run`]. If the
@@ -1012,3 +1182,137 @@ fn run_once(mut app: App) {
 /// frame is over.
 #[derive(Debug, Clone, Default)]
 pub struct AppExit;
+
+/// The archetype invariants added with [`App::add_archetype_invariant_after_startup`] that have not been enabled yet.
+#[derive(Resource, Default)]
+struct StartupArchetypeInvariants(Vec<ArchetypeInvariantId>);
+
+/// Enables the invariants added with [`App::add_archetype_invariant_after_startup`].
+fn enable_startup_archetype_invariants(world: &mut World) {
+    let ids = std::mem::take(&mut world.resource_mut::<StartupArchetypeInvariants>().0);
+    for id in ids {
+        world.set_archetype_invariant_enabled(id, true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::{
+        archetype_invariants::{
+            ArchetypeInvariant, ArchetypeStatement, UntypedArchetypeInvariant, ViolationPolicy,
+        },
+        component::Component,
+    };
+
+    use crate::{App, Plugin};
+
+    #[derive(Component)]
+    struct Player;
+    #[derive(Component)]
+    struct Health;
+    #[derive(Component)]
+    struct Enemy;
+
+    struct InvariantPlugin;
+
+    impl Plugin for InvariantPlugin {
+        fn build(&self, app: &mut App) {
+            let untyped =
+                ArchetypeInvariant::<(Enemy,), (Health,)>::requires().into_untyped(&mut app.world);
+            app.add_archetype_invariant(ArchetypeInvariant::<(Player,), (Health,)>::requires())
+                .add_archetype_invariant(ArchetypeInvariant::<(Player,), (Enemy,)>::forbids())
+                .add_untyped_archetype_invariant(untyped);
+        }
+    }
+
+    #[test]
+    fn add_archetype_invariants_in_plugin() {
+        let mut app = App::new();
+        app.add_plugin(InvariantPlugin);
+
+        let forbids = ArchetypeInvariant::<(Player,), (Enemy,)> {
+            predicate: ArchetypeStatement::all_of(),
+            consequence: ArchetypeStatement::none_of(),
+        }
+        .into_untyped(&mut app.world);
+        let invariants: Vec<&UntypedArchetypeInvariant> =
+            app.world.archetype_invariants().collect();
+        assert_eq!(invariants.len(), 3);
+        assert_eq!(*invariants[1], forbids);
+
+        app.world.spawn((Player, Health));
+        app.world.spawn((Enemy, Health));
+        assert!(app.world.check_archetype_invariants().is_empty());
+    }
+
+    #[test]
+    fn plugin_archetype_invariants_are_enforced() {
+        let mut app = App::new();
+        app.add_plugin(InvariantPlugin);
+        // Spawning the violating entities must not panic if the automatic checks are compiled in
+        app.world
+            .set_archetype_invariant_policy(ViolationPolicy::Warn);
+
+        app.world.spawn((Player, Enemy, Health));
+        app.world.spawn(Enemy);
+        assert_eq!(app.world.check_archetype_invariants().len(), 2);
+    }
+
+    #[test]
+    fn archetype_invariants_after_startup() {
+        use bevy_ecs::{
+            prelude::{Entity, Query, Without},
+            system::Commands,
+        };
+
+        use crate::StartupStage;
+
+        fn spawn_player(mut commands: Commands) {
+            commands.spawn(Player);
+        }
+
+        fn add_health(mut commands: Commands, players: Query<Entity, Without<Health>>) {
+            for player in &players {
+                commands.entity(player).insert(Health);
+            }
+        }
+
+        let mut app = App::new();
+        app.world
+            .set_archetype_invariant_policy(ViolationPolicy::Warn);
+        app.add_archetype_invariant_after_startup(
+            ArchetypeInvariant::<(Player,), (Health,)>::requires(),
+        )
+        .add_startup_system(spawn_player)
+        .add_startup_system_to_stage(StartupStage::PostStartup, add_health);
+        // Not enforced before the startup systems have run
+        let player = app.world.spawn(Player).id();
+        assert!(app.world.check_archetype_invariants().is_empty());
+
+        app.update();
+        assert!(app
+            .world
+            .archetype_invariant_violations_for_entity(player)
+            .is_empty());
+        let late_player = app.world.spawn(Player).id();
+        assert_eq!(
+            app.world
+                .archetype_invariant_violations_for_entity(late_player)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn debug_archetype_invariants() {
+        let mut app = App::new();
+        app.world
+            .set_archetype_invariant_policy(ViolationPolicy::Warn);
+        app.add_debug_archetype_invariant(ArchetypeInvariant::<(Player,), (Health,)>::requires());
+        assert_eq!(app.world.archetype_invariants().count(), 1);
+
+        app.world.spawn(Player);
+        assert_eq!(app.world.check_archetype_invariants().len(), 1);
+    }
+}
@@ -16,7 +16,13 @@ pub mod prelude {
 }
 
 use bevy_app::prelude::*;
-use bevy_ecs::entity::Entity;
+use bevy_ecs::{
+    archetype_invariants::{
+        ComponentIdSet, Severity, UntypedArchetypeInvariant, UntypedArchetypeStatement,
+    },
+    component::ComponentId,
+    entity::Entity,
+};
 use bevy_utils::{Duration, HashSet, Instant};
 use std::borrow::Cow;
 use std::ops::Range;
@@ -35,6 +41,7 @@ impl Plugin for CorePlugin {
             .create_default_pools();
 
         app.register_type::<Entity>().register_type::<Name>();
+        register_archetype_invariant_types(app);
 
         register_rust_types(app);
         register_math_types(app);
@@ -43,6 +50,14 @@ impl Plugin for CorePlugin {
     }
 }
 
+fn register_archetype_invariant_types(app: &mut App) {
+    app.register_type::<ComponentId>()
+        .register_type::<ComponentIdSet>()
+        .register_type::<UntypedArchetypeStatement>()
+        .register_type::<UntypedArchetypeInvariant>()
+        .register_type::<Severity>();
+}
+
 fn register_rust_types(app: &mut App) {
     app.register_type::<Range<f32>>()
         .register_type::<String>()
@@ -0,0 +1,44 @@
+use bevy_app::{App, Plugin};
+use bevy_ecs::{
+    archetype_invariants::ArchetypeInvariantViolated, event::EventReader, system::ResMut,
+};
+
+use crate::{Diagnostic, DiagnosticId, Diagnostics};
+
+/// Adds "archetype invariant violations" diagnostic to an App, counting the violations detected each frame
+///
+/// Violations are counted by reading the [`ArchetypeInvariantViolated`] events, which are only sent while the
+/// world's [`ViolationPolicy`](bevy_ecs::archetype_invariants::ViolationPolicy) is `Event`.
+/// This plugin does not change the policy: set it with `World::set_archetype_invariant_policy`,
+/// otherwise the measured count is always zero.
+#[derive(Default)]
+pub struct ArchetypeInvariantDiagnosticsPlugin;
+
+impl Plugin for ArchetypeInvariantDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ArchetypeInvariantViolated>()
+            .add_startup_system(Self::setup_system)
+            .add_system(Self::diagnostic_system);
+    }
+}
+
+impl ArchetypeInvariantDiagnosticsPlugin {
+    pub const ARCHETYPE_INVARIANT_VIOLATIONS: DiagnosticId =
+        DiagnosticId::from_u128(80758349494960574845893931515184478203);
+
+    pub fn setup_system(mut diagnostics: ResMut<Diagnostics>) {
+        diagnostics.add(Diagnostic::new(
+            Self::ARCHETYPE_INVARIANT_VIOLATIONS,
+            "archetype_invariant_violations",
+            20,
+        ));
+    }
+
+    pub fn diagnostic_system(
+        mut diagnostics: ResMut<Diagnostics>,
+        mut violations: EventReader<ArchetypeInvariantViolated>,
+    ) {
+        let count = violations.iter().count();
+        diagnostics.add_measurement(Self::ARCHETYPE_INVARIANT_VIOLATIONS, || count as f64);
+    }
+}
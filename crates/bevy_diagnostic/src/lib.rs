@@ -1,7 +1,9 @@
+mod archetype_invariant_diagnostics_plugin;
 mod diagnostic;
 mod entity_count_diagnostics_plugin;
 mod frame_time_diagnostics_plugin;
 mod log_diagnostics_plugin;
+pub use archetype_invariant_diagnostics_plugin::ArchetypeInvariantDiagnosticsPlugin;
 pub use diagnostic::*;
 pub use entity_count_diagnostics_plugin::EntityCountDiagnosticsPlugin;
 pub use frame_time_diagnostics_plugin::FrameTimeDiagnosticsPlugin;
@@ -32,6 +32,25 @@ pub fn derive_component(input: TokenStream) -> TokenStream {
     };
 
     let storage = storage_path(&bevy_ecs_path, attrs.storage);
+    let register_archetype_invariants =
+        (!attrs.requires.is_empty() || !attrs.forbids.is_empty()).then(|| {
+            let requires = &attrs.requires;
+            let forbids = &attrs.forbids;
+            quote! {
+                fn register_archetype_invariants(world: &mut #bevy_ecs_path::world::World) {
+                    #(
+                        world.add_archetype_invariant(
+                            #bevy_ecs_path::archetype_invariants::ArchetypeInvariant::<(Self,), (#requires,)>::requires()
+                        );
+                    )*
+                    #(
+                        world.add_archetype_invariant(
+                            #bevy_ecs_path::archetype_invariants::ArchetypeInvariant::<(Self,), (#forbids,)>::forbids()
+                        );
+                    )*
+                }
+            }
+        });
 
     ast.generics
         .make_where_clause()
@@ -44,15 +63,21 @@ pub fn derive_component(input: TokenStream) -> TokenStream {
     TokenStream::from(quote! {
         impl #impl_generics #bevy_ecs_path::component::Component for #struct_name #type_generics #where_clause {
             type Storage = #storage;
+
+            #register_archetype_invariants
         }
     })
 }
 
 pub const COMPONENT: Symbol = Symbol("component");
 pub const STORAGE: Symbol = Symbol("storage");
+pub const REQUIRES: Symbol = Symbol("requires");
+pub const FORBIDS: Symbol = Symbol("forbids");
 
 struct Attrs {
     storage: StorageTy,
+    requires: Vec<Path>,
+    forbids: Vec<Path>,
 }
 
 #[derive(Clone, Copy)]
@@ -70,11 +95,13 @@ fn parse_component_attr(ast: &DeriveInput) -> Result<Attrs> {
 
     let mut attrs = Attrs {
         storage: StorageTy::Table,
+        requires: Vec::new(),
+        forbids: Vec::new(),
     };
 
     for meta in meta_items {
         use syn::{
-            Meta::NameValue,
+            Meta::{List, NameValue},
             NestedMeta::{Lit, Meta},
         };
         match meta {
@@ -93,6 +120,16 @@ fn parse_component_attr(ast: &DeriveInput) -> Result<Attrs> {
                     }
                 };
             }
+            Meta(List(m)) if m.path == REQUIRES => {
+                attrs
+                    .requires
+                    .extend(parse_component_list(REQUIRES, m.nested)?);
+            }
+            Meta(List(m)) if m.path == FORBIDS => {
+                attrs
+                    .forbids
+                    .extend(parse_component_list(FORBIDS, m.nested)?);
+            }
             Meta(meta_item) => {
                 return Err(Error::new_spanned(
                     meta_item.path(),
@@ -114,6 +151,23 @@ fn parse_component_attr(ast: &DeriveInput) -> Result<Attrs> {
     Ok(attrs)
 }
 
+/// Parses the component types listed in a `requires(...)` or `forbids(...)` attribute.
+fn parse_component_list(
+    attr_name: Symbol,
+    nested: impl IntoIterator<Item = syn::NestedMeta>,
+) -> Result<Vec<Path>> {
+    nested
+        .into_iter()
+        .map(|nested| match nested {
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) => Ok(path),
+            other => Err(Error::new_spanned(
+                other,
+                format!("expected a component type in the `{}` attribute", attr_name),
+            )),
+        })
+        .collect()
+}
+
 fn storage_path(bevy_ecs_path: &Path, ty: StorageTy) -> TokenStream2 {
     let typename = match ty {
         StorageTy::Table => Ident::new("TableStorage", Span::call_site()),
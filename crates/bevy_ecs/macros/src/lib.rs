@@ -100,12 +100,16 @@ pub fn derive_bundle(input: TokenStream) -> TokenStream {
         .collect::<Vec<_>>();
 
     let mut field_component_ids = Vec::new();
+    let mut field_component_type_ids = Vec::new();
     let mut field_get_components = Vec::new();
     let mut field_from_components = Vec::new();
     for (field_type, field) in field_type.iter().zip(field.iter()) {
         field_component_ids.push(quote! {
         <#field_type as #ecs_path::bundle::Bundle>::component_ids(components, storages, &mut *ids);
         });
+        field_component_type_ids.push(quote! {
+        <#field_type as #ecs_path::bundle::Bundle>::component_type_ids(&mut *ids);
+        });
         field_get_components.push(quote! {
             self.#field.get_components(&mut *func);
         });
@@ -128,6 +132,12 @@ pub fn derive_bundle(input: TokenStream) -> TokenStream {
                 #(#field_component_ids)*
             }
 
+            fn component_type_ids(
+                ids: &mut impl FnMut(::std::any::TypeId, &'static str)
+            ){
+                #(#field_component_type_ids)*
+            }
+
             #[allow(unused_variables, non_snake_case)]
             unsafe fn from_components<__T, __F>(ctx: &mut __T, func: &mut __F) -> Self
             where
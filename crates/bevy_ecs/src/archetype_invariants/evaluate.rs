@@ -0,0 +1,233 @@
+//! Evaluation of archetype statements against the components of an archetype.
+//!
+//! This only relies on the operations of [`ComponentIdSet`] and [`Archetype`], and does not log anything,
+//! so it behaves the same regardless of the enabled features.
+
+use super::{ComponentIdSet, UntypedArchetypeInvariant, UntypedArchetypeStatement};
+use crate::{archetype::Archetype, component::ComponentId};
+
+/// The components of an archetype, as needed to evaluate statements against it.
+///
+/// This lets the same evaluation run against a sorted [`ComponentIdSet`],
+/// or directly against an [`Archetype`] without collecting its components first.
+pub(super) trait ArchetypeComponents {
+    /// The number of components in the archetype.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the archetype contains the component.
+    fn contains(&self, id: ComponentId) -> bool;
+
+    /// Counts the components of `set` that the archetype contains, stopping once `limit` of them have been found.
+    fn count_common(&self, set: &ComponentIdSet, limit: usize) -> usize;
+
+    /// Returns `true` if the archetype contains every component of `set`.
+    #[inline]
+    fn contains_all(&self, set: &ComponentIdSet) -> bool {
+        set.iter().all(|id| self.contains(id))
+    }
+
+    /// Returns `true` if the archetype contains any component of `set`.
+    #[inline]
+    fn contains_any(&self, set: &ComponentIdSet) -> bool {
+        set.iter().any(|id| self.contains(id))
+    }
+
+    /// Returns `true` if the archetype contains exactly the components of `set`.
+    #[inline]
+    fn is(&self, set: &ComponentIdSet) -> bool {
+        self.len() == set.len() && self.contains_all(set)
+    }
+}
+
+impl ArchetypeComponents for ComponentIdSet {
+    #[inline]
+    fn len(&self) -> usize {
+        ComponentIdSet::len(self)
+    }
+
+    #[inline]
+    fn contains(&self, id: ComponentId) -> bool {
+        ComponentIdSet::contains(self, id)
+    }
+
+    #[inline]
+    fn count_common(&self, set: &ComponentIdSet, limit: usize) -> usize {
+        set.count_common(self, limit)
+    }
+
+    // Both sets are sorted, so these can walk them side by side instead of searching for each component
+    #[inline]
+    fn contains_all(&self, set: &ComponentIdSet) -> bool {
+        set.is_subset(self)
+    }
+
+    #[inline]
+    fn contains_any(&self, set: &ComponentIdSet) -> bool {
+        !set.is_disjoint(self)
+    }
+
+    #[inline]
+    fn is(&self, set: &ComponentIdSet) -> bool {
+        self == set
+    }
+}
+
+impl ArchetypeComponents for Archetype {
+    #[inline]
+    fn len(&self) -> usize {
+        self.table_components().len() + self.sparse_set_components().len()
+    }
+
+    #[inline]
+    fn contains(&self, id: ComponentId) -> bool {
+        Archetype::contains(self, id)
+    }
+
+    #[inline]
+    fn count_common(&self, set: &ComponentIdSet, limit: usize) -> usize {
+        set.iter()
+            .filter(|&id| Archetype::contains(self, id))
+            .take(limit)
+            .count()
+    }
+}
+
+impl UntypedArchetypeStatement {
+    /// Tests if this statement is true for an entity with the provided set of [`ComponentId`]s.
+    ///
+    /// Any [`UntypedArchetypeStatement::ResourcePresent`] is false,
+    /// since resources are not part of an archetype.
+    /// Component groups are treated as empty, see [`UntypedArchetypeStatement::GroupAllOf`].
+    #[inline]
+    pub fn evaluate(&self, component_ids: &ComponentIdSet) -> bool {
+        self.evaluate_with_resources(component_ids, &ComponentIdSet::new())
+    }
+
+    /// Tests if this statement is true for an entity with the provided set of [`ComponentId`]s,
+    /// in a world where exactly the `resources` exist.
+    #[inline]
+    pub fn evaluate_with_resources(
+        &self,
+        component_ids: &ComponentIdSet,
+        resources: &ComponentIdSet,
+    ) -> bool {
+        self.evaluate_components(component_ids, resources)
+    }
+
+    /// Tests if this statement is true for the entities of `archetype`.
+    ///
+    /// This gives the same result as [`UntypedArchetypeStatement::evaluate`] with the components of the archetype,
+    /// but looks each component up in the archetype instead of collecting them into a [`ComponentIdSet`],
+    /// so that it never allocates.
+    ///
+    /// ```
+    /// # use bevy_ecs::{archetype_invariants::*, prelude::*};
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Enemy;
+    ///
+    /// let mut world = World::new();
+    /// let entity = world.spawn(Player).id();
+    /// let statement = UntypedArchetypeStatement::none_of_ids([world.init_component::<Enemy>()]);
+    ///
+    /// let archetype = &world.archetypes()[world.entity(entity).location().archetype_id];
+    /// assert!(statement.evaluate_against_archetype(archetype));
+    /// ```
+    #[inline]
+    pub fn evaluate_against_archetype(&self, archetype: &Archetype) -> bool {
+        self.evaluate_components(archetype, &ComponentIdSet::new())
+    }
+
+    /// Tests if this statement is true for the entities of `archetype`, in a world where exactly the `resources` exist.
+    ///
+    /// See [`UntypedArchetypeStatement::evaluate_against_archetype`].
+    #[inline]
+    pub fn evaluate_against_archetype_with_resources(
+        &self,
+        archetype: &Archetype,
+        resources: &ComponentIdSet,
+    ) -> bool {
+        self.evaluate_components(archetype, resources)
+    }
+
+    pub(super) fn evaluate_components(
+        &self,
+        component_ids: &(impl ArchetypeComponents + ?Sized),
+        resources: &ComponentIdSet,
+    ) -> bool {
+        match self {
+            UntypedArchetypeStatement::AllOf(required) => component_ids.contains_all(required),
+            UntypedArchetypeStatement::AtLeastOneOf(desired) => component_ids.contains_any(desired),
+            UntypedArchetypeStatement::NoneOf(forbidden) => !component_ids.contains_any(forbidden),
+            UntypedArchetypeStatement::ExactlyOneOf(exclusive) => {
+                component_ids.count_common(exclusive, 2) == 1
+            }
+            UntypedArchetypeStatement::AtMostOneOf(exclusive) => {
+                component_ids.count_common(exclusive, 2) <= 1
+            }
+            UntypedArchetypeStatement::ExactlyNOf(counted, n) => {
+                component_ids.count_common(counted, n.saturating_add(1)) == *n
+            }
+            UntypedArchetypeStatement::AtLeastNOf(counted, n) => {
+                component_ids.count_common(counted, *n) == *n
+            }
+            UntypedArchetypeStatement::AtMostNOf(counted, n) => {
+                component_ids.count_common(counted, n.saturating_add(1)) <= *n
+            }
+            UntypedArchetypeStatement::Exactly(exact) => component_ids.is(exact),
+            UntypedArchetypeStatement::SubsetOf { relevant, allowed } => relevant
+                .iter()
+                .filter(|&id| !allowed.contains(id))
+                .all(|id| !component_ids.contains(id)),
+            UntypedArchetypeStatement::ComponentCount { min, max } => {
+                let count = component_ids.len();
+                count >= min.unwrap_or(0) && count <= max.unwrap_or(usize::MAX)
+            }
+            UntypedArchetypeStatement::ResourcePresent(resource) => resources.contains(*resource),
+            UntypedArchetypeStatement::HasParent(id)
+            | UntypedArchetypeStatement::HasChildren(id) => component_ids.contains(*id),
+            UntypedArchetypeStatement::StorageType { mismatched, .. } => {
+                !component_ids.contains_any(mismatched)
+            }
+            UntypedArchetypeStatement::Always => true,
+            // Groups are resolved by `ArchetypeInvariants`, and are treated as empty otherwise
+            UntypedArchetypeStatement::GroupAllOf(_) => true,
+            UntypedArchetypeStatement::GroupAtLeastOneOf(_) => false,
+            UntypedArchetypeStatement::Empty => component_ids.len() == 0,
+            UntypedArchetypeStatement::And(statements) => statements
+                .iter()
+                .all(|statement| statement.evaluate_components(component_ids, resources)),
+            UntypedArchetypeStatement::Or(statements) => statements
+                .iter()
+                .any(|statement| statement.evaluate_components(component_ids, resources)),
+            UntypedArchetypeStatement::Not(statement) => {
+                !statement.evaluate_components(component_ids, resources)
+            }
+        }
+    }
+}
+
+impl UntypedArchetypeInvariant {
+    /// Tests if this invariant holds for the entities of `archetype`,
+    /// without collecting its components into a [`ComponentIdSet`].
+    ///
+    /// See [`UntypedArchetypeStatement::evaluate_against_archetype`].
+    #[inline]
+    pub fn evaluate_against_archetype(&self, archetype: &Archetype) -> bool {
+        self.evaluate_against_archetype_with_resources(archetype, &ComponentIdSet::new())
+    }
+
+    /// Tests if this invariant holds for the entities of `archetype`, in a world where exactly the `resources` exist.
+    ///
+    /// See [`UntypedArchetypeStatement::evaluate_against_archetype`].
+    #[inline]
+    pub fn evaluate_against_archetype_with_resources(
+        &self,
+        archetype: &Archetype,
+        resources: &ComponentIdSet,
+    ) -> bool {
+        !self.predicate.evaluate_components(archetype, resources)
+            || self.consequence.evaluate_components(archetype, resources)
+    }
+}
@@ -0,0 +1,259 @@
+//! Plain-English descriptions of archetype invariants, for tooltips, log messages and reports.
+
+use std::fmt::Write;
+
+use super::{
+    component_name, present_resources, sorted_names, ArchetypeInvariant, ArchetypeInvariants,
+    ComponentIdSet, GroupId, LoweringWorld, Severity, UntypedArchetypeInvariant,
+    UntypedArchetypeStatement,
+};
+use crate::{archetype::ArchetypeId, bundle::Bundle, world::World};
+
+impl<B1: Bundle, B2: Bundle> ArchetypeInvariant<B1, B2> {
+    /// Describes what this invariant enforces in a plain-English sentence, using the names of its components.
+    ///
+    /// Components that have not been registered in `world` yet are still named by their types.
+    /// See [`UntypedArchetypeInvariant::explain`] for an example.
+    pub fn explain(&self, world: &World) -> String {
+        match self.try_into_untyped(world) {
+            Ok(invariant) => invariant.explain(world),
+            Err(_) => {
+                // The names of the components do not depend on the world they are registered in
+                let mut scratch = World::new();
+                let mut lowering = LoweringWorld::Mut(&mut scratch);
+                let invariant = UntypedArchetypeInvariant {
+                    predicate: self.predicate.lower(&mut lowering).unwrap(),
+                    consequence: self.consequence.lower(&mut lowering).unwrap(),
+                    severity: Severity::Error,
+                };
+                invariant.explain(&scratch)
+            }
+        }
+    }
+}
+
+impl UntypedArchetypeInvariant {
+    /// Describes what this invariant enforces in a plain-English sentence,
+    /// using the names of its components as registered in `world`.
+    ///
+    /// ```
+    /// # use bevy_ecs::{archetype_invariants::*, prelude::*};
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Alive;
+    /// #[derive(Component)]
+    /// struct Disabled;
+    ///
+    /// let mut world = World::new();
+    /// let invariant = ArchetypeInvariant::<(Player, Alive), (Disabled,)>::forbids()
+    ///     .into_untyped(&mut world);
+    /// assert_eq!(
+    ///     invariant.explain(&world),
+    ///     "For every entity that has all of [Alive, Player], it must also have none of [Disabled]."
+    /// );
+    /// ```
+    pub fn explain(&self, world: &World) -> String {
+        let consequence = explain_statement(&self.consequence, false, world);
+        let mut explanation = match self.predicate {
+            UntypedArchetypeStatement::Always => format!("Every entity must {consequence}."),
+            _ => format!(
+                "For every entity that {}, it must also {consequence}.",
+                explain_statement(&self.predicate, true, world)
+            ),
+        };
+        if self.severity == Severity::Warning {
+            explanation.push_str(" Violations only cause a warning.");
+        }
+        explanation
+    }
+}
+
+impl ArchetypeInvariants {
+    /// Lists every invariant with its explanation and the number of archetypes its predicate matches,
+    /// followed by every violation found in `world`, which must be the world that owns these invariants.
+    ///
+    /// See [`World::archetype_invariant_report`].
+    pub(crate) fn report(&self, world: &World) -> String {
+        let resources = present_resources(world.archetypes());
+        let archetypes: Vec<_> = world
+            .archetypes()
+            .iter()
+            .filter(|archetype| archetype.id() != ArchetypeId::RESOURCE)
+            .map(|archetype| {
+                (
+                    archetype,
+                    archetype.components().collect::<ComponentIdSet>(),
+                )
+            })
+            .collect();
+
+        let mut report = String::new();
+        let mut violations = Vec::new();
+        writeln!(report, "archetype invariants: {}", self.raw_list.len()).unwrap();
+        for stored in &self.raw_list {
+            let checked = stored.checked();
+            let matching = archetypes
+                .iter()
+                .filter(|(_, component_ids)| {
+                    checked
+                        .predicate
+                        .evaluate_with_resources(component_ids, &resources)
+                })
+                .count();
+            let status = if stored.enabled { "" } else { " (disabled)" };
+            writeln!(
+                report,
+                "invariant #{}{status}: {} [matches {matching} archetype(s)]",
+                stored.id.index(),
+                stored.invariant.explain(world)
+            )
+            .unwrap();
+
+            if !stored.enabled {
+                continue;
+            }
+            for (archetype, component_ids) in &archetypes {
+                if (!self.check_empty_archetypes && archetype.is_empty())
+                    || checked.evaluate_with_resources(component_ids, &resources)
+                {
+                    continue;
+                }
+                if let Some(violation) = stored.violation(archetype, world.components()) {
+                    violations.push((stored.id, violation));
+                }
+            }
+        }
+
+        writeln!(report, "violations: {}", violations.len()).unwrap();
+        for (id, violation) in violations {
+            writeln!(
+                report,
+                "violation of invariant #{}: {violation}",
+                id.index()
+            )
+            .unwrap();
+        }
+        report
+    }
+}
+
+/// Describes the condition of `statement` as a verb phrase about an entity, such as "has all of [A, B]".
+///
+/// If `third_person` is `true`, the verb agrees with "the entity", as in "it has";
+/// otherwise it is in its base form, as in "it must have".
+fn explain_statement(
+    statement: &UntypedArchetypeStatement,
+    third_person: bool,
+    world: &World,
+) -> String {
+    let [has, stores, lives, exists, matches] = if third_person {
+        ["has", "stores", "lives", "exists", "matches"]
+    } else {
+        ["have", "store", "live", "exist", "match"]
+    };
+    let names = |set: &ComponentIdSet| {
+        let names = sorted_names(set.iter(), &|id| component_name(world.components(), id));
+        format!("[{}]", names.join(", "))
+    };
+    let group_name = |group: &GroupId| match world.archetype_invariants.component_group_name(*group)
+    {
+        Some(name) => format!("\"{name}\""),
+        None => format!("#{}", group.index()),
+    };
+    // Compound statements are wrapped in parentheses when nested, to keep their grouping clear
+    let nested = |statement: &UntypedArchetypeStatement, third_person: bool| {
+        let explanation = explain_statement(statement, third_person, world);
+        match statement {
+            UntypedArchetypeStatement::And(statements)
+            | UntypedArchetypeStatement::Or(statements)
+                if statements.len() > 1 =>
+            {
+                format!("({explanation})")
+            }
+            _ => explanation,
+        }
+    };
+    let join = |statements: &[UntypedArchetypeStatement], conjunction: &str| {
+        statements
+            .iter()
+            .map(|statement| nested(statement, third_person))
+            .collect::<Vec<_>>()
+            .join(conjunction)
+    };
+
+    match statement {
+        UntypedArchetypeStatement::AllOf(set) => format!("{has} all of {}", names(set)),
+        UntypedArchetypeStatement::AtLeastOneOf(set) => {
+            format!("{has} at least one of {}", names(set))
+        }
+        UntypedArchetypeStatement::NoneOf(set) => format!("{has} none of {}", names(set)),
+        UntypedArchetypeStatement::ExactlyOneOf(set) => {
+            format!("{has} exactly one of {}", names(set))
+        }
+        UntypedArchetypeStatement::AtMostOneOf(set) => {
+            format!("{has} at most one of {}", names(set))
+        }
+        UntypedArchetypeStatement::ExactlyNOf(set, n) => {
+            format!("{has} exactly {n} of {}", names(set))
+        }
+        UntypedArchetypeStatement::AtLeastNOf(set, n) => {
+            format!("{has} at least {n} of {}", names(set))
+        }
+        UntypedArchetypeStatement::AtMostNOf(set, n) => {
+            format!("{has} at most {n} of {}", names(set))
+        }
+        UntypedArchetypeStatement::Exactly(set) => {
+            format!("{has} exactly the components {} and no others", names(set))
+        }
+        UntypedArchetypeStatement::SubsetOf { relevant, allowed } => format!(
+            "{has} none of {} other than {}",
+            names(relevant),
+            names(allowed)
+        ),
+        UntypedArchetypeStatement::ComponentCount { min, max } => match (min, max) {
+            (Some(min), Some(max)) => {
+                format!("{has} between {min} and {max} components in total")
+            }
+            (Some(min), None) => format!("{has} at least {min} components in total"),
+            (None, Some(max)) => format!("{has} at most {max} components in total"),
+            (None, None) => format!("{has} any number of components"),
+        },
+        UntypedArchetypeStatement::ResourcePresent(resource) => format!(
+            "{lives} in a world where the resource {} exists",
+            component_name(world.components(), *resource)
+        ),
+        UntypedArchetypeStatement::HasParent(_) => format!("{has} a parent"),
+        UntypedArchetypeStatement::HasChildren(_) => format!("{has} children"),
+        UntypedArchetypeStatement::StorageType {
+            components,
+            storage,
+            ..
+        } => format!(
+            "{stores} each of {} that it has with the {storage:?} storage type",
+            names(components)
+        ),
+        UntypedArchetypeStatement::GroupAllOf(group) => {
+            format!(
+                "{has} all of the components in the group {}",
+                group_name(group)
+            )
+        }
+        UntypedArchetypeStatement::GroupAtLeastOneOf(group) => format!(
+            "{has} at least one of the components in the group {}",
+            group_name(group)
+        ),
+        UntypedArchetypeStatement::Always => exists.to_string(),
+        UntypedArchetypeStatement::Empty => format!("{has} no components"),
+        UntypedArchetypeStatement::And(statements) if statements.is_empty() => exists.to_string(),
+        UntypedArchetypeStatement::And(statements) => join(statements, " and "),
+        UntypedArchetypeStatement::Or(statements) if statements.is_empty() => {
+            format!("{matches} nothing")
+        }
+        UntypedArchetypeStatement::Or(statements) => join(statements, " or "),
+        UntypedArchetypeStatement::Not(statement) => {
+            let prefix = if third_person { "does not" } else { "not" };
+            format!("{prefix} {}", nested(statement, false))
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Lowering of query filters such as `(With<A>, Without<B>)` into archetype statements.
+
+use std::fmt;
+
+use bevy_ecs_macros::all_tuples;
+
+use super::UntypedArchetypeStatement;
+use crate::{
+    component::Component,
+    query::{Added, Changed, Or, With, Without},
+    world::World,
+};
+
+/// A query filter that can be expressed as an [`UntypedArchetypeStatement`] about the shape of archetypes.
+///
+/// This is implemented for [`With`], which becomes [`UntypedArchetypeStatement::AllOf`],
+/// [`Without`], which becomes [`UntypedArchetypeStatement::NoneOf`],
+/// [`Or`], which becomes [`UntypedArchetypeStatement::Or`],
+/// and tuples of filters, which become [`UntypedArchetypeStatement::And`].
+///
+/// [`Added`] and [`Changed`] also implement this trait so that they can be nested in other filters,
+/// but they always return an [`UnsupportedFilterError`]:
+/// they depend on when a component was last changed, which is not part of the shape of an archetype.
+///
+/// See [`UntypedArchetypeStatement::from_filter`].
+pub trait ArchetypeFilter {
+    /// Lowers this filter into an [`UntypedArchetypeStatement`],
+    /// registering the components it refers to in `world`.
+    fn archetype_statement(
+        world: &mut World,
+    ) -> Result<UntypedArchetypeStatement, UnsupportedFilterError>;
+}
+
+impl<T: Component> ArchetypeFilter for With<T> {
+    fn archetype_statement(
+        world: &mut World,
+    ) -> Result<UntypedArchetypeStatement, UnsupportedFilterError> {
+        let id = world.init_component::<T>();
+        Ok(UntypedArchetypeStatement::all_of_ids([id]))
+    }
+}
+
+impl<T: Component> ArchetypeFilter for Without<T> {
+    fn archetype_statement(
+        world: &mut World,
+    ) -> Result<UntypedArchetypeStatement, UnsupportedFilterError> {
+        let id = world.init_component::<T>();
+        Ok(UntypedArchetypeStatement::none_of_ids([id]))
+    }
+}
+
+impl<T: Component> ArchetypeFilter for Added<T> {
+    fn archetype_statement(
+        _world: &mut World,
+    ) -> Result<UntypedArchetypeStatement, UnsupportedFilterError> {
+        Err(UnsupportedFilterError {
+            name: std::any::type_name::<Self>(),
+        })
+    }
+}
+
+impl<T: Component> ArchetypeFilter for Changed<T> {
+    fn archetype_statement(
+        _world: &mut World,
+    ) -> Result<UntypedArchetypeStatement, UnsupportedFilterError> {
+        Err(UnsupportedFilterError {
+            name: std::any::type_name::<Self>(),
+        })
+    }
+}
+
+macro_rules! impl_archetype_filter_tuple {
+    ($($filter: ident),*) => {
+        #[allow(unused_variables)]
+        impl<$($filter: ArchetypeFilter),*> ArchetypeFilter for ($($filter,)*) {
+            fn archetype_statement(
+                world: &mut World,
+            ) -> Result<UntypedArchetypeStatement, UnsupportedFilterError> {
+                Ok(UntypedArchetypeStatement::And(vec![
+                    $($filter::archetype_statement(world)?,)*
+                ]))
+            }
+        }
+
+        #[allow(unused_variables)]
+        impl<$($filter: ArchetypeFilter),*> ArchetypeFilter for Or<($($filter,)*)> {
+            fn archetype_statement(
+                world: &mut World,
+            ) -> Result<UntypedArchetypeStatement, UnsupportedFilterError> {
+                Ok(UntypedArchetypeStatement::Or(vec![
+                    $($filter::archetype_statement(world)?,)*
+                ]))
+            }
+        }
+    };
+}
+
+all_tuples!(impl_archetype_filter_tuple, 0, 15, F);
+
+impl UntypedArchetypeStatement {
+    /// Derives a statement that is true for exactly the archetypes matched by the query filter `F`,
+    /// registering the components it refers to in `world`.
+    ///
+    /// Returns an [`UnsupportedFilterError`] if `F` contains a filter with no archetype-shape equivalent,
+    /// such as [`Added`] or [`Changed`]. See [`ArchetypeFilter`] for the supported filters.
+    ///
+    /// ```
+    /// # use bevy_ecs::{archetype_invariants::*, prelude::*};
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Dead;
+    ///
+    /// let mut world = World::new();
+    /// let statement =
+    ///     UntypedArchetypeStatement::from_filter::<(With<Player>, Without<Dead>)>(&mut world)
+    ///         .unwrap();
+    /// assert_eq!(
+    ///     statement,
+    ///     UntypedArchetypeStatement::And(vec![
+    ///         UntypedArchetypeStatement::all_of_ids([world.init_component::<Player>()]),
+    ///         UntypedArchetypeStatement::none_of_ids([world.init_component::<Dead>()]),
+    ///     ])
+    /// );
+    ///
+    /// assert!(UntypedArchetypeStatement::from_filter::<Changed<Player>>(&mut world).is_err());
+    /// ```
+    pub fn from_filter<F: ArchetypeFilter>(
+        world: &mut World,
+    ) -> Result<UntypedArchetypeStatement, UnsupportedFilterError> {
+        F::archetype_statement(world)
+    }
+}
+
+/// An error returned by [`UntypedArchetypeStatement::from_filter`]
+/// when the filter depends on something other than the shape of archetypes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsupportedFilterError {
+    /// The type name of the first unsupported filter.
+    pub name: &'static str,
+}
+
+impl std::error::Error for UnsupportedFilterError {}
+
+impl fmt::Display for UnsupportedFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the query filter `{}` depends on change detection and has no archetype statement equivalent",
+            self.name
+        )
+    }
+}
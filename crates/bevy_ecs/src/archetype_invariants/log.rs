@@ -0,0 +1,26 @@
+//! Logging for archetype invariants, which can be disabled with the `archetype_invariant_logging` feature.
+//!
+//! Without the feature, these macros expand to nothing, so warnings about suspicious statements and invariants
+//! are not logged, and violations under [`ViolationPolicy::Warn`](super::ViolationPolicy::Warn)
+//! or with [`Severity::Warning`](super::Severity::Warning) are ignored.
+
+#[cfg(feature = "archetype_invariant_logging")]
+pub(crate) use bevy_utils::tracing::{debug, info, warn};
+
+#[cfg(not(feature = "archetype_invariant_logging"))]
+// The arguments are still type checked, so that they do not become unused.
+macro_rules! disabled {
+    ($($field:ident = $value:expr,)+ $message:literal) => {
+        if false {
+            $(let _ = &$value;)+
+        }
+    };
+    ($($arg:tt)+) => {
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    };
+}
+
+#[cfg(not(feature = "archetype_invariant_logging"))]
+pub(crate) use {disabled as debug, disabled as info, disabled as warn};
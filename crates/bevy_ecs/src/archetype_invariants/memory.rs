@@ -0,0 +1,232 @@
+//! Estimating and reclaiming the memory used by archetype invariants, for long-running applications.
+
+use std::{any::TypeId, borrow::Cow, mem};
+
+use super::{
+    ArchetypeInvariants, ComponentIdSet, StoredArchetypeInvariant, UntypedArchetypeInvariant,
+    UntypedArchetypeStatement,
+};
+use crate::{component::ComponentId, entity::Entity};
+
+/// Returns the number of bytes allocated for the elements of `vec`, including its unused capacity.
+fn vec_size<T>(vec: &Vec<T>) -> usize {
+    vec.capacity() * mem::size_of::<T>()
+}
+
+impl ComponentIdSet {
+    /// Returns the number of bytes this set has allocated on the heap,
+    /// which is zero while its components fit inline.
+    fn heap_size(&self) -> usize {
+        if self.0.spilled() {
+            self.0.capacity() * mem::size_of::<ComponentId>()
+        } else {
+            0
+        }
+    }
+
+    /// Shrinks the capacity of this set as much as possible, moving its components back inline if they fit.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit();
+    }
+}
+
+impl UntypedArchetypeStatement {
+    /// Returns the number of bytes this statement has allocated on the heap, including nested statements.
+    fn heap_size(&self) -> usize {
+        match self {
+            UntypedArchetypeStatement::AllOf(set)
+            | UntypedArchetypeStatement::AtLeastOneOf(set)
+            | UntypedArchetypeStatement::NoneOf(set)
+            | UntypedArchetypeStatement::ExactlyOneOf(set)
+            | UntypedArchetypeStatement::AtMostOneOf(set)
+            | UntypedArchetypeStatement::ExactlyNOf(set, _)
+            | UntypedArchetypeStatement::AtLeastNOf(set, _)
+            | UntypedArchetypeStatement::AtMostNOf(set, _)
+            | UntypedArchetypeStatement::Exactly(set) => set.heap_size(),
+            UntypedArchetypeStatement::SubsetOf { relevant, allowed } => {
+                relevant.heap_size() + allowed.heap_size()
+            }
+            UntypedArchetypeStatement::StorageType {
+                components,
+                mismatched,
+                ..
+            } => components.heap_size() + mismatched.heap_size(),
+            UntypedArchetypeStatement::And(statements)
+            | UntypedArchetypeStatement::Or(statements) => {
+                vec_size(statements)
+                    + statements
+                        .iter()
+                        .map(UntypedArchetypeStatement::heap_size)
+                        .sum::<usize>()
+            }
+            UntypedArchetypeStatement::Not(statement) => {
+                mem::size_of::<UntypedArchetypeStatement>() + statement.heap_size()
+            }
+            UntypedArchetypeStatement::ComponentCount { .. }
+            | UntypedArchetypeStatement::ResourcePresent(_)
+            | UntypedArchetypeStatement::HasParent(_)
+            | UntypedArchetypeStatement::HasChildren(_)
+            | UntypedArchetypeStatement::GroupAllOf(_)
+            | UntypedArchetypeStatement::GroupAtLeastOneOf(_)
+            | UntypedArchetypeStatement::Always
+            | UntypedArchetypeStatement::Empty => 0,
+        }
+    }
+
+    /// Shrinks the capacity of every set and list of statements in this statement as much as possible.
+    fn shrink_to_fit(&mut self) {
+        match self {
+            UntypedArchetypeStatement::AllOf(set)
+            | UntypedArchetypeStatement::AtLeastOneOf(set)
+            | UntypedArchetypeStatement::NoneOf(set)
+            | UntypedArchetypeStatement::ExactlyOneOf(set)
+            | UntypedArchetypeStatement::AtMostOneOf(set)
+            | UntypedArchetypeStatement::ExactlyNOf(set, _)
+            | UntypedArchetypeStatement::AtLeastNOf(set, _)
+            | UntypedArchetypeStatement::AtMostNOf(set, _)
+            | UntypedArchetypeStatement::Exactly(set) => set.shrink_to_fit(),
+            UntypedArchetypeStatement::SubsetOf { relevant, allowed } => {
+                relevant.shrink_to_fit();
+                allowed.shrink_to_fit();
+            }
+            UntypedArchetypeStatement::StorageType {
+                components,
+                mismatched,
+                ..
+            } => {
+                components.shrink_to_fit();
+                mismatched.shrink_to_fit();
+            }
+            UntypedArchetypeStatement::And(statements)
+            | UntypedArchetypeStatement::Or(statements) => {
+                statements.shrink_to_fit();
+                statements
+                    .iter_mut()
+                    .for_each(UntypedArchetypeStatement::shrink_to_fit);
+            }
+            UntypedArchetypeStatement::Not(statement) => statement.shrink_to_fit(),
+            UntypedArchetypeStatement::ComponentCount { .. }
+            | UntypedArchetypeStatement::ResourcePresent(_)
+            | UntypedArchetypeStatement::HasParent(_)
+            | UntypedArchetypeStatement::HasChildren(_)
+            | UntypedArchetypeStatement::GroupAllOf(_)
+            | UntypedArchetypeStatement::GroupAtLeastOneOf(_)
+            | UntypedArchetypeStatement::Always
+            | UntypedArchetypeStatement::Empty => {}
+        }
+    }
+}
+
+impl UntypedArchetypeInvariant {
+    fn heap_size(&self) -> usize {
+        self.predicate.heap_size() + self.consequence.heap_size()
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.predicate.shrink_to_fit();
+        self.consequence.shrink_to_fit();
+    }
+}
+
+impl StoredArchetypeInvariant {
+    fn heap_size(&self) -> usize {
+        let label = match &self.label {
+            Some(Cow::Owned(label)) => label.capacity(),
+            _ => 0,
+        };
+        self.invariant.heap_size()
+            + self.resolved.as_ref().map_or(0, |resolved| {
+                mem::size_of::<UntypedArchetypeInvariant>() + resolved.heap_size()
+            })
+            + label
+            + self.exempt_entities.capacity() * mem::size_of::<Entity>()
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.invariant.shrink_to_fit();
+        if let Some(resolved) = &mut self.resolved {
+            resolved.shrink_to_fit();
+        }
+        if let Some(Cow::Owned(label)) = &mut self.label {
+            label.shrink_to_fit();
+        }
+        self.exempt_entities.shrink_to_fit();
+    }
+}
+
+impl ArchetypeInvariants {
+    /// Returns an estimate of the number of bytes used by these archetype invariants, including their unused capacity.
+    ///
+    /// This is the size of [`ArchetypeInvariants`] itself, plus the capacity of each of its lists
+    /// times the size of their elements, plus the heap memory owned by the invariants,
+    /// such as the sets of components of their statements and their labels.
+    /// Hash maps are counted by their capacity only, and the violations and handler calls
+    /// that are waiting to be reported are counted without the memory they own.
+    /// Use [`ArchetypeInvariants::shrink_to_fit`] to release the unused capacity,
+    /// for example after removing many invariants.
+    pub fn memory_usage(&self) -> usize {
+        let invariants: usize = self
+            .raw_list
+            .iter()
+            .map(StoredArchetypeInvariant::heap_size)
+            .sum();
+        let bundles: usize = self
+            .bundle_component_ids
+            .values()
+            .map(ComponentIdSet::heap_size)
+            .sum();
+        let groups: usize = self
+            .component_groups
+            .iter()
+            .map(|(name, members)| {
+                let name = match name {
+                    Cow::Owned(name) => name.capacity(),
+                    Cow::Borrowed(_) => 0,
+                };
+                name + members.heap_size()
+            })
+            .sum();
+        mem::size_of::<Self>()
+            + vec_size(&self.raw_list)
+            + invariants
+            + self.bundle_component_ids.capacity() * mem::size_of::<(TypeId, ComponentIdSet)>()
+            + bundles
+            + vec_size(&self.component_groups)
+            + groups
+            + vec_size(&self.reported_removals)
+            + vec_size(&self.skipped_empty_archetypes)
+            + vec_size(&self.skipped_reenabled_archetypes)
+            + vec_size(&self.skipped_exempt_archetypes)
+            + vec_size(&self.unreported_violations)
+            + vec_size(&self.pending_handler_calls)
+            + vec_size(&self.reenabled_invariants)
+    }
+
+    /// Shrinks the capacity of every list owned by these archetype invariants as much as possible,
+    /// including the sets of components of each invariant.
+    ///
+    /// Adding and removing many invariants over the lifetime of an application can leave unused capacity behind.
+    /// This reclaims it; see [`ArchetypeInvariants::memory_usage`] to find out how much memory is in use.
+    pub fn shrink_to_fit(&mut self) {
+        self.raw_list.shrink_to_fit();
+        self.raw_list
+            .iter_mut()
+            .for_each(StoredArchetypeInvariant::shrink_to_fit);
+        self.bundle_component_ids.shrink_to_fit();
+        self.bundle_component_ids
+            .values_mut()
+            .for_each(ComponentIdSet::shrink_to_fit);
+        self.component_groups.shrink_to_fit();
+        for (_, members) in &mut self.component_groups {
+            members.shrink_to_fit();
+        }
+        self.reported_removals.shrink_to_fit();
+        self.skipped_empty_archetypes.shrink_to_fit();
+        self.skipped_reenabled_archetypes.shrink_to_fit();
+        self.skipped_exempt_archetypes.shrink_to_fit();
+        self.unreported_violations.shrink_to_fit();
+        self.pending_handler_calls.shrink_to_fit();
+        self.reenabled_invariants.shrink_to_fit();
+    }
+}
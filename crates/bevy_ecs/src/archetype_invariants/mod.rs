@@ -0,0 +1,9278 @@
+//! Types for declaring rules about which [`Component`](crate::component::Component)s can coexist on entities.
+
+mod evaluate;
+mod explain;
+mod filter;
+mod log;
+mod memory;
+mod parse;
+#[cfg(all(test, feature = "archetype_invariant_property_tests"))]
+mod property_tests;
+#[cfg(feature = "serialize")]
+mod serde;
+mod spec;
+mod subsumption;
+#[cfg(feature = "test_utils")]
+pub mod test_utils;
+
+use std::{
+    any::{Any, TypeId},
+    borrow::{Borrow, Cow},
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem,
+    ops::RangeInclusive,
+    panic::Location,
+    sync::Arc,
+};
+
+#[cfg(feature = "bevy_reflect")]
+use bevy_reflect::{FromReflect, Reflect};
+use bevy_tasks::{ComputeTaskPool, ParallelSlice, TaskPool};
+use bevy_utils::{get_short_name, HashMap, HashSet};
+use smallvec::SmallVec;
+
+use self::evaluate::ArchetypeComponents;
+use self::log::{debug, info, warn};
+use crate::{
+    archetype::{Archetype, ArchetypeId, Archetypes},
+    bundle::Bundle,
+    component::{ComponentId, Components, StorageType},
+    entity::Entity,
+    storage::Storages,
+    system::Resource,
+    world::World,
+};
+
+pub use self::filter::{ArchetypeFilter, UnsupportedFilterError};
+pub use self::parse::ArchetypeInvariantParseError;
+#[cfg(feature = "serialize")]
+pub use self::serde::*;
+pub use self::spec::{ArchetypeInvariantSpec, ArchetypeStatementSpec};
+
+/// A rule about which [`Component`](crate::component::Component)s can coexist on entities.
+///
+/// These rules must be true at all times for all entities in the [`World`].
+/// The generic [`Bundle`] type `B1` is always used in the `predicate`,
+/// while `B2` is used in the `consequence`.
+/// If only a single generic is provided, these types are the same.
+///
+/// When added to the [`World`], archetype invariants behave like [`assert!`].
+/// Archetype invariants are checked each time [`Archetypes`] is modified;
+/// this can occur on component addition, component removal, and entity spawning.
+/// These checks only see the shape of each new archetype. To also check each removal of components
+/// against the invariants that pair required components together, use [`World::set_check_archetype_invariant_removals`].
+///
+/// Each new archetype is checked once against every invariant, which takes time proportional
+/// to the number of invariants and the number of components involved.
+/// Since archetypes are created rarely, this is usually cheap,
+/// but the automatic checks can be turned off by disabling the `archetype_invariants` cargo feature.
+/// Invariants can then only be checked explicitly, using [`World::check_archetype_invariants`].
+///
+/// Note that archetype invariants are not symmetric by default.
+/// For example, an invariant with a predicate of `AllOf(B1)` and a consequence of `AllOf(B2)`
+/// means that `B1` requires `B2`, but not that `B2` requires `B1`.
+/// In this case, an entity with just `B2` is completely valid, but an entity with just `B1` is not.
+/// If symmetry is desired, repeat the invariant with the order of the types switched.
+pub struct ArchetypeInvariant<B1: Bundle, B2: Bundle = B1> {
+    /// Defines which entities this invariant applies to.
+    /// This is the "if" of the if/then clause.
+    pub predicate: ArchetypeStatement<B1>,
+    /// Defines what entities that meet the predicate must look like.
+    /// This is the "then" of the if/then clause.
+    pub consequence: ArchetypeStatement<B2>,
+}
+
+impl<B1: Bundle, B2: Bundle> ArchetypeInvariant<B1, B2> {
+    /// Erases the type information of this archetype invariant.
+    ///
+    /// Requires mutable world access, since the components might not have been added to the world yet.
+    #[inline]
+    pub fn into_untyped(self, world: &mut World) -> UntypedArchetypeInvariant {
+        UntypedArchetypeInvariant {
+            predicate: self.predicate.into_untyped(world),
+            consequence: self.consequence.into_untyped(world),
+            severity: Severity::Error,
+        }
+    }
+
+    /// Erases the type information of this archetype invariant without registering any components.
+    ///
+    /// See [`ArchetypeStatement::try_into_untyped`] for more details.
+    #[inline]
+    pub fn try_into_untyped(
+        &self,
+        world: &World,
+    ) -> Result<UntypedArchetypeInvariant, UnregisteredComponentError> {
+        Ok(UntypedArchetypeInvariant {
+            predicate: self.predicate.try_into_untyped(world)?,
+            consequence: self.consequence.try_into_untyped(world)?,
+            severity: Severity::Error,
+        })
+    }
+
+    /// Returns every entity currently in `world` that violates this invariant,
+    /// together with the details of the violation.
+    ///
+    /// This does not add the invariant to the world.
+    /// See [`UntypedArchetypeInvariant::check_world_now`] for more details.
+    pub fn check_world_now(self, world: &mut World) -> Vec<(Entity, ArchetypeInvariantViolation)> {
+        self.into_untyped(world).check_world_now(world)
+    }
+
+    /// Combines several rules with the same predicate into a single invariant,
+    /// whose consequence is the [`ArchetypeStatement::And`] of all `consequences`.
+    ///
+    /// The combined invariant is violated exactly when at least one of the separate invariants would be,
+    /// but its predicate is only evaluated once per archetype, rather than once per rule.
+    /// Violations are reported for the combined invariant as a whole.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Health;
+    /// #[derive(Component)]
+    /// struct Enemy;
+    ///
+    /// let mut world = World::new();
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(Player,), (Health, Enemy)>::combine_all(
+    ///     ArchetypeStatement::all_of(),
+    ///     vec![
+    ///         ArchetypeStatement::at_least_one_of(),
+    ///         ArchetypeStatement::at_most_one_of(),
+    ///     ],
+    /// ));
+    /// world.spawn((Player, Health));
+    /// ```
+    #[inline]
+    pub fn combine_all(
+        predicate: ArchetypeStatement<B1>,
+        consequences: Vec<ArchetypeStatement<B2>>,
+    ) -> Self {
+        Self {
+            predicate,
+            consequence: ArchetypeStatement::And(consequences),
+        }
+    }
+
+    /// Returns the negation of this invariant: where the predicate holds, the consequence must be false.
+    ///
+    /// The predicate is kept unchanged, and the consequence is wrapped in [`ArchetypeStatement::Not`].
+    /// So for every archetype matching the predicate, exactly one of this invariant and its negation holds.
+    /// Archetypes that do not match the predicate trivially uphold both.
+    ///
+    /// This is mostly useful for testing invariants: the negation should fail wherever the original passes.
+    #[inline]
+    pub fn negate(self) -> Self {
+        ArchetypeInvariant {
+            predicate: self.predicate,
+            consequence: self.consequence.not(),
+        }
+    }
+
+    /// This is a helper function for constructing common invariants.
+    /// All components of `B1` require all components of `B2`.
+    /// In other words, if every component of `B1` is present, then every component of `B2` must be too.
+    ///
+    /// # Example
+    ///
+    /// ```should_panic
+    /// # use bevy_ecs::prelude::*;
+    /// #[derive(Component)]
+    /// struct Player;
+    ///
+    /// #[derive(Component)]
+    /// struct Health;
+    ///
+    /// let mut world = World::new();
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(Player,), (Health,)>::requires());
+    ///
+    /// // Valid: `Health` does not require `Player`
+    /// world.spawn(Health);
+    /// world.spawn((Player, Health));
+    ///
+    /// // Panics: `Player` requires `Health`
+    /// world.spawn(Player);
+    /// ```
+    #[inline]
+    pub fn requires() -> Self {
+        Self {
+            predicate: ArchetypeStatement::<B1>::all_of(),
+            consequence: ArchetypeStatement::<B2>::all_of(),
+        }
+    }
+
+    /// This is a helper function for constructing common invariants.
+    /// All components of `B1` forbid all components of `B2`.
+    /// In other words, if every component of `B1` is present, then no component of `B2` may be.
+    ///
+    /// # Example
+    ///
+    /// ```should_panic
+    /// # use bevy_ecs::prelude::*;
+    /// #[derive(Component)]
+    /// struct Player;
+    ///
+    /// #[derive(Component)]
+    /// struct Enemy;
+    ///
+    /// let mut world = World::new();
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(Player,), (Enemy,)>::forbids());
+    ///
+    /// world.spawn(Player);
+    /// world.spawn(Enemy);
+    ///
+    /// // Panics: `Player` forbids `Enemy`
+    /// world.spawn((Player, Enemy));
+    /// ```
+    #[inline]
+    pub fn forbids() -> Self {
+        Self {
+            predicate: ArchetypeStatement::<B1>::all_of(),
+            consequence: ArchetypeStatement::<B2>::none_of(),
+        }
+    }
+
+    /// This is a helper function for constructing common invariants.
+    /// The components of `B1` and the components of `B2` may never be mixed on the same entity.
+    /// In other words, if any component of `B1` is present, then no component of `B2` may be.
+    ///
+    /// Unlike most invariants, this one is symmetric:
+    /// an entity with any component of `B2` and any component of `B1` violates it regardless of
+    /// which component was added first, so there is no need to also register `disjoint::<B2, B1>()`.
+    ///
+    /// # Example
+    ///
+    /// ```should_panic
+    /// # use bevy_ecs::prelude::*;
+    /// #[derive(Component)]
+    /// struct Sprite2d;
+    /// #[derive(Component)]
+    /// struct Transform2d;
+    ///
+    /// #[derive(Component)]
+    /// struct Mesh3d;
+    /// #[derive(Component)]
+    /// struct Transform3d;
+    ///
+    /// let mut world = World::new();
+    /// world.add_archetype_invariant(ArchetypeInvariant::<
+    ///     (Sprite2d, Transform2d),
+    ///     (Mesh3d, Transform3d),
+    /// >::disjoint());
+    ///
+    /// world.spawn((Sprite2d, Transform2d));
+    /// world.spawn((Mesh3d, Transform3d));
+    ///
+    /// // Panics: 2d and 3d components cannot be mixed
+    /// world.spawn((Mesh3d, Transform2d));
+    /// ```
+    #[inline]
+    pub fn disjoint() -> Self {
+        Self {
+            predicate: ArchetypeStatement::<B1>::at_least_one_of(),
+            consequence: ArchetypeStatement::<B2>::none_of(),
+        }
+    }
+}
+
+impl<B: Bundle> ArchetypeInvariant<B, B> {
+    /// Starts building an archetype invariant that applies to entities matching `predicate`.
+    ///
+    /// Call [`ArchetypeInvariantBuilder::then`] on the result to supply the consequence.
+    /// This reads in the same order as the rule itself,
+    /// so the predicate and the consequence can not be mixed up.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// #[derive(Component)]
+    /// struct Rigidbody;
+    /// #[derive(Component)]
+    /// struct Velocity;
+    ///
+    /// let mut world = World::new();
+    /// world.add_archetype_invariant(
+    ///     ArchetypeInvariant::when(ArchetypeStatement::<(Rigidbody,)>::all_of())
+    ///         .then(ArchetypeStatement::<(Velocity,)>::all_of()),
+    /// );
+    /// ```
+    #[inline]
+    pub fn when(predicate: ArchetypeStatement<B>) -> ArchetypeInvariantBuilder<B> {
+        ArchetypeInvariantBuilder { predicate }
+    }
+
+    /// This is a helper function for constructing common invariants.
+    /// All components of the provided bundle require each other.
+    /// In other words, if any one component of this bundle is present, then all of them must be.
+    ///
+    /// See [`ArchetypeInvariant::at_least_k`] to only require some of the components.
+    #[inline]
+    pub fn full_bundle() -> Self {
+        Self {
+            predicate: ArchetypeStatement::<B>::at_least_one_of(),
+            consequence: ArchetypeStatement::<B>::all_of(),
+        }
+    }
+
+    /// This is a helper function for constructing common invariants.
+    /// If any one component of the provided bundle is present, then at least `k` of them must be.
+    ///
+    /// This generalizes [`ArchetypeInvariant::full_bundle`], which is the special case
+    /// where `k` is the number of components in the bundle. A `k` of one or less is always upheld.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// #[derive(Component)]
+    /// struct Head;
+    /// #[derive(Component)]
+    /// struct Body;
+    /// #[derive(Component)]
+    /// struct Legs;
+    ///
+    /// let mut world = World::new();
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(Head, Body, Legs)>::at_least_k(2));
+    ///
+    /// world.spawn((Head, Body));
+    /// world.spawn((Body, Legs));
+    /// assert!(world.check_archetype_invariants().is_empty());
+    /// ```
+    #[inline]
+    pub fn at_least_k(k: usize) -> Self {
+        Self {
+            predicate: ArchetypeStatement::<B>::at_least_one_of(),
+            consequence: ArchetypeStatement::<B>::at_least_n_of(k),
+        }
+    }
+
+    /// This is a helper function for constructing common invariants.
+    /// The components of the provided bundle are mutually exclusive.
+    /// In other words, if any one component of this bundle is present, then none of the others may be.
+    ///
+    /// For a bundle of `N` components, this enforces the "at most one present" rule with a single invariant,
+    /// rather than one [`ArchetypeStatement::NoneOf`] invariant for each pair of components.
+    #[inline]
+    pub fn mutually_exclusive() -> Self {
+        Self {
+            predicate: ArchetypeStatement::<B>::at_least_one_of(),
+            consequence: ArchetypeStatement::<B>::at_most_one_of(),
+        }
+    }
+
+    /// This is a helper function for constructing common invariants.
+    /// Every entity must have at least one component of the provided bundle, unconditionally.
+    ///
+    /// The predicate is [`ArchetypeStatement::Always`], so this also applies to the empty archetype,
+    /// which every world contains. Disable [`World::set_check_empty_archetypes`] before adding this invariant,
+    /// unless entities are only ever spawned with their components.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Enemy;
+    ///
+    /// let mut world = World::new();
+    /// world.set_check_empty_archetypes(false);
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(Player, Enemy)>::require_at_least_one());
+    ///
+    /// world.spawn(Player);
+    /// world.spawn((Player, Enemy));
+    /// assert!(world.check_archetype_invariants().is_empty());
+    /// ```
+    #[inline]
+    pub fn require_at_least_one() -> Self {
+        Self {
+            predicate: ArchetypeStatement::<B>::always(),
+            consequence: ArchetypeStatement::<B>::at_least_one_of(),
+        }
+    }
+
+    /// This is a helper function for constructing common invariants.
+    /// No entity may have any component of the provided bundle, unconditionally.
+    ///
+    /// This is the counterpart of [`ArchetypeInvariant::full_bundle`] that bans the bundle from the world entirely,
+    /// such as to make sure that debugging components are never used in a shipped build.
+    ///
+    /// # Example
+    ///
+    /// ```should_panic
+    /// # use bevy_ecs::prelude::*;
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct DebugOverlay;
+    ///
+    /// let mut world = World::new();
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(DebugOverlay,)>::forbid_bundle());
+    ///
+    /// world.spawn(Player);
+    ///
+    /// // Panics: `DebugOverlay` is forbidden
+    /// world.spawn((Player, DebugOverlay));
+    /// ```
+    #[inline]
+    pub fn forbid_bundle() -> Self {
+        Self {
+            predicate: ArchetypeStatement::<B>::always(),
+            consequence: ArchetypeStatement::<B>::none_of(),
+        }
+    }
+
+    /// This is a helper function for constructing common invariants.
+    /// If any component of the provided bundle is present, then exactly one of the `groups` of components must be fully present.
+    ///
+    /// This models state machines whose states are made of several components, such as the groups of the components
+    /// of each state of a character. The groups are usually components of the bundle as well.
+    /// Since the groups are given by their [`ComponentId`]s, this produces an [`UntypedArchetypeInvariant`],
+    /// registering the components of the bundle in `world` if needed.
+    /// See [`UntypedArchetypeInvariant::partition`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy_ecs::{archetype_invariants::{ArchetypeInvariant, ViolationPolicy}, prelude::*};
+    /// #[derive(Component)]
+    /// struct Idle;
+    /// #[derive(Component)]
+    /// struct Walking;
+    /// #[derive(Component)]
+    /// struct Speed;
+    ///
+    /// let mut world = World::new();
+    /// world.set_archetype_invariant_policy(ViolationPolicy::Warn);
+    /// let idle = world.init_component::<Idle>();
+    /// let walking = world.init_component::<Walking>();
+    /// let speed = world.init_component::<Speed>();
+    /// let invariant = ArchetypeInvariant::<(Idle, Walking, Speed)>::partition(
+    ///     &[&[idle], &[walking, speed]],
+    ///     &mut world,
+    /// );
+    /// world.add_untyped_archetype_invariant(invariant);
+    ///
+    /// world.spawn(Idle);
+    /// world.spawn((Walking, Speed));
+    /// assert!(world.check_archetype_invariants().is_empty());
+    /// world.spawn((Idle, Walking, Speed));
+    /// assert_eq!(world.check_archetype_invariants().len(), 1);
+    /// ```
+    pub fn partition(groups: &[&[ComponentId]], world: &mut World) -> UntypedArchetypeInvariant {
+        let component_ids = ArchetypeStatement::<B>::bundle_component_ids(world);
+        UntypedArchetypeInvariant::partition(component_ids.iter(), groups)
+    }
+}
+
+impl<B1: Bundle, B2: Bundle> PartialEq for ArchetypeInvariant<B1, B2> {
+    fn eq(&self, other: &Self) -> bool {
+        self.predicate == other.predicate && self.consequence == other.consequence
+    }
+}
+
+impl<B1: Bundle, B2: Bundle> Eq for ArchetypeInvariant<B1, B2> {}
+
+impl<B1: Bundle, B2: Bundle> Hash for ArchetypeInvariant<B1, B2> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.predicate.hash(state);
+        self.consequence.hash(state);
+    }
+}
+
+/// A partially constructed [`ArchetypeInvariant`], which has a predicate but no consequence yet.
+///
+/// Created by [`ArchetypeInvariant::when`].
+pub struct ArchetypeInvariantBuilder<B1: Bundle> {
+    predicate: ArchetypeStatement<B1>,
+}
+
+impl<B1: Bundle> ArchetypeInvariantBuilder<B1> {
+    /// Completes the archetype invariant:
+    /// all entities that match the predicate must also match `consequence`.
+    #[inline]
+    pub fn then<B2: Bundle>(
+        self,
+        consequence: ArchetypeStatement<B2>,
+    ) -> ArchetypeInvariant<B1, B2> {
+        ArchetypeInvariant {
+            predicate: self.predicate,
+            consequence,
+        }
+    }
+
+    /// Completes the archetype invariant, requiring `consequence` of all entities that match the predicate.
+    ///
+    /// This is identical to [`ArchetypeInvariantBuilder::then`].
+    #[inline]
+    pub fn require<B2: Bundle>(
+        self,
+        consequence: ArchetypeStatement<B2>,
+    ) -> ArchetypeInvariant<B1, B2> {
+        self.then(consequence)
+    }
+}
+
+/// Constructs an [`ArchetypeInvariant`] from a predicate and a consequence, written as `predicate => consequence`.
+///
+/// Each side is one of `all_of(...)`, `at_least_one_of(...)` or `none_of(...)`,
+/// listing the component types of the corresponding [`ArchetypeStatement`].
+///
+/// # Example
+///
+/// ```
+/// use bevy_ecs::{archetype_invariant, prelude::*};
+///
+/// #[derive(Component)]
+/// struct Player;
+/// #[derive(Component)]
+/// struct Health;
+/// #[derive(Component)]
+/// struct Enemy;
+///
+/// let mut world = World::new();
+/// // Equivalent to `ArchetypeInvariant::<(Player,), (Health,)>::requires()`
+/// world.add_archetype_invariant(archetype_invariant!(all_of(Player) => all_of(Health)));
+/// world.add_archetype_invariant(archetype_invariant!(all_of(Player, Health) => none_of(Enemy)));
+/// ```
+#[macro_export]
+macro_rules! archetype_invariant {
+    (@statement all_of($($component:ty),+)) => {
+        $crate::archetype_invariants::ArchetypeStatement::<($($component,)+)>::all_of()
+    };
+    (@statement at_least_one_of($($component:ty),+)) => {
+        $crate::archetype_invariants::ArchetypeStatement::<($($component,)+)>::at_least_one_of()
+    };
+    (@statement none_of($($component:ty),+)) => {
+        $crate::archetype_invariants::ArchetypeStatement::<($($component,)+)>::none_of()
+    };
+    (@statement $statement:ident($($component:ty),+)) => {
+        compile_error!(concat!(
+            "unknown archetype statement `",
+            stringify!($statement),
+            "`, expected `all_of`, `at_least_one_of` or `none_of`"
+        ))
+    };
+    (
+        $predicate:ident($($predicate_component:ty),+ $(,)?)
+        => $consequence:ident($($consequence_component:ty),+ $(,)?)
+    ) => {
+        $crate::archetype_invariants::ArchetypeInvariant::<
+            ($($predicate_component,)+),
+            ($($consequence_component,)+),
+        > {
+            predicate: $crate::archetype_invariant!(@statement $predicate($($predicate_component),+)),
+            consequence: $crate::archetype_invariant!(@statement $consequence($($consequence_component),+)),
+        }
+    };
+}
+
+/// Constructs an [`ArchetypeStatement`] that is true for entities that have all of the components
+/// of at least one of the given bundles, written as `any_of!((A, B), (C, D))`.
+///
+/// This is an [`ArchetypeStatement::Or`] of one [`ArchetypeStatement::AllOf`] for each bundle.
+/// The statement is about the components of the first bundle, and the others are combined with it
+/// using [`ArchetypeStatement::or_statement`].
+///
+/// # Example
+///
+/// ```
+/// use bevy_ecs::{any_of, archetype_invariants::ArchetypeInvariant, prelude::*};
+///
+/// #[derive(Component)]
+/// struct Player;
+/// #[derive(Component)]
+/// struct Sword;
+/// #[derive(Component)]
+/// struct Shield;
+/// #[derive(Component)]
+/// struct Bow;
+///
+/// let mut world = World::new();
+/// // Every player is either a knight or an archer
+/// world.add_archetype_invariant(ArchetypeInvariant {
+///     predicate: ArchetypeStatement::<(Player,)>::all_of(),
+///     consequence: any_of!((Sword, Shield), (Bow,)),
+/// });
+/// world.spawn((Player, Sword, Shield));
+/// world.spawn((Player, Bow));
+/// ```
+#[macro_export]
+macro_rules! any_of {
+    (($($first:ty),+ $(,)?) $(, ($($component:ty),+ $(,)?))* $(,)?) => {
+        $crate::archetype_invariants::ArchetypeStatement::<($($first,)+)>::Or(vec![
+            $crate::archetype_invariants::ArchetypeStatement::all_of(),
+        ])
+        $(.or_statement(
+            $crate::archetype_invariants::ArchetypeStatement::<($($component,)+)>::all_of()
+        ))*
+    };
+}
+
+/// A statement about the presence or absence of some subset of components in the given [`Bundle`].
+///
+/// This type is used as part of an [`ArchetypeInvariant`].
+///
+/// When used as a predicate, the archetype invariant matches all entities which satisfy the statement.
+/// When used as a consequence, then the statement must be true for all entities that were matched by the predicate.
+///
+/// For the statements about a single component `C`, wrap it in a single-component bundle `(C,)`.
+/// For single component bundles, `AllOf`, `AtLeastOneOf` and `ExactlyOneOf` are equivalent,
+/// while `AtMostOneOf` is always true.
+/// Prefer `ArchetypeStatement::<(C,)>::all_of` for consistency and clarity.
+///
+/// Note that this is converted to an [`UntypedArchetypeStatement`] when added to a [`World`].
+/// This is to ensure compatibility between different invariants.
+pub enum ArchetypeStatement<B: Bundle> {
+    /// Evaluates to true if and only if the entity has all of the components present in the bundle `B`.
+    AllOf(PhantomData<B>),
+    /// The entity has at least one component in the bundle `B`.
+    /// When using a single-component bundle, `AllOf` is preferred.
+    AtLeastOneOf(PhantomData<B>),
+    /// The entity has none of the components in the bundle `B`.
+    ///
+    /// For multi-component bundles, this forbids each of the components individually,
+    /// not just the combination of all of them. Use `AllOf` wrapped in `Not` to forbid only the combination.
+    NoneOf(PhantomData<B>),
+    /// The entity has exactly one of the components in the bundle `B`.
+    /// When using a single-component bundle, `AllOf` is preferred.
+    ExactlyOneOf(PhantomData<B>),
+    /// The entity has at most one of the components in the bundle `B`, and may have none of them.
+    AtMostOneOf(PhantomData<B>),
+    /// The entity has exactly `n` of the components in the bundle `B`.
+    ExactlyNOf(PhantomData<B>, usize),
+    /// The entity has at least `n` of the components in the bundle `B`.
+    AtLeastNOf(PhantomData<B>, usize),
+    /// The entity has at most `n` of the components in the bundle `B`.
+    AtMostNOf(PhantomData<B>, usize),
+    /// The entity has every component in the bundle `B`, and no other components.
+    ///
+    /// As a consequence, this enforces a closed shape: components unrelated to the invariant are forbidden too.
+    Exactly(PhantomData<B>),
+    /// Of the `relevant` components, the entity only has ones that are also in the bundle `B`.
+    ///
+    /// In other words, the bundle `B` is a whitelist: the relevant components the entity has are a subset of `B`.
+    /// Components that are not relevant are ignored, and the entity is not required to have any component of `B`.
+    /// `relevant` returns the [`ComponentId`]s of the relevant components, initializing them if needed;
+    /// construct this with [`ArchetypeStatement::subset_of`] to use the components of a bundle.
+    SubsetOf {
+        /// Returns the components that are restricted by the whitelist.
+        relevant: fn(&mut World) -> ComponentIdSet,
+        /// Returns the same components as `relevant` without registering them,
+        /// used by [`ArchetypeStatement::try_into_untyped`].
+        try_relevant: fn(&World) -> Result<ComponentIdSet, UnregisteredComponentError>,
+        /// The whitelist.
+        allowed: PhantomData<B>,
+    },
+    /// The entity has at least `min` and at most `max` components in total, counting every component it has.
+    /// A bound of `None` is not checked.
+    ///
+    /// This does not refer to the components in the bundle `B`. It is mostly useful as a predicate,
+    /// for example to require a marker component on entities with an unusually large number of components.
+    /// As a consequence, it is unusual: it limits the total number of components of an entity,
+    /// including ones unrelated to the invariant.
+    ComponentCount {
+        /// The minimum number of components, inclusive.
+        min: Option<usize>,
+        /// The maximum number of components, inclusive.
+        max: Option<usize>,
+    },
+    /// Evaluates to true if the resource whose [`ComponentId`] is returned by the functions exists in the world.
+    /// The second function must return the same id as the first, without registering the resource.
+    ///
+    /// This does not refer to the components in the bundle `B`, or to the entity at all.
+    /// Construct it with [`ArchetypeStatement::resource_present`].
+    /// See [`UntypedArchetypeStatement::ResourcePresent`] for more details.
+    ResourcePresent(
+        fn(&mut World) -> ComponentId,
+        fn(&World) -> Result<ComponentId, UnregisteredComponentError>,
+    ),
+    /// Each component in the bundle `B` that the entity has is stored with the given [`StorageType`].
+    ///
+    /// Construct it with [`ArchetypeStatement::storage_type`].
+    /// See [`UntypedArchetypeStatement::StorageType`] for more details.
+    StorageType(PhantomData<B>, StorageType),
+    /// Evaluates to true for every entity.
+    ///
+    /// As a predicate, this makes the consequence apply to all entities.
+    /// This does not refer to the components in the bundle `B`.
+    Always,
+    /// Evaluates to true only for entities without any components.
+    ///
+    /// This does not refer to the components in the bundle `B`.
+    Empty,
+    /// Evaluates to true if and only if all of the contained statements are true.
+    /// Evaluates to true if there are no contained statements.
+    And(Vec<ArchetypeStatement<B>>),
+    /// Evaluates to true if and only if at least one of the contained statements is true.
+    /// Evaluates to false if there are no contained statements.
+    Or(Vec<ArchetypeStatement<B>>),
+    /// Evaluates to true if and only if the contained statement is false.
+    Not(Box<ArchetypeStatement<B>>),
+    /// Evaluates to true if and only if the contained statement about the components of some other bundle is true.
+    ///
+    /// This allows [`ArchetypeStatement::And`] and [`ArchetypeStatement::Or`] to combine statements about different bundles.
+    /// Construct it with [`ArchetypeStatement::and_statement`] or [`ArchetypeStatement::or_statement`].
+    Foreign(ForeignArchetypeStatement),
+}
+
+impl<B: Bundle> ArchetypeStatement<B> {
+    /// Erases the type information of this archetype statement.
+    ///
+    /// Requires mutable world access, since the components might not have been added to the world yet.
+    pub fn into_untyped(self, world: &mut World) -> UntypedArchetypeStatement {
+        match self.lower(&mut LoweringWorld::Mut(world)) {
+            Ok(statement) => statement,
+            Err(_) => {
+                unreachable!("components are registered while lowering with mutable world access")
+            }
+        }
+    }
+
+    /// Erases the type information of this archetype statement without registering any components.
+    ///
+    /// Unlike [`ArchetypeStatement::into_untyped`], this only requires shared world access,
+    /// so it can be used in contexts without exclusive access, as long as every component
+    /// (and resource) it refers to has already been registered in the world.
+    /// Returns an error naming the first component that has not been registered.
+    pub fn try_into_untyped(
+        &self,
+        world: &World,
+    ) -> Result<UntypedArchetypeStatement, UnregisteredComponentError> {
+        self.lower(&mut LoweringWorld::Ref(world))
+    }
+
+    fn lower(
+        &self,
+        world: &mut LoweringWorld,
+    ) -> Result<UntypedArchetypeStatement, UnregisteredComponentError> {
+        Ok(match self {
+            ArchetypeStatement::AllOf(_) => {
+                UntypedArchetypeStatement::AllOf(world.bundle_component_ids::<B>()?)
+            }
+            ArchetypeStatement::AtLeastOneOf(_) => {
+                let component_ids = world.bundle_component_ids::<B>()?;
+                if component_ids.len() == 1 {
+                    warn!("An `ArchetypeStatement::AtLeastOneOf` was constructed for a bundle with only one component. Prefer the equivalent `ArchetypeStatement::AllOf` for consistency and clarity.");
+                }
+                UntypedArchetypeStatement::AtLeastOneOf(component_ids)
+            }
+            ArchetypeStatement::NoneOf(_) => {
+                let component_ids = world.bundle_component_ids::<B>()?;
+                if component_ids.len() > 1 && world.is_strict() {
+                    warn!("An `ArchetypeStatement::NoneOf` was constructed for a bundle with {} components. This forbids each of the components individually; to only forbid all of them together, use `ArchetypeStatement::AllOf` wrapped in `ArchetypeStatement::Not`.", component_ids.len());
+                }
+                UntypedArchetypeStatement::NoneOf(component_ids)
+            }
+            ArchetypeStatement::ExactlyOneOf(_) => {
+                let component_ids = world.bundle_component_ids::<B>()?;
+                if component_ids.len() == 1 {
+                    warn!("An `ArchetypeStatement::ExactlyOneOf` was constructed for a bundle with only one component. Prefer the equivalent `ArchetypeStatement::AllOf` for consistency and clarity.");
+                }
+                UntypedArchetypeStatement::ExactlyOneOf(component_ids)
+            }
+            ArchetypeStatement::AtMostOneOf(_) => {
+                let component_ids = world.bundle_component_ids::<B>()?;
+                if component_ids.len() == 1 {
+                    warn!("An `ArchetypeStatement::AtMostOneOf` was constructed for a bundle with only one component. This statement is always true.");
+                }
+                UntypedArchetypeStatement::AtMostOneOf(component_ids)
+            }
+            ArchetypeStatement::ExactlyNOf(_, n) => {
+                let n = *n;
+                let component_ids = world.bundle_component_ids::<B>()?;
+                if n > component_ids.len() {
+                    warn!("An `ArchetypeStatement::ExactlyNOf` was constructed requiring {n} components from a bundle with only {} components. This statement can never be true.", component_ids.len());
+                }
+                UntypedArchetypeStatement::ExactlyNOf(component_ids, n)
+            }
+            ArchetypeStatement::AtLeastNOf(_, n) => {
+                let n = *n;
+                let component_ids = world.bundle_component_ids::<B>()?;
+                if n > component_ids.len() {
+                    warn!("An `ArchetypeStatement::AtLeastNOf` was constructed requiring {n} components from a bundle with only {} components. This statement can never be true.", component_ids.len());
+                }
+                UntypedArchetypeStatement::AtLeastNOf(component_ids, n)
+            }
+            ArchetypeStatement::AtMostNOf(_, n) => {
+                UntypedArchetypeStatement::AtMostNOf(world.bundle_component_ids::<B>()?, *n)
+            }
+            ArchetypeStatement::Exactly(_) => {
+                UntypedArchetypeStatement::Exactly(world.bundle_component_ids::<B>()?)
+            }
+            ArchetypeStatement::SubsetOf {
+                relevant,
+                try_relevant,
+                ..
+            } => {
+                let relevant = world.relevant(*relevant, *try_relevant)?;
+                let allowed = world.bundle_component_ids::<B>()?;
+                if relevant.is_subset(&allowed) {
+                    warn!("An `ArchetypeStatement::SubsetOf` was constructed where every relevant component is allowed. This statement is always true.");
+                }
+                UntypedArchetypeStatement::SubsetOf { relevant, allowed }
+            }
+            ArchetypeStatement::ComponentCount { min, max } => {
+                if let (Some(min), Some(max)) = (*min, *max) {
+                    if min > max {
+                        warn!("An `ArchetypeStatement::ComponentCount` was constructed with a minimum of {min} components, which is more than its maximum of {max} components. This statement can never be true.");
+                    }
+                }
+                UntypedArchetypeStatement::ComponentCount {
+                    min: *min,
+                    max: *max,
+                }
+            }
+            ArchetypeStatement::ResourcePresent(resource_id, try_resource_id) => {
+                UntypedArchetypeStatement::ResourcePresent(
+                    world.resource_id(*resource_id, *try_resource_id)?,
+                )
+            }
+            ArchetypeStatement::StorageType(_, storage) => {
+                let component_ids = world.bundle_component_ids::<B>()?;
+                UntypedArchetypeStatement::storage_type_ids(
+                    component_ids,
+                    *storage,
+                    world.components(),
+                )
+            }
+            ArchetypeStatement::Always => UntypedArchetypeStatement::Always,
+            ArchetypeStatement::Empty => UntypedArchetypeStatement::Empty,
+            ArchetypeStatement::And(statements) => UntypedArchetypeStatement::And(
+                statements
+                    .iter()
+                    .map(|statement| statement.lower(world))
+                    .collect::<Result<_, _>>()?,
+            ),
+            ArchetypeStatement::Or(statements) => UntypedArchetypeStatement::Or(
+                statements
+                    .iter()
+                    .map(|statement| statement.lower(world))
+                    .collect::<Result<_, _>>()?,
+            ),
+            ArchetypeStatement::Not(statement) => {
+                UntypedArchetypeStatement::Not(Box::new(statement.lower(world)?))
+            }
+            ArchetypeStatement::Foreign(statement) => statement.0.lower(world)?,
+        })
+    }
+
+    /// Returns the set of [`ComponentId`]s of the components in the bundle `B`, initializing them if needed.
+    fn bundle_component_ids(world: &mut World) -> ComponentIdSet {
+        world
+            .archetype_invariants
+            .bundle_component_ids::<B>(&mut world.components, &mut world.storages)
+            .clone()
+    }
+
+    /// Returns the set of [`ComponentId`]s of the components in the bundle `B`,
+    /// or an error if any of them has not been registered.
+    fn try_bundle_component_ids(
+        world: &World,
+    ) -> Result<ComponentIdSet, UnregisteredComponentError> {
+        let mut component_ids = ComponentIdSet::new();
+        let mut unregistered = None;
+        B::component_type_ids(
+            &mut |type_id, name| match world.components().get_id(type_id) {
+                Some(id) => {
+                    component_ids.insert(id);
+                }
+                None => {
+                    unregistered.get_or_insert(name);
+                }
+            },
+        );
+        match unregistered {
+            Some(name) => Err(UnregisteredComponentError { name }),
+            None => Ok(component_ids),
+        }
+    }
+
+    /// Constructs a new [`ArchetypeStatement::AllOf`] variant for all components stored in the bundle `B`.
+    #[inline]
+    pub const fn all_of() -> Self {
+        ArchetypeStatement::AllOf(PhantomData)
+    }
+
+    /// Constructs a new [`ArchetypeStatement::AtLeastOneOf`] variant for all components stored in the bundle `B`.
+    #[inline]
+    pub const fn at_least_one_of() -> Self {
+        ArchetypeStatement::AtLeastOneOf(PhantomData)
+    }
+
+    /// Constructs a new [`ArchetypeStatement::NoneOf`] variant for all components stored in the bundle `B`.
+    ///
+    /// This is only true for entities that have none of the components at all:
+    /// having any single one of them is enough to make it false.
+    /// If the intent is to forbid the combination of all the components, use `all_of().not()` instead.
+    /// When the strict mode of [`ArchetypeInvariants`] is enabled,
+    /// a warning is logged for every `NoneOf` statement with more than one component to confirm this intent.
+    ///
+    /// ```
+    /// # use bevy_ecs::{archetype_invariants::{ArchetypeStatement, ComponentIdSet}, prelude::*};
+    /// #[derive(Component)]
+    /// struct A;
+    /// #[derive(Component)]
+    /// struct B;
+    ///
+    /// let mut world = World::new();
+    /// let none_of = ArchetypeStatement::<(A, B)>::none_of().into_untyped(&mut world);
+    /// let not_all_of = ArchetypeStatement::<(A, B)>::all_of().not().into_untyped(&mut world);
+    /// let a = world.component_id::<A>().unwrap();
+    /// let b = world.component_id::<B>().unwrap();
+    ///
+    /// let just_a: ComponentIdSet = [a].into_iter().collect();
+    /// let both: ComponentIdSet = [a, b].into_iter().collect();
+    /// // `NoneOf` forbids `A` on its own
+    /// assert!(!none_of.evaluate(&just_a));
+    /// assert!(!none_of.evaluate(&both));
+    /// // Negating `AllOf` only forbids the combination
+    /// assert!(not_all_of.evaluate(&just_a));
+    /// assert!(!not_all_of.evaluate(&both));
+    /// ```
+    #[inline]
+    pub const fn none_of() -> Self {
+        ArchetypeStatement::NoneOf(PhantomData)
+    }
+
+    /// Constructs a new [`ArchetypeStatement::ExactlyOneOf`] variant for all components stored in the bundle `B`.
+    #[inline]
+    pub const fn exactly_one_of() -> Self {
+        ArchetypeStatement::ExactlyOneOf(PhantomData)
+    }
+
+    /// Constructs a new [`ArchetypeStatement::AtMostOneOf`] variant for all components stored in the bundle `B`.
+    #[inline]
+    pub const fn at_most_one_of() -> Self {
+        ArchetypeStatement::AtMostOneOf(PhantomData)
+    }
+
+    /// Constructs a new [`ArchetypeStatement::ExactlyNOf`] variant for all components stored in the bundle `B`.
+    #[inline]
+    pub const fn exactly_n_of(n: usize) -> Self {
+        ArchetypeStatement::ExactlyNOf(PhantomData, n)
+    }
+
+    /// Constructs a new [`ArchetypeStatement::AtLeastNOf`] variant for all components stored in the bundle `B`.
+    #[inline]
+    pub const fn at_least_n_of(n: usize) -> Self {
+        ArchetypeStatement::AtLeastNOf(PhantomData, n)
+    }
+
+    /// Constructs a new [`ArchetypeStatement::AtMostNOf`] variant for all components stored in the bundle `B`.
+    #[inline]
+    pub const fn at_most_n_of(n: usize) -> Self {
+        ArchetypeStatement::AtMostNOf(PhantomData, n)
+    }
+
+    /// Constructs a new [`ArchetypeStatement::ComponentCount`] variant,
+    /// which is true for entities with at least `min` and at most `max` components in total.
+    ///
+    /// The bundle `B` is not used, so this can be combined with statements about any bundle.
+    ///
+    /// ```
+    /// # use bevy_ecs::{archetype_invariants::{ArchetypeInvariant, ArchetypeStatement}, prelude::*};
+    /// #[derive(Component)]
+    /// struct ComplexEntity;
+    ///
+    /// // Any entity with more than 16 components must be marked as a `ComplexEntity`
+    /// let invariant = ArchetypeInvariant::<(ComplexEntity,)> {
+    ///     predicate: ArchetypeStatement::component_count(Some(17), None),
+    ///     consequence: ArchetypeStatement::all_of(),
+    /// };
+    /// ```
+    #[inline]
+    pub const fn component_count(min: Option<usize>, max: Option<usize>) -> Self {
+        ArchetypeStatement::ComponentCount { min, max }
+    }
+
+    /// Constructs a new [`ArchetypeStatement::Exactly`] variant, which is true only for entities
+    /// with every component in the bundle `B` and nothing else.
+    ///
+    /// ```should_panic
+    /// # use bevy_ecs::{archetype_invariants::{ArchetypeInvariant, ArchetypeStatement}, prelude::*};
+    /// #[derive(Component)]
+    /// struct Camera;
+    /// #[derive(Component)]
+    /// struct Transform;
+    /// #[derive(Component)]
+    /// struct Sprite;
+    ///
+    /// let mut world = World::new();
+    /// // The camera entity must be made of exactly these components
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(Camera,), (Camera, Transform)> {
+    ///     predicate: ArchetypeStatement::all_of(),
+    ///     consequence: ArchetypeStatement::exactly(),
+    /// });
+    /// world.spawn((Camera, Transform));
+    ///
+    /// // This panics
+    /// world.spawn((Camera, Transform, Sprite));
+    /// ```
+    #[inline]
+    pub const fn exactly() -> Self {
+        ArchetypeStatement::Exactly(PhantomData)
+    }
+
+    /// Constructs a new [`ArchetypeStatement::SubsetOf`] variant, which is true if the components of the bundle `R`
+    /// that the entity has are all in the bundle `B`.
+    ///
+    /// For the opposite direction, where the entity must have every component of `B`, use [`ArchetypeStatement::all_of`].
+    ///
+    /// ```should_panic
+    /// # use bevy_ecs::{archetype_invariants::{ArchetypeInvariant, ArchetypeStatement}, prelude::*};
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Sword;
+    /// #[derive(Component)]
+    /// struct Shield;
+    /// #[derive(Component)]
+    /// struct Bow;
+    ///
+    /// let mut world = World::new();
+    /// // Players may carry a sword and a shield, but no other weapons
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(Player,), (Sword, Shield)> {
+    ///     predicate: ArchetypeStatement::all_of(),
+    ///     consequence: ArchetypeStatement::subset_of::<(Sword, Shield, Bow)>(),
+    /// });
+    /// world.spawn((Player, Sword));
+    ///
+    /// // This panics
+    /// world.spawn((Player, Bow));
+    /// ```
+    #[inline]
+    pub const fn subset_of<R: Bundle>() -> Self {
+        ArchetypeStatement::SubsetOf {
+            relevant: ArchetypeStatement::<R>::bundle_component_ids,
+            try_relevant: ArchetypeStatement::<R>::try_bundle_component_ids,
+            allowed: PhantomData,
+        }
+    }
+
+    /// Constructs a new [`ArchetypeStatement::ResourcePresent`] variant,
+    /// which is true while the resource `R` exists in the world.
+    ///
+    /// This should only be used in predicates, to make an invariant only apply while the resource exists.
+    ///
+    /// ```should_panic
+    /// # use bevy_ecs::{archetype_invariants::{ArchetypeInvariant, ArchetypeStatement}, prelude::*};
+    /// #[derive(Resource)]
+    /// struct NetworkConfig;
+    /// #[derive(Component)]
+    /// struct Networked;
+    /// #[derive(Component)]
+    /// struct Replicated;
+    ///
+    /// let mut world = World::new();
+    /// // While the game is networked, networked entities must be replicated
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(Networked,), (Replicated,)> {
+    ///     predicate: ArchetypeStatement::resource_present::<NetworkConfig>()
+    ///         .and(ArchetypeStatement::all_of()),
+    ///     consequence: ArchetypeStatement::all_of(),
+    /// });
+    /// world.spawn(Networked);
+    ///
+    /// // This panics, since the entity spawned above is now in violation
+    /// world.insert_resource(NetworkConfig);
+    /// ```
+    #[inline]
+    pub const fn resource_present<R: Resource>() -> Self {
+        ArchetypeStatement::ResourcePresent(resource_id::<R>, try_resource_id::<R>)
+    }
+
+    /// Constructs a new [`ArchetypeStatement::StorageType`] variant,
+    /// which is true if each component in the bundle `B` that the entity has is stored with the given [`StorageType`].
+    ///
+    /// This is an advanced invariant, intended for performance tuning:
+    /// for example, to catch components that are frequently added and removed, but were not marked as sparse set components.
+    ///
+    /// ```
+    /// # use bevy_ecs::{archetype_invariants::*, component::StorageType, prelude::*};
+    /// #[derive(Component)]
+    /// #[component(storage = "SparseSet")]
+    /// struct Stunned;
+    /// #[derive(Component)]
+    /// struct Slowed;
+    ///
+    /// let mut world = World::new();
+    /// // Status effects change often, so they should avoid moving entities between tables
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(), (Stunned, Slowed)> {
+    ///     predicate: ArchetypeStatement::always(),
+    ///     consequence: ArchetypeStatement::storage_type(StorageType::SparseSet),
+    /// });
+    /// world.spawn(Stunned);
+    /// ```
+    #[inline]
+    pub const fn storage_type(storage: StorageType) -> Self {
+        ArchetypeStatement::StorageType(PhantomData, storage)
+    }
+
+    /// Constructs a new [`ArchetypeStatement::Always`] variant, which is true for every entity.
+    ///
+    /// As a predicate, this states that the consequence must hold for all entities.
+    #[inline]
+    pub const fn always() -> Self {
+        ArchetypeStatement::Always
+    }
+
+    /// Constructs a new [`ArchetypeStatement::Empty`] variant, which is true only for entities without components.
+    #[inline]
+    pub const fn empty() -> Self {
+        ArchetypeStatement::Empty
+    }
+
+    /// Combines this statement with `other` into an [`ArchetypeStatement::And`],
+    /// which is true only if both statements are true.
+    ///
+    /// Chained calls are flattened into a single [`ArchetypeStatement::And`].
+    #[inline]
+    pub fn and(self, other: Self) -> Self {
+        match self {
+            ArchetypeStatement::And(mut statements) => {
+                statements.push(other);
+                ArchetypeStatement::And(statements)
+            }
+            _ => ArchetypeStatement::And(vec![self, other]),
+        }
+    }
+
+    /// Combines this statement with `other` into an [`ArchetypeStatement::Or`],
+    /// which is true if either statement is true.
+    ///
+    /// Chained calls are flattened into a single [`ArchetypeStatement::Or`].
+    #[inline]
+    pub fn or(self, other: Self) -> Self {
+        match self {
+            ArchetypeStatement::Or(mut statements) => {
+                statements.push(other);
+                ArchetypeStatement::Or(statements)
+            }
+            _ => ArchetypeStatement::Or(vec![self, other]),
+        }
+    }
+
+    /// Like [`ArchetypeStatement::and`], but `other` may be about the components of a different bundle `B2`.
+    ///
+    /// `other` is wrapped in an [`ArchetypeStatement::Foreign`], and both statements are lowered together
+    /// when the result is converted into an [`UntypedArchetypeStatement`].
+    #[inline]
+    pub fn and_statement<B2: Bundle>(self, other: ArchetypeStatement<B2>) -> Self {
+        self.and(ArchetypeStatement::Foreign(ForeignArchetypeStatement(
+            Box::new(other),
+        )))
+    }
+
+    /// Like [`ArchetypeStatement::or`], but `other` may be about the components of a different bundle `B2`.
+    ///
+    /// For example, `ArchetypeStatement::<(A, B)>::all_of().or_statement(ArchetypeStatement::<(C,)>::none_of())`
+    /// is true for entities that have both `A` and `B`, or that do not have `C`.
+    /// `other` is wrapped in an [`ArchetypeStatement::Foreign`], and both statements are lowered together
+    /// when the result is converted into an [`UntypedArchetypeStatement`].
+    #[inline]
+    pub fn or_statement<B2: Bundle>(self, other: ArchetypeStatement<B2>) -> Self {
+        self.or(ArchetypeStatement::Foreign(ForeignArchetypeStatement(
+            Box::new(other),
+        )))
+    }
+
+    /// Wraps this statement in an [`ArchetypeStatement::Not`],
+    /// which is true only if this statement is false.
+    ///
+    /// For example, `ArchetypeStatement::<(A, B)>::all_of().not()` is true
+    /// for archetypes that are missing at least one of `A` or `B`.
+    #[inline]
+    // `!` would hide the intent of an invariant
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        ArchetypeStatement::Not(Box::new(self))
+    }
+}
+
+// Implemented by hand, since deriving these traits would require `B` to implement them as well.
+// The functions in `SubsetOf` and `ResourcePresent` are compared by address.
+// They are instantiated once per type, so statements about the same types usually compare equal,
+// but the compiler may emit duplicate instances across codegen units. Untyped statements have no such caveat.
+impl<B: Bundle> PartialEq for ArchetypeStatement<B> {
+    fn eq(&self, other: &Self) -> bool {
+        use ArchetypeStatement::*;
+        match (self, other) {
+            (AllOf(_), AllOf(_))
+            | (AtLeastOneOf(_), AtLeastOneOf(_))
+            | (NoneOf(_), NoneOf(_))
+            | (ExactlyOneOf(_), ExactlyOneOf(_))
+            | (AtMostOneOf(_), AtMostOneOf(_))
+            | (Exactly(_), Exactly(_))
+            | (Always, Always)
+            | (Empty, Empty) => true,
+            (ExactlyNOf(_, a), ExactlyNOf(_, b))
+            | (AtLeastNOf(_, a), AtLeastNOf(_, b))
+            | (AtMostNOf(_, a), AtMostNOf(_, b)) => a == b,
+            (SubsetOf { relevant: a, .. }, SubsetOf { relevant: b, .. }) => {
+                *a as usize == *b as usize
+            }
+            (
+                ComponentCount { min, max },
+                ComponentCount {
+                    min: other_min,
+                    max: other_max,
+                },
+            ) => min == other_min && max == other_max,
+            (ResourcePresent(a, _), ResourcePresent(b, _)) => *a as usize == *b as usize,
+            (StorageType(_, a), StorageType(_, b)) => a == b,
+            (And(a), And(b)) | (Or(a), Or(b)) => a == b,
+            (Not(a), Not(b)) => a == b,
+            (Foreign(a), Foreign(b)) => a.0.eq_dyn(b.0.as_any()),
+            _ => false,
+        }
+    }
+}
+
+impl<B: Bundle> Eq for ArchetypeStatement<B> {}
+
+impl<B: Bundle> Hash for ArchetypeStatement<B> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            ArchetypeStatement::ExactlyNOf(_, n)
+            | ArchetypeStatement::AtLeastNOf(_, n)
+            | ArchetypeStatement::AtMostNOf(_, n) => n.hash(state),
+            ArchetypeStatement::SubsetOf { relevant, .. } => (*relevant as usize).hash(state),
+            ArchetypeStatement::ComponentCount { min, max } => {
+                min.hash(state);
+                max.hash(state);
+            }
+            ArchetypeStatement::ResourcePresent(id, _) => (*id as usize).hash(state),
+            ArchetypeStatement::StorageType(_, storage) => storage.hash(state),
+            ArchetypeStatement::And(statements) | ArchetypeStatement::Or(statements) => {
+                statements.hash(state);
+            }
+            ArchetypeStatement::Not(statement) => statement.hash(state),
+            ArchetypeStatement::Foreign(statement) => statement.0.hash_dyn(state),
+            _ => {}
+        }
+    }
+}
+
+/// An [`ArchetypeStatement`] about the components of a bundle whose type has been erased,
+/// contained in an [`ArchetypeStatement::Foreign`].
+pub struct ForeignArchetypeStatement(Box<dyn ErasedArchetypeStatement>);
+
+/// The operations on an [`ArchetypeStatement`] that do not depend on its bundle type.
+trait ErasedArchetypeStatement: Send + Sync + 'static {
+    fn lower(
+        &self,
+        world: &mut LoweringWorld,
+    ) -> Result<UntypedArchetypeStatement, UnregisteredComponentError>;
+
+    fn as_any(&self) -> &dyn Any;
+
+    /// Returns `true` if `other` is an equal statement about the same bundle type.
+    fn eq_dyn(&self, other: &dyn Any) -> bool;
+
+    fn hash_dyn(&self, state: &mut dyn Hasher);
+}
+
+impl<B: Bundle> ErasedArchetypeStatement for ArchetypeStatement<B> {
+    fn lower(
+        &self,
+        world: &mut LoweringWorld,
+    ) -> Result<UntypedArchetypeStatement, UnregisteredComponentError> {
+        ArchetypeStatement::lower(self, world)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_dyn(&self, other: &dyn Any) -> bool {
+        matches!(other.downcast_ref::<ArchetypeStatement<B>>(), Some(other) if self == other)
+    }
+
+    fn hash_dyn(&self, mut state: &mut dyn Hasher) {
+        TypeId::of::<B>().hash(&mut state);
+        self.hash(&mut state);
+    }
+}
+
+/// Returns the [`ComponentId`] of the resource `R`, initializing it if needed.
+fn resource_id<R: Resource>(world: &mut World) -> ComponentId {
+    world.components.init_resource::<R>()
+}
+
+/// Returns the [`ComponentId`] of the resource `R`, or an error if it has not been registered.
+fn try_resource_id<R: Resource>(world: &World) -> Result<ComponentId, UnregisteredComponentError> {
+    world
+        .components()
+        .get_resource_id(TypeId::of::<R>())
+        .ok_or(UnregisteredComponentError {
+            name: std::any::type_name::<R>(),
+        })
+}
+
+/// The world that an [`ArchetypeStatement`] is lowered into an [`UntypedArchetypeStatement`] with.
+enum LoweringWorld<'w> {
+    /// Components are registered as needed.
+    Mut(&'w mut World),
+    /// Components must already be registered.
+    Ref(&'w World),
+}
+
+impl<'w> LoweringWorld<'w> {
+    fn world(&self) -> &World {
+        match self {
+            LoweringWorld::Mut(world) => world,
+            LoweringWorld::Ref(world) => world,
+        }
+    }
+
+    fn components(&self) -> &Components {
+        self.world().components()
+    }
+
+    fn is_strict(&self) -> bool {
+        self.world().archetype_invariants.is_strict()
+    }
+
+    fn bundle_component_ids<B: Bundle>(
+        &mut self,
+    ) -> Result<ComponentIdSet, UnregisteredComponentError> {
+        match self {
+            LoweringWorld::Mut(world) => Ok(ArchetypeStatement::<B>::bundle_component_ids(world)),
+            LoweringWorld::Ref(world) => ArchetypeStatement::<B>::try_bundle_component_ids(world),
+        }
+    }
+
+    fn relevant(
+        &mut self,
+        relevant: fn(&mut World) -> ComponentIdSet,
+        try_relevant: fn(&World) -> Result<ComponentIdSet, UnregisteredComponentError>,
+    ) -> Result<ComponentIdSet, UnregisteredComponentError> {
+        match self {
+            LoweringWorld::Mut(world) => Ok(relevant(world)),
+            LoweringWorld::Ref(world) => try_relevant(world),
+        }
+    }
+
+    fn resource_id(
+        &mut self,
+        resource_id: fn(&mut World) -> ComponentId,
+        try_resource_id: fn(&World) -> Result<ComponentId, UnregisteredComponentError>,
+    ) -> Result<ComponentId, UnregisteredComponentError> {
+        match self {
+            LoweringWorld::Mut(world) => Ok(resource_id(world)),
+            LoweringWorld::Ref(world) => try_resource_id(world),
+        }
+    }
+}
+
+/// An error returned by [`ArchetypeStatement::try_into_untyped`]
+/// when a component or resource it refers to has not been registered in the world.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnregisteredComponentError {
+    /// The type name of the first unregistered component.
+    pub name: &'static str,
+}
+
+impl std::error::Error for UnregisteredComponentError {}
+
+impl fmt::Display for UnregisteredComponentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the component `{}` is not registered in the world",
+            self.name
+        )
+    }
+}
+
+/// Returns the [`ComponentId`]s of every resource that currently exists.
+pub(crate) fn present_resources(archetypes: &Archetypes) -> ComponentIdSet {
+    archetypes
+        .resource()
+        .unique_components()
+        .iter()
+        .filter(|(_, column)| !column.is_empty())
+        .map(|(&id, _)| id)
+        .collect()
+}
+
+/// A set of [`ComponentId`]s, kept sorted by [`ComponentId`].
+///
+/// Archetype statements rarely refer to more than a handful of components,
+/// so up to four of them are stored inline without allocating.
+/// Since both sets being compared are sorted, comparisons are a single merge-style pass
+/// over both sets rather than a hash lookup for each component.
+///
+/// When reflected, the set is a tuple struct whose only field is the list of its components.
+/// Changes made to that list through reflection must keep it sorted and free of duplicates,
+/// while sets created with [`FromReflect`] sort their components themselves.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect))]
+pub struct ComponentIdSet(SmallVec<[ComponentId; 4]>);
+
+impl ComponentIdSet {
+    /// Creates an empty [`ComponentIdSet`].
+    #[inline]
+    pub fn new() -> Self {
+        Self(SmallVec::new())
+    }
+
+    /// Creates a set of every [`ComponentId`] from the start of `range` to its end, inclusive.
+    ///
+    /// Components registered one after another receive consecutive ids, so this is a concise way to
+    /// refer to a whole family of similar components, such as one component per layer.
+    /// Make sure no other component was registered in between, since it would be included as well.
+    ///
+    /// ```
+    /// # use bevy_ecs::{archetype_invariants::*, prelude::*};
+    /// #[derive(Component)]
+    /// struct Layer<const N: usize>;
+    ///
+    /// let mut world = World::new();
+    /// let first = world.init_component::<Layer<0>>();
+    /// world.init_component::<Layer<1>>();
+    /// let last = world.init_component::<Layer<2>>();
+    ///
+    /// let layers = ComponentIdSet::range(first..=last);
+    /// assert_eq!(layers.len(), 3);
+    /// world.add_untyped_archetype_invariant(UntypedArchetypeInvariant {
+    ///     predicate: UntypedArchetypeStatement::Always,
+    ///     consequence: UntypedArchetypeStatement::AtMostOneOf(layers),
+    ///     severity: Severity::Error,
+    /// });
+    /// ```
+    pub fn range(range: RangeInclusive<ComponentId>) -> Self {
+        let (start, end) = range.into_inner();
+        (start.index()..=end.index())
+            .map(ComponentId::new)
+            .collect()
+    }
+
+    /// Adds `id` to the set, returning `false` if it was already present.
+    pub fn insert(&mut self, id: ComponentId) -> bool {
+        match self.0.binary_search(&id) {
+            Ok(_) => false,
+            Err(index) => {
+                self.0.insert(index, id);
+                true
+            }
+        }
+    }
+
+    /// Returns `true` if `id` is in the set.
+    #[inline]
+    pub fn contains(&self, id: ComponentId) -> bool {
+        self.0.binary_search(&id).is_ok()
+    }
+
+    /// Returns the number of components in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the set contains no components.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns an iterator over the components in the set, in ascending order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = ComponentId> + '_ {
+        self.0.iter().copied()
+    }
+
+    /// Returns the components in the set as a sorted slice.
+    #[inline]
+    pub fn as_slice(&self) -> &[ComponentId] {
+        &self.0
+    }
+
+    /// Returns `true` if every component in this set is also in `other`.
+    #[inline]
+    pub fn is_subset(&self, other: &ComponentIdSet) -> bool {
+        self.len() <= other.len() && self.count_common(other, self.len()) == self.len()
+    }
+
+    /// Returns `true` if this set and `other` have no components in common.
+    #[inline]
+    pub fn is_disjoint(&self, other: &ComponentIdSet) -> bool {
+        self.count_common(other, 1) == 0
+    }
+
+    /// Counts how many of the components in this set are also in `other`.
+    ///
+    /// Counting stops as soon as `limit` matches have been found,
+    /// so the returned value never exceeds `limit`.
+    pub fn count_common(&self, other: &ComponentIdSet, limit: usize) -> usize {
+        let (mut left, mut right) = (self.0.iter().peekable(), other.0.iter().peekable());
+        let mut count = 0;
+        while count < limit {
+            match (left.peek(), right.peek()) {
+                (Some(a), Some(b)) => match a.cmp(b) {
+                    Ordering::Less => {
+                        left.next();
+                    }
+                    Ordering::Greater => {
+                        right.next();
+                    }
+                    Ordering::Equal => {
+                        count += 1;
+                        left.next();
+                        right.next();
+                    }
+                },
+                _ => break,
+            }
+        }
+        count
+    }
+}
+
+impl FromIterator<ComponentId> for ComponentIdSet {
+    fn from_iter<I: IntoIterator<Item = ComponentId>>(iter: I) -> Self {
+        let mut component_ids: SmallVec<[ComponentId; 4]> = iter.into_iter().collect();
+        component_ids.sort_unstable();
+        component_ids.dedup();
+        Self(component_ids)
+    }
+}
+
+impl Extend<ComponentId> for ComponentIdSet {
+    fn extend<I: IntoIterator<Item = ComponentId>>(&mut self, iter: I) {
+        self.0.extend(iter);
+        self.0.sort_unstable();
+        self.0.dedup();
+    }
+}
+
+impl From<HashSet<ComponentId>> for ComponentIdSet {
+    fn from(component_ids: HashSet<ComponentId>) -> Self {
+        component_ids.into_iter().collect()
+    }
+}
+
+impl IntoIterator for ComponentIdSet {
+    type Item = ComponentId;
+    type IntoIter = smallvec::IntoIter<[ComponentId; 4]>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// A type-erased version of [`ArchetypeInvariant`].
+///
+/// Intended to be used with dynamic components that cannot be represented with Rust types.
+/// Prefer [`ArchetypeInvariant`] when possible.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect, FromReflect))]
+pub struct UntypedArchetypeInvariant {
+    /// Defines which entities this invariant applies to.
+    /// This is the "if" of the if/then clause.
+    pub predicate: UntypedArchetypeStatement,
+    /// Defines what entities that meet the predicate must look like.
+    /// This is the "then" of the if/then clause.
+    pub consequence: UntypedArchetypeStatement,
+    /// How a violation of this invariant is reported by the automatic checks.
+    pub severity: Severity,
+}
+
+impl UntypedArchetypeInvariant {
+    /// Returns a value that formats this invariant as `predicate => consequence`,
+    /// naming each component by its type name in `world`.
+    ///
+    /// This is intended for diagnostics; the [`Debug`] representation shows raw [`ComponentId`]s instead.
+    pub fn display<'a>(&'a self, world: &'a World) -> impl fmt::Display + 'a {
+        ArchetypeInvariantDisplay {
+            invariant: self,
+            components: world.components(),
+        }
+    }
+
+    /// Tests if this invariant holds for an entity with the provided set of [`ComponentId`]s.
+    ///
+    /// An invariant holds if its predicate is false, or if both its predicate and its consequence are true.
+    #[inline]
+    pub fn evaluate(&self, component_ids: &ComponentIdSet) -> bool {
+        !self.predicate.evaluate(component_ids) || self.consequence.evaluate(component_ids)
+    }
+
+    /// Tests if this invariant holds for an entity with the provided set of [`ComponentId`]s,
+    /// in a world where exactly the `resources` exist.
+    ///
+    /// See [`UntypedArchetypeStatement::evaluate_with_resources`] for more details.
+    #[inline]
+    pub fn evaluate_with_resources(
+        &self,
+        component_ids: &ComponentIdSet,
+        resources: &ComponentIdSet,
+    ) -> bool {
+        !self
+            .predicate
+            .evaluate_with_resources(component_ids, resources)
+            || self
+                .consequence
+                .evaluate_with_resources(component_ids, resources)
+    }
+
+    /// Returns `true` if this invariant contains an [`UntypedArchetypeStatement::ResourcePresent`],
+    /// so that whether it holds depends on the world's resources as well as on the archetype.
+    pub fn uses_resources(&self) -> bool {
+        self.predicate.uses_resources() || self.consequence.uses_resources()
+    }
+
+    /// Returns a rough estimate of the cost of evaluating this invariant against an archetype,
+    /// which is the sum of the [`UntypedArchetypeStatement::complexity`] of its predicate and its consequence.
+    #[inline]
+    pub fn complexity(&self) -> usize {
+        self.predicate.complexity() + self.consequence.complexity()
+    }
+
+    /// Constructs an invariant stating that if any of the `component_ids` is present,
+    /// then exactly one of the `groups` of components must be fully present.
+    ///
+    /// The consequence is an [`UntypedArchetypeStatement::Or`] with one alternative per group,
+    /// each requiring all of the components of its group and forbidding the full presence of every other group.
+    /// An entity that has some of the components of a second group, but not all of them, therefore still upholds it.
+    ///
+    /// The groups are meant to be disjoint. If they overlap, a warning is logged, but the invariant is still constructed
+    /// with the same meaning: for example, if one group contains another, the larger group can never be the one group that is present.
+    pub fn partition(
+        component_ids: impl IntoIterator<Item = ComponentId>,
+        groups: &[&[ComponentId]],
+    ) -> Self {
+        let groups: Vec<ComponentIdSet> = groups
+            .iter()
+            .map(|group| group.iter().copied().collect())
+            .collect();
+        for (i, group) in groups.iter().enumerate() {
+            for (j, other) in groups.iter().enumerate().skip(i + 1) {
+                if !group.is_disjoint(other) {
+                    warn!("The groups {i} and {j} of a partition archetype invariant share components. Partition groups are meant to be disjoint, so that exactly one of them can be present.");
+                }
+            }
+        }
+
+        let alternatives = groups
+            .iter()
+            .enumerate()
+            .map(|(i, group)| {
+                let mut statements = vec![UntypedArchetypeStatement::AllOf(group.clone())];
+                statements.extend(groups.iter().enumerate().filter(|&(j, _)| j != i).map(
+                    |(_, other)| {
+                        UntypedArchetypeStatement::Not(Box::new(UntypedArchetypeStatement::AllOf(
+                            other.clone(),
+                        )))
+                    },
+                ));
+                UntypedArchetypeStatement::And(statements)
+            })
+            .collect();
+        UntypedArchetypeInvariant {
+            predicate: UntypedArchetypeStatement::at_least_one_of_ids(component_ids),
+            consequence: UntypedArchetypeStatement::Or(alternatives),
+            severity: Severity::Error,
+        }
+    }
+
+    /// Constructs one invariant per pair, each stating that the first component of the pair requires the second,
+    /// like [`ArchetypeInvariant::requires`].
+    ///
+    /// This expresses rules whose consequence depends on which component is present,
+    /// such as every kind of weapon requiring its own kind of ammunition, in a single call.
+    /// Since each pair is a separate invariant, the message of a violation names the pair that was broken.
+    /// Add the invariants with [`World::add_archetype_invariants`].
+    ///
+    /// ```should_panic
+    /// # use bevy_ecs::{archetype_invariants::UntypedArchetypeInvariant, prelude::*};
+    /// #[derive(Component)]
+    /// struct Bow;
+    /// #[derive(Component)]
+    /// struct Arrows;
+    /// #[derive(Component)]
+    /// struct Musket;
+    /// #[derive(Component)]
+    /// struct Bullets;
+    ///
+    /// let mut world = World::new();
+    /// let pairs = [
+    ///     (world.init_component::<Bow>(), world.init_component::<Arrows>()),
+    ///     (world.init_component::<Musket>(), world.init_component::<Bullets>()),
+    /// ];
+    /// world.add_archetype_invariants(UntypedArchetypeInvariant::paired_requires(&pairs));
+    ///
+    /// world.spawn((Bow, Arrows));
+    ///
+    /// // Panics: "... but invariant AllOf(Musket) => AllOf(Bullets) was violated."
+    /// world.spawn((Bow, Arrows, Musket));
+    /// ```
+    pub fn paired_requires(pairs: &[(ComponentId, ComponentId)]) -> Vec<Self> {
+        pairs
+            .iter()
+            .map(|&(component, required)| UntypedArchetypeInvariant {
+                predicate: UntypedArchetypeStatement::all_of_ids([component]),
+                consequence: UntypedArchetypeStatement::all_of_ids([required]),
+                severity: Severity::Error,
+            })
+            .collect()
+    }
+
+    /// Returns the negation of this invariant: where the predicate holds, the consequence must be false.
+    ///
+    /// The predicate and [`Severity`] are kept unchanged, and the consequence is negated,
+    /// unwrapping it instead if it is already an [`UntypedArchetypeStatement::Not`].
+    /// See [`ArchetypeInvariant::negate`] for more details.
+    pub fn negate(self) -> Self {
+        let consequence = match self.consequence {
+            UntypedArchetypeStatement::Not(inner) => *inner,
+            consequence => UntypedArchetypeStatement::Not(Box::new(consequence)),
+        };
+        UntypedArchetypeInvariant {
+            consequence,
+            ..self
+        }
+    }
+
+    /// Translates this invariant from the [`ComponentId`]s of `old_world` to those of `new_world`,
+    /// matching components and resources across the worlds by their [`ComponentInfo::name`](crate::component::ComponentInfo::name).
+    ///
+    /// This is useful when rebuilding a world from a snapshot, where the same components may have been
+    /// registered in a different order. Returns `None` if any referenced component is unknown to `old_world`,
+    /// or is not registered in `new_world`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// #[derive(Component)]
+    /// struct A;
+    /// #[derive(Component)]
+    /// struct B;
+    ///
+    /// let mut old_world = World::new();
+    /// let invariant = ArchetypeInvariant::<(A,), (B,)>::requires().into_untyped(&mut old_world);
+    ///
+    /// let mut new_world = World::new();
+    /// assert_eq!(invariant.remap(&old_world, &new_world), None);
+    ///
+    /// new_world.init_component::<B>();
+    /// new_world.init_component::<A>();
+    /// let expected = ArchetypeInvariant::<(A,), (B,)>::requires().into_untyped(&mut new_world);
+    /// assert_eq!(invariant.remap(&old_world, &new_world), Some(expected));
+    /// ```
+    pub fn remap(&self, old_world: &World, new_world: &World) -> Option<UntypedArchetypeInvariant> {
+        let mut complete = true;
+        let mut remap_id = |id| {
+            old_world
+                .components()
+                .get_info(id)
+                .and_then(|info| new_world.components().get_id_by_name(info.name()))
+                .unwrap_or_else(|| {
+                    complete = false;
+                    id
+                })
+        };
+        let predicate = self.predicate.clone().map_component_ids(&mut remap_id);
+        let consequence = self.consequence.clone().map_component_ids(&mut remap_id);
+        if !complete {
+            return None;
+        }
+
+        Some(UntypedArchetypeInvariant {
+            predicate,
+            consequence,
+            severity: self.severity,
+        })
+    }
+
+    /// Returns every entity currently in `world` that violates this invariant,
+    /// together with the details of the violation.
+    ///
+    /// This does not add the invariant to the world, and ignores its [`Severity`].
+    /// It is useful before adding an invariant to a world that already contains entities,
+    /// to find out which of them would violate it.
+    /// Archetypes that violate the invariant but contain no entities are not reported.
+    pub fn check_world_now(&self, world: &World) -> Vec<(Entity, ArchetypeInvariantViolation)> {
+        let resources = present_resources(world.archetypes());
+        let mut violations = Vec::new();
+        for archetype in world.archetypes().iter() {
+            if archetype.is_empty() {
+                continue;
+            }
+
+            let component_ids: ComponentIdSet = archetype.components().collect();
+            if self.evaluate_with_resources(&component_ids, &resources) {
+                continue;
+            }
+
+            let violation = ArchetypeInvariantViolation::for_archetype(
+                archetype,
+                self.clone(),
+                world.components(),
+            );
+            violations.extend(
+                archetype
+                    .entities()
+                    .iter()
+                    .map(|&entity| (entity, violation.clone())),
+            );
+        }
+        violations
+    }
+
+    /// Returns the [`ArchetypeId`]s of every archetype in `world` that matches the predicate of this invariant,
+    /// whether or not it also satisfies the consequence.
+    ///
+    /// This is intended for debugging invariants that do not apply to the archetypes they were expected to.
+    /// Archetypes without entities are included, but the archetype that stores resources is not.
+    pub fn matching_archetypes(&self, world: &World) -> Vec<ArchetypeId> {
+        let resources = present_resources(world.archetypes());
+        world
+            .archetypes()
+            .iter()
+            .filter(|archetype| archetype.id() != ArchetypeId::RESOURCE)
+            .filter(|archetype| {
+                self.predicate
+                    .evaluate_with_resources(&archetype.components().collect(), &resources)
+            })
+            .map(Archetype::id)
+            .collect()
+    }
+
+    /// Returns the components that this invariant both requires and forbids, sorted by [`ComponentId`].
+    ///
+    /// Any entity that matches the predicate of an invariant with such components will always violate it.
+    /// This analysis is conservative: only a predicate of [`UntypedArchetypeStatement::AllOf`]
+    /// paired with a consequence of [`UntypedArchetypeStatement::NoneOf`] is inspected,
+    /// so an empty result does not guarantee that the invariant can be satisfied.
+    fn contradictions(&self) -> Vec<ComponentId> {
+        match (&self.predicate, &self.consequence) {
+            (
+                UntypedArchetypeStatement::AllOf(required),
+                UntypedArchetypeStatement::NoneOf(forbidden),
+            ) => required
+                .iter()
+                .filter(|&id| forbidden.contains(id))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// A type-erased version of [`ArchetypeStatement`].
+///
+/// Intended to be used with dynamic components that cannot be represented with Rust types.
+/// Prefer [`ArchetypeStatement`] when possible.
+///
+/// Components are only identified by their [`ComponentId`], and named by their [`ComponentInfo::name`](crate::component::ComponentInfo::name),
+/// so components registered with [`World::init_component_with_descriptor`] can be referred to as soon as they are registered.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect, FromReflect))]
+pub enum UntypedArchetypeStatement {
+    /// Evaluates to true if and only if the entity has all of the components present in the set.
+    AllOf(ComponentIdSet),
+    /// The entity has at least one component in the set, and may have all of them.
+    /// When using a single-component set, `AllOf` is preferred.
+    AtLeastOneOf(ComponentIdSet),
+    /// The entity has none of the components in the set.
+    NoneOf(ComponentIdSet),
+    /// The entity has exactly one of the components in the set.
+    /// When using a single-component set, `AllOf` is preferred.
+    ExactlyOneOf(ComponentIdSet),
+    /// The entity has at most one of the components in the set, and may have none of them.
+    AtMostOneOf(ComponentIdSet),
+    /// The entity has exactly `n` of the components in the set.
+    ExactlyNOf(ComponentIdSet, usize),
+    /// The entity has at least `n` of the components in the set.
+    AtLeastNOf(ComponentIdSet, usize),
+    /// The entity has at most `n` of the components in the set.
+    AtMostNOf(ComponentIdSet, usize),
+    /// The entity has every component in the set, and no other components.
+    Exactly(ComponentIdSet),
+    /// Of the components in `relevant`, the entity only has ones that are also in `allowed`.
+    ///
+    /// In other words, the components of `relevant` the entity has are a subset of `allowed`.
+    SubsetOf {
+        /// The components restricted by the whitelist.
+        relevant: ComponentIdSet,
+        /// The whitelist.
+        allowed: ComponentIdSet,
+    },
+    /// The entity has at least `min` and at most `max` components in total, counting every component it has.
+    /// A bound of `None` is not checked.
+    ///
+    /// This is mostly useful as a predicate: as a consequence, it limits the total number of components
+    /// of an entity, including ones unrelated to the invariant.
+    ComponentCount {
+        /// The minimum number of components, inclusive.
+        min: Option<usize>,
+        /// The maximum number of components, inclusive.
+        max: Option<usize>,
+    },
+    /// Evaluates to true if the resource with the given [`ComponentId`] exists in the world.
+    ///
+    /// This depends on the world's state rather than the shape of the archetype,
+    /// so it should only be used in predicates, to make an invariant apply only while a resource exists.
+    /// Any invariant using it is re-checked against every archetype whenever a resource is inserted or removed.
+    ///
+    /// [`UntypedArchetypeStatement::evaluate`] only sees the archetype, so it treats every resource as missing.
+    /// Use [`UntypedArchetypeStatement::evaluate_with_resources`] to provide the existing resources.
+    /// The resource is not included in [`UntypedArchetypeStatement::component_ids`].
+    ResourcePresent(ComponentId),
+    /// The entity has the component with the given [`ComponentId`], which marks it as the child of another entity,
+    /// such as `Parent` from `bevy_hierarchy`.
+    ///
+    /// This is equivalent to an [`UntypedArchetypeStatement::AllOf`] with that single component,
+    /// but states the intent more clearly, in violation messages and explanations too.
+    /// `bevy_ecs` does not know about any hierarchy itself, so the component must be registered first
+    /// using [`World::set_hierarchy_components`]. Construct it with [`UntypedArchetypeStatement::has_parent`].
+    HasParent(ComponentId),
+    /// The entity has the component with the given [`ComponentId`], which lists the children of an entity,
+    /// such as `Children` from `bevy_hierarchy`.
+    ///
+    /// See [`UntypedArchetypeStatement::HasParent`] for details.
+    /// Construct it with [`UntypedArchetypeStatement::has_children`].
+    HasChildren(ComponentId),
+    /// Each component in `components` that the entity has is stored with the given [`StorageType`].
+    ///
+    /// This is an advanced invariant, intended for performance tuning rather than correctness:
+    /// for example, to require that components which are frequently added and removed use [`StorageType::SparseSet`].
+    ///
+    /// The storage type of a component never changes, so it is looked up from
+    /// [`ComponentInfo::storage_type`](crate::component::ComponentInfo::storage_type) once, when the statement is constructed.
+    /// Construct it with [`UntypedArchetypeStatement::storage_type_ids`] to keep `mismatched` consistent.
+    StorageType {
+        /// The components whose storage type is checked.
+        components: ComponentIdSet,
+        /// The storage type the components must use.
+        storage: StorageType,
+        /// The components in `components` that are not stored with `storage`.
+        /// The statement is true if the entity has none of them.
+        mismatched: ComponentIdSet,
+    },
+    /// The entity has every component in the component group with the given [`GroupId`].
+    ///
+    /// Group membership is dynamic: the statement always refers to the current members of the group,
+    /// as registered with [`ArchetypeInvariants::register_component_group`]. Whenever the members of a group change,
+    /// every invariant referring to it is re-checked against every archetype.
+    /// [`UntypedArchetypeStatement::evaluate`] does not know about groups, so it treats every group as empty.
+    GroupAllOf(GroupId),
+    /// The entity has at least one component in the component group with the given [`GroupId`].
+    ///
+    /// See [`UntypedArchetypeStatement::GroupAllOf`] for how groups are resolved.
+    GroupAtLeastOneOf(GroupId),
+    /// Evaluates to true for every entity.
+    Always,
+    /// Evaluates to true only for entities without any components.
+    Empty,
+    /// Evaluates to true if and only if all of the contained statements are true.
+    /// Evaluates to true if there are no contained statements.
+    ///
+    /// The contained statements may be of any kind, and may refer to any components.
+    And(Vec<UntypedArchetypeStatement>),
+    /// Evaluates to true if and only if at least one of the contained statements is true.
+    /// Evaluates to false if there are no contained statements.
+    ///
+    /// The contained statements may be of any kind, and may refer to any components.
+    Or(Vec<UntypedArchetypeStatement>),
+    /// Evaluates to true if and only if the contained statement is false.
+    Not(Box<UntypedArchetypeStatement>),
+}
+
+impl UntypedArchetypeStatement {
+    /// Constructs a new [`UntypedArchetypeStatement::StorageType`] variant for the given components,
+    /// looking up the storage type of each one in `components`.
+    ///
+    /// Components that are not registered in `components` are treated as using the correct storage type.
+    pub fn storage_type_ids(
+        component_ids: impl IntoIterator<Item = ComponentId>,
+        storage: StorageType,
+        components: &Components,
+    ) -> Self {
+        let component_ids: ComponentIdSet = component_ids.into_iter().collect();
+        let mismatched = component_ids
+            .iter()
+            .filter(|&id| {
+                components
+                    .get_info(id)
+                    .filter(|info| info.storage_type() != storage)
+                    .is_some()
+            })
+            .collect();
+        UntypedArchetypeStatement::StorageType {
+            components: component_ids,
+            storage,
+            mismatched,
+        }
+    }
+
+    /// Constructs a new [`UntypedArchetypeStatement::HasParent`] variant,
+    /// using the parent component registered in `world` with [`World::set_hierarchy_components`].
+    ///
+    /// Returns `None` if no hierarchy components were registered,
+    /// for example because the `HierarchyPlugin` of `bevy_hierarchy` was not added.
+    ///
+    /// ```
+    /// # use bevy_ecs::{archetype_invariants::*, prelude::*};
+    /// #[derive(Component)]
+    /// struct Parent;
+    /// #[derive(Component)]
+    /// struct Children;
+    /// #[derive(Component)]
+    /// struct UiNode;
+    /// #[derive(Component)]
+    /// struct UiRoot;
+    ///
+    /// let mut world = World::new();
+    /// world.set_hierarchy_components::<Parent, Children>();
+    /// let ui_node = world.init_component::<UiNode>();
+    /// let ui_root = world.init_component::<UiRoot>();
+    ///
+    /// // Any UI node must have a parent, or be the root of the UI
+    /// world.add_untyped_archetype_invariant(UntypedArchetypeInvariant {
+    ///     predicate: UntypedArchetypeStatement::all_of_ids([ui_node]),
+    ///     consequence: UntypedArchetypeStatement::Or(vec![
+    ///         UntypedArchetypeStatement::has_parent(&world).unwrap(),
+    ///         UntypedArchetypeStatement::all_of_ids([ui_root]),
+    ///     ]),
+    ///     severity: Severity::Error,
+    /// });
+    /// world.spawn((UiNode, UiRoot));
+    /// world.spawn((UiNode, Parent));
+    /// ```
+    pub fn has_parent(world: &World) -> Option<Self> {
+        let (parent, _) = world.archetype_invariants.hierarchy_components()?;
+        Some(UntypedArchetypeStatement::HasParent(parent))
+    }
+
+    /// Constructs a new [`UntypedArchetypeStatement::HasChildren`] variant,
+    /// using the children component registered in `world` with [`World::set_hierarchy_components`].
+    ///
+    /// Returns `None` if no hierarchy components were registered.
+    /// See [`UntypedArchetypeStatement::has_parent`] for an example.
+    pub fn has_children(world: &World) -> Option<Self> {
+        let (_, children) = world.archetype_invariants.hierarchy_components()?;
+        Some(UntypedArchetypeStatement::HasChildren(children))
+    }
+
+    /// Constructs a new [`UntypedArchetypeStatement::AllOf`] variant for the given components.
+    #[inline]
+    pub fn all_of_ids(component_ids: impl IntoIterator<Item = ComponentId>) -> Self {
+        UntypedArchetypeStatement::AllOf(component_ids.into_iter().collect())
+    }
+
+    /// Constructs a new [`UntypedArchetypeStatement::AtLeastOneOf`] variant for the given components.
+    ///
+    /// In debug builds, this panics if no components are given, since the statement could never be true.
+    #[inline]
+    pub fn at_least_one_of_ids(component_ids: impl IntoIterator<Item = ComponentId>) -> Self {
+        let component_ids: ComponentIdSet = component_ids.into_iter().collect();
+        debug_assert!(
+            !component_ids.is_empty(),
+            "`UntypedArchetypeStatement::AtLeastOneOf` requires at least one component"
+        );
+        UntypedArchetypeStatement::AtLeastOneOf(component_ids)
+    }
+
+    /// Constructs a new [`UntypedArchetypeStatement::NoneOf`] variant for the given components.
+    #[inline]
+    pub fn none_of_ids(component_ids: impl IntoIterator<Item = ComponentId>) -> Self {
+        UntypedArchetypeStatement::NoneOf(component_ids.into_iter().collect())
+    }
+
+    /// Constructs a new [`UntypedArchetypeStatement::ExactlyOneOf`] variant for the given components.
+    ///
+    /// In debug builds, this panics if no components are given, since the statement could never be true.
+    #[inline]
+    pub fn exactly_one_of_ids(component_ids: impl IntoIterator<Item = ComponentId>) -> Self {
+        let component_ids: ComponentIdSet = component_ids.into_iter().collect();
+        debug_assert!(
+            !component_ids.is_empty(),
+            "`UntypedArchetypeStatement::ExactlyOneOf` requires at least one component"
+        );
+        UntypedArchetypeStatement::ExactlyOneOf(component_ids)
+    }
+
+    /// Constructs a new [`UntypedArchetypeStatement::AtMostOneOf`] variant for the given components.
+    ///
+    /// This is the easiest way to keep a large family of mutually exclusive components apart,
+    /// such as one component per layer an entity can occupy, without listing them all in a [`Bundle`].
+    /// The ids can be collected from [`World::init_component`] or [`World::init_component_with_descriptor`],
+    /// or taken from a [`ComponentIdSet::range`] if the components were registered consecutively.
+    /// Checking the statement takes a single pass over the components of each archetype, however many ids it has.
+    #[inline]
+    pub fn at_most_one_of_ids(component_ids: impl IntoIterator<Item = ComponentId>) -> Self {
+        UntypedArchetypeStatement::AtMostOneOf(component_ids.into_iter().collect())
+    }
+
+    /// Constructs a new [`UntypedArchetypeStatement::ExactlyNOf`] variant for the given components.
+    ///
+    /// In debug builds, this panics if fewer than `n` components are given, since the statement could never be true.
+    #[inline]
+    pub fn exactly_n_of_ids(
+        component_ids: impl IntoIterator<Item = ComponentId>,
+        n: usize,
+    ) -> Self {
+        let component_ids: ComponentIdSet = component_ids.into_iter().collect();
+        debug_assert!(
+            n <= component_ids.len(),
+            "`UntypedArchetypeStatement::ExactlyNOf` requires {n} components, but only {} were given",
+            component_ids.len()
+        );
+        UntypedArchetypeStatement::ExactlyNOf(component_ids, n)
+    }
+
+    /// Constructs a new [`UntypedArchetypeStatement::AtLeastNOf`] variant for the given components.
+    ///
+    /// In debug builds, this panics if fewer than `n` components are given, since the statement could never be true.
+    #[inline]
+    pub fn at_least_n_of_ids(
+        component_ids: impl IntoIterator<Item = ComponentId>,
+        n: usize,
+    ) -> Self {
+        let component_ids: ComponentIdSet = component_ids.into_iter().collect();
+        debug_assert!(
+            n <= component_ids.len(),
+            "`UntypedArchetypeStatement::AtLeastNOf` requires {n} components, but only {} were given",
+            component_ids.len()
+        );
+        UntypedArchetypeStatement::AtLeastNOf(component_ids, n)
+    }
+
+    /// Constructs a new [`UntypedArchetypeStatement::AtMostNOf`] variant for the given components.
+    #[inline]
+    pub fn at_most_n_of_ids(
+        component_ids: impl IntoIterator<Item = ComponentId>,
+        n: usize,
+    ) -> Self {
+        UntypedArchetypeStatement::AtMostNOf(component_ids.into_iter().collect(), n)
+    }
+
+    /// Constructs a new [`UntypedArchetypeStatement::Exactly`] variant for the given components.
+    #[inline]
+    pub fn exactly_ids(component_ids: impl IntoIterator<Item = ComponentId>) -> Self {
+        UntypedArchetypeStatement::Exactly(component_ids.into_iter().collect())
+    }
+
+    /// Constructs a new [`UntypedArchetypeStatement::SubsetOf`] variant,
+    /// which only allows the `relevant` components that are also `allowed`.
+    #[inline]
+    pub fn subset_of_ids(
+        relevant: impl IntoIterator<Item = ComponentId>,
+        allowed: impl IntoIterator<Item = ComponentId>,
+    ) -> Self {
+        UntypedArchetypeStatement::SubsetOf {
+            relevant: relevant.into_iter().collect(),
+            allowed: allowed.into_iter().collect(),
+        }
+    }
+
+    /// Returns a value that formats this statement like `AllOf(Transform, GlobalTransform)`,
+    /// naming each component by its type name in `world`.
+    ///
+    /// This is intended for diagnostics; the [`Debug`] representation shows raw [`ComponentId`]s instead.
+    pub fn display<'a>(&'a self, world: &'a World) -> impl fmt::Display + 'a {
+        ArchetypeStatementDisplay {
+            statement: self,
+            components: world.components(),
+        }
+    }
+
+    /// Get the set of [`ComponentId`]s affected by this statement,
+    /// including those affected by any nested statements.
+    ///
+    /// Resources referred to by [`UntypedArchetypeStatement::ResourcePresent`] are not included.
+    pub fn component_ids(&self) -> ComponentIdSet {
+        let mut component_ids = Vec::new();
+        self.visit_ids(false, &mut |id| component_ids.push(id));
+        component_ids.into_iter().collect()
+    }
+
+    /// Calls `f` with every [`ComponentId`] referred to by this statement, recursing into nested statements.
+    ///
+    /// Unlike [`UntypedArchetypeStatement::component_ids`], this includes the ids of the resources
+    /// referred to by [`UntypedArchetypeStatement::ResourcePresent`].
+    /// Ids are visited in statement order, and an id is visited once for each place it occurs.
+    pub fn for_each_component_id(&self, mut f: impl FnMut(ComponentId)) {
+        self.visit_ids(true, &mut f);
+    }
+
+    fn visit_ids<F: FnMut(ComponentId)>(&self, resources: bool, f: &mut F) {
+        match self {
+            UntypedArchetypeStatement::AllOf(set)
+            | UntypedArchetypeStatement::AtLeastOneOf(set)
+            | UntypedArchetypeStatement::NoneOf(set)
+            | UntypedArchetypeStatement::ExactlyOneOf(set)
+            | UntypedArchetypeStatement::AtMostOneOf(set)
+            | UntypedArchetypeStatement::ExactlyNOf(set, _)
+            | UntypedArchetypeStatement::AtLeastNOf(set, _)
+            | UntypedArchetypeStatement::AtMostNOf(set, _)
+            | UntypedArchetypeStatement::Exactly(set) => set.iter().for_each(&mut *f),
+            UntypedArchetypeStatement::SubsetOf { relevant, allowed } => {
+                relevant.iter().for_each(&mut *f);
+                allowed.iter().for_each(&mut *f);
+            }
+            UntypedArchetypeStatement::StorageType { components, .. } => {
+                components.iter().for_each(&mut *f);
+            }
+            UntypedArchetypeStatement::ResourcePresent(id) => {
+                if resources {
+                    f(*id);
+                }
+            }
+            UntypedArchetypeStatement::HasParent(id)
+            | UntypedArchetypeStatement::HasChildren(id) => {
+                f(*id);
+            }
+            UntypedArchetypeStatement::ComponentCount { .. }
+            | UntypedArchetypeStatement::GroupAllOf(_)
+            | UntypedArchetypeStatement::GroupAtLeastOneOf(_)
+            | UntypedArchetypeStatement::Always
+            | UntypedArchetypeStatement::Empty => {}
+            UntypedArchetypeStatement::And(statements)
+            | UntypedArchetypeStatement::Or(statements) => {
+                for statement in statements {
+                    statement.visit_ids(resources, f);
+                }
+            }
+            UntypedArchetypeStatement::Not(statement) => statement.visit_ids(resources, f),
+        }
+    }
+
+    /// Replaces every [`ComponentId`] in this statement with the result of `f`, recursing into nested statements.
+    ///
+    /// This includes the ids of the resources referred to by [`UntypedArchetypeStatement::ResourcePresent`].
+    /// If `f` maps several ids of a set to the same id, they are merged,
+    /// which may change the meaning of counting statements such as [`UntypedArchetypeStatement::AtLeastNOf`].
+    pub fn map_component_ids(self, mut f: impl FnMut(ComponentId) -> ComponentId) -> Self {
+        self.map_ids(&mut f)
+    }
+
+    fn map_ids<F: FnMut(ComponentId) -> ComponentId>(self, f: &mut F) -> Self {
+        let mut map = |set: ComponentIdSet| set.iter().map(&mut *f).collect();
+        match self {
+            UntypedArchetypeStatement::AllOf(set) => UntypedArchetypeStatement::AllOf(map(set)),
+            UntypedArchetypeStatement::AtLeastOneOf(set) => {
+                UntypedArchetypeStatement::AtLeastOneOf(map(set))
+            }
+            UntypedArchetypeStatement::NoneOf(set) => UntypedArchetypeStatement::NoneOf(map(set)),
+            UntypedArchetypeStatement::ExactlyOneOf(set) => {
+                UntypedArchetypeStatement::ExactlyOneOf(map(set))
+            }
+            UntypedArchetypeStatement::AtMostOneOf(set) => {
+                UntypedArchetypeStatement::AtMostOneOf(map(set))
+            }
+            UntypedArchetypeStatement::ExactlyNOf(set, n) => {
+                UntypedArchetypeStatement::ExactlyNOf(map(set), n)
+            }
+            UntypedArchetypeStatement::AtLeastNOf(set, n) => {
+                UntypedArchetypeStatement::AtLeastNOf(map(set), n)
+            }
+            UntypedArchetypeStatement::AtMostNOf(set, n) => {
+                UntypedArchetypeStatement::AtMostNOf(map(set), n)
+            }
+            UntypedArchetypeStatement::Exactly(set) => UntypedArchetypeStatement::Exactly(map(set)),
+            UntypedArchetypeStatement::SubsetOf { relevant, allowed } => {
+                UntypedArchetypeStatement::SubsetOf {
+                    relevant: map(relevant),
+                    allowed: map(allowed),
+                }
+            }
+            UntypedArchetypeStatement::ResourcePresent(id) => {
+                UntypedArchetypeStatement::ResourcePresent(f(id))
+            }
+            UntypedArchetypeStatement::HasParent(id) => UntypedArchetypeStatement::HasParent(f(id)),
+            UntypedArchetypeStatement::HasChildren(id) => {
+                UntypedArchetypeStatement::HasChildren(f(id))
+            }
+            UntypedArchetypeStatement::StorageType {
+                components,
+                storage,
+                mismatched,
+            } => UntypedArchetypeStatement::StorageType {
+                components: map(components),
+                storage,
+                mismatched: map(mismatched),
+            },
+            statement @ (UntypedArchetypeStatement::ComponentCount { .. }
+            | UntypedArchetypeStatement::GroupAllOf(_)
+            | UntypedArchetypeStatement::GroupAtLeastOneOf(_)
+            | UntypedArchetypeStatement::Always
+            | UntypedArchetypeStatement::Empty) => statement,
+            UntypedArchetypeStatement::And(statements) => UntypedArchetypeStatement::And(
+                statements.into_iter().map(|s| s.map_ids(f)).collect(),
+            ),
+            UntypedArchetypeStatement::Or(statements) => UntypedArchetypeStatement::Or(
+                statements.into_iter().map(|s| s.map_ids(f)).collect(),
+            ),
+            UntypedArchetypeStatement::Not(statement) => {
+                UntypedArchetypeStatement::Not(Box::new(statement.map_ids(f)))
+            }
+        }
+    }
+
+    /// Returns `true` if this statement contains an [`UntypedArchetypeStatement::ResourcePresent`].
+    pub fn uses_resources(&self) -> bool {
+        match self {
+            UntypedArchetypeStatement::ResourcePresent(_) => true,
+            UntypedArchetypeStatement::And(statements)
+            | UntypedArchetypeStatement::Or(statements) => statements
+                .iter()
+                .any(UntypedArchetypeStatement::uses_resources),
+            UntypedArchetypeStatement::Not(statement) => statement.uses_resources(),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if this statement contains an [`UntypedArchetypeStatement::GroupAllOf`]
+    /// or an [`UntypedArchetypeStatement::GroupAtLeastOneOf`].
+    pub fn uses_groups(&self) -> bool {
+        match self {
+            UntypedArchetypeStatement::GroupAllOf(_)
+            | UntypedArchetypeStatement::GroupAtLeastOneOf(_) => true,
+            UntypedArchetypeStatement::And(statements)
+            | UntypedArchetypeStatement::Or(statements) => statements
+                .iter()
+                .any(UntypedArchetypeStatement::uses_groups),
+            UntypedArchetypeStatement::Not(statement) => statement.uses_groups(),
+            _ => false,
+        }
+    }
+
+    /// Returns a rough estimate of the cost of evaluating this statement against an archetype.
+    ///
+    /// This is one for each statement, including every nested statement, plus one for each component it refers to.
+    /// Statements about component groups count as a single statement, since their members can change.
+    ///
+    /// ```
+    /// # use bevy_ecs::{archetype_invariants::UntypedArchetypeStatement, component::ComponentId};
+    /// let [a, b] = [ComponentId::new(0), ComponentId::new(1)];
+    /// let statement = UntypedArchetypeStatement::Not(Box::new(UntypedArchetypeStatement::Or(vec![
+    ///     UntypedArchetypeStatement::all_of_ids([a, b]),
+    ///     UntypedArchetypeStatement::Always,
+    /// ])));
+    /// // `Not`, `Or`, `AllOf` with two components, and `Always`
+    /// assert_eq!(statement.complexity(), 6);
+    /// ```
+    pub fn complexity(&self) -> usize {
+        let components = match self {
+            UntypedArchetypeStatement::AllOf(set)
+            | UntypedArchetypeStatement::AtLeastOneOf(set)
+            | UntypedArchetypeStatement::NoneOf(set)
+            | UntypedArchetypeStatement::ExactlyOneOf(set)
+            | UntypedArchetypeStatement::AtMostOneOf(set)
+            | UntypedArchetypeStatement::ExactlyNOf(set, _)
+            | UntypedArchetypeStatement::AtLeastNOf(set, _)
+            | UntypedArchetypeStatement::AtMostNOf(set, _)
+            | UntypedArchetypeStatement::Exactly(set)
+            | UntypedArchetypeStatement::StorageType {
+                components: set, ..
+            } => set.len(),
+            UntypedArchetypeStatement::SubsetOf { relevant, allowed } => {
+                relevant.len() + allowed.len()
+            }
+            UntypedArchetypeStatement::ResourcePresent(_)
+            | UntypedArchetypeStatement::HasParent(_)
+            | UntypedArchetypeStatement::HasChildren(_) => 1,
+            UntypedArchetypeStatement::ComponentCount { .. }
+            | UntypedArchetypeStatement::GroupAllOf(_)
+            | UntypedArchetypeStatement::GroupAtLeastOneOf(_)
+            | UntypedArchetypeStatement::Always
+            | UntypedArchetypeStatement::Empty => 0,
+            UntypedArchetypeStatement::And(statements)
+            | UntypedArchetypeStatement::Or(statements) => statements
+                .iter()
+                .map(UntypedArchetypeStatement::complexity)
+                .sum(),
+            UntypedArchetypeStatement::Not(statement) => statement.complexity(),
+        };
+        1 + components
+    }
+
+    /// Replaces every statement about a component group with the equivalent statement about its current members.
+    /// Groups that do not exist are treated as empty.
+    fn resolve_groups(&self, groups: &[(Cow<'static, str>, ComponentIdSet)]) -> Self {
+        let members = |group: &GroupId| {
+            groups
+                .get(group.0)
+                .map(|(_, members)| members.clone())
+                .unwrap_or_default()
+        };
+        match self {
+            UntypedArchetypeStatement::GroupAllOf(group) => {
+                UntypedArchetypeStatement::AllOf(members(group))
+            }
+            UntypedArchetypeStatement::GroupAtLeastOneOf(group) => {
+                UntypedArchetypeStatement::AtLeastOneOf(members(group))
+            }
+            UntypedArchetypeStatement::And(statements) => UntypedArchetypeStatement::And(
+                statements
+                    .iter()
+                    .map(|s| s.resolve_groups(groups))
+                    .collect(),
+            ),
+            UntypedArchetypeStatement::Or(statements) => UntypedArchetypeStatement::Or(
+                statements
+                    .iter()
+                    .map(|s| s.resolve_groups(groups))
+                    .collect(),
+            ),
+            UntypedArchetypeStatement::Not(statement) => {
+                UntypedArchetypeStatement::Not(Box::new(statement.resolve_groups(groups)))
+            }
+            statement => statement.clone(),
+        }
+    }
+}
+
+/// The kind of an [`UntypedArchetypeStatement`] about a set of components,
+/// used to construct statements from component names with [`ArchetypeStatementKind::resolve`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ArchetypeStatementKind {
+    /// Constructs an [`UntypedArchetypeStatement::AllOf`].
+    AllOf,
+    /// Constructs an [`UntypedArchetypeStatement::AtLeastOneOf`].
+    AtLeastOneOf,
+    /// Constructs an [`UntypedArchetypeStatement::NoneOf`].
+    NoneOf,
+    /// Constructs an [`UntypedArchetypeStatement::ExactlyOneOf`].
+    ExactlyOneOf,
+    /// Constructs an [`UntypedArchetypeStatement::AtMostOneOf`].
+    AtMostOneOf,
+    /// Constructs an [`UntypedArchetypeStatement::ExactlyNOf`].
+    ExactlyNOf(usize),
+    /// Constructs an [`UntypedArchetypeStatement::AtLeastNOf`].
+    AtLeastNOf(usize),
+    /// Constructs an [`UntypedArchetypeStatement::AtMostNOf`].
+    AtMostNOf(usize),
+    /// Constructs an [`UntypedArchetypeStatement::Exactly`].
+    Exactly,
+}
+
+impl ArchetypeStatementKind {
+    /// Constructs a statement of this kind about the given components.
+    pub fn with_ids(self, component_ids: ComponentIdSet) -> UntypedArchetypeStatement {
+        match self {
+            ArchetypeStatementKind::AllOf => UntypedArchetypeStatement::AllOf(component_ids),
+            ArchetypeStatementKind::AtLeastOneOf => {
+                UntypedArchetypeStatement::AtLeastOneOf(component_ids)
+            }
+            ArchetypeStatementKind::NoneOf => UntypedArchetypeStatement::NoneOf(component_ids),
+            ArchetypeStatementKind::ExactlyOneOf => {
+                UntypedArchetypeStatement::ExactlyOneOf(component_ids)
+            }
+            ArchetypeStatementKind::AtMostOneOf => {
+                UntypedArchetypeStatement::AtMostOneOf(component_ids)
+            }
+            ArchetypeStatementKind::ExactlyNOf(n) => {
+                UntypedArchetypeStatement::ExactlyNOf(component_ids, n)
+            }
+            ArchetypeStatementKind::AtLeastNOf(n) => {
+                UntypedArchetypeStatement::AtLeastNOf(component_ids, n)
+            }
+            ArchetypeStatementKind::AtMostNOf(n) => {
+                UntypedArchetypeStatement::AtMostNOf(component_ids, n)
+            }
+            ArchetypeStatementKind::Exactly => UntypedArchetypeStatement::Exactly(component_ids),
+        }
+    }
+
+    /// Constructs a statement of this kind about the components with the given names,
+    /// as registered in `components`.
+    ///
+    /// Names must match [`ComponentInfo::name`](crate::component::ComponentInfo::name) exactly.
+    /// Returns an error listing every name that does not belong to a registered component.
+    pub fn resolve(
+        self,
+        names: &[&str],
+        components: &Components,
+    ) -> Result<UntypedArchetypeStatement, UnknownComponentNames> {
+        let mut unknown = Vec::new();
+        let component_ids = names
+            .iter()
+            .filter_map(|&name| {
+                let id = components.get_id_by_name(name);
+                if id.is_none() {
+                    unknown.push(name.to_string());
+                }
+                id
+            })
+            .collect();
+        if unknown.is_empty() {
+            Ok(self.with_ids(component_ids))
+        } else {
+            Err(UnknownComponentNames { names: unknown })
+        }
+    }
+}
+
+/// An error returned when constructing archetype statements from component names
+/// that do not belong to any registered component.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownComponentNames {
+    /// The names that could not be resolved, in the order they were given.
+    pub names: Vec<String>,
+}
+
+impl std::error::Error for UnknownComponentNames {}
+
+impl fmt::Display for UnknownComponentNames {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no component is registered with the name ")?;
+        for (i, name) in self.names.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "`{name}`")?;
+        }
+        Ok(())
+    }
+}
+
+/// An archetype was found that does not uphold an [`UntypedArchetypeInvariant`].
+///
+/// The [`Display`](fmt::Display) implementation of this type renders components by their type names,
+/// as they were registered in the [`World`] that the violation was found in.
+/// Each list of components is sorted by name, so the message does not depend on the order of registration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchetypeInvariantViolation {
+    /// The archetype that violated the invariant.
+    pub archetype_id: ArchetypeId,
+    /// The components present in the violating archetype.
+    pub component_ids: Vec<ComponentId>,
+    /// A sample of the entities in the violating archetype at the time the violation was found,
+    /// containing at most [`ArchetypeInvariantViolation::MAX_SAMPLED_ENTITIES`] entities.
+    ///
+    /// This is empty if the archetype contained no entities, which can happen when an invariant is added
+    /// after the archetype was created.
+    /// The archetype of a [`World::spawn_batch`] is only checked once the batch has been spawned,
+    /// so its violations include the spawned entities.
+    pub entities: Vec<Entity>,
+    /// The archetype invariant that was violated.
+    pub invariant: UntypedArchetypeInvariant,
+    /// The components whose removal from the affected entity caused this violation,
+    /// if it was found by the removal checks enabled with [`ArchetypeInvariants::set_check_removals`].
+    /// This is empty for violations found by any other check.
+    pub removed_component_ids: Vec<ComponentId>,
+    /// Where in the source code the violated invariant was added to the world, if known.
+    pub location: Option<&'static Location<'static>>,
+    /// The names of every component referred to by this violation.
+    component_names: HashMap<ComponentId, String>,
+}
+
+impl ArchetypeInvariantViolation {
+    /// The maximum number of entities of the violating archetype that are recorded in a violation.
+    pub const MAX_SAMPLED_ENTITIES: usize = 8;
+
+    /// Creates a new violation of `invariant` by the archetype with the given [`ArchetypeId`] and [`ComponentId`]s,
+    /// affecting the given `entities`, and looks up the names of all involved components in `components`.
+    pub fn new(
+        archetype_id: ArchetypeId,
+        component_ids: Vec<ComponentId>,
+        entities: Vec<Entity>,
+        invariant: UntypedArchetypeInvariant,
+        components: &Components,
+    ) -> Self {
+        let component_names = component_ids
+            .iter()
+            .copied()
+            .chain(invariant.predicate.component_ids())
+            .chain(invariant.consequence.component_ids())
+            .map(|id| (id, component_name(components, id)))
+            .collect();
+
+        Self {
+            archetype_id,
+            component_ids,
+            entities,
+            invariant,
+            removed_component_ids: Vec::new(),
+            location: None,
+            component_names,
+        }
+    }
+
+    /// Creates a new violation of `invariant` by `archetype`,
+    /// sampling up to [`ArchetypeInvariantViolation::MAX_SAMPLED_ENTITIES`] of its entities.
+    fn for_archetype(
+        archetype: &Archetype,
+        invariant: UntypedArchetypeInvariant,
+        components: &Components,
+    ) -> Self {
+        let entities = archetype.entities();
+        Self::new(
+            archetype.id(),
+            archetype.components().collect(),
+            entities[..entities.len().min(Self::MAX_SAMPLED_ENTITIES)].to_vec(),
+            invariant,
+            components,
+        )
+    }
+
+    /// Returns the name of the component with the given [`ComponentId`].
+    fn name_of(&self, id: ComponentId) -> String {
+        match self.component_names.get(&id) {
+            Some(name) => name.clone(),
+            None => format!("{:?}", id),
+        }
+    }
+
+    /// Writes where the violated invariant was registered, if known, as the last sentence of the message.
+    fn write_location(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.location {
+            Some(location) => write!(
+                f,
+                " The invariant was registered at {}:{}.",
+                location.file(),
+                location.line()
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for ArchetypeInvariantViolation {}
+
+impl fmt::Display for ArchetypeInvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name_of = |id| self.name_of(id);
+        if let (false, UntypedArchetypeStatement::AllOf(orphaned)) = (
+            self.removed_component_ids.is_empty(),
+            &self.invariant.predicate,
+        ) {
+            write!(f, "Removing [")?;
+            write_names(f, self.removed_component_ids.iter().copied(), &name_of)?;
+            write!(f, "] left [")?;
+            write_names(f, orphaned.iter(), &name_of)?;
+            write!(
+                f,
+                "] orphaned in archetype {}, violating invariant ",
+                self.archetype_id.index()
+            )?;
+            write_statement(f, &self.invariant.predicate, &name_of)?;
+            write!(f, " => ")?;
+            write_statement(f, &self.invariant.consequence, &name_of)?;
+            write!(f, ". Affected entities include {:?}.", self.entities)?;
+            return self.write_location(f);
+        }
+        write!(
+            f,
+            "Entity in archetype {} has components [",
+            self.archetype_id.index()
+        )?;
+        write_names(f, self.component_ids.iter().copied(), &name_of)?;
+        write!(f, "] but invariant ")?;
+        write_statement(f, &self.invariant.predicate, &name_of)?;
+        write!(f, " => ")?;
+        write_statement(f, &self.invariant.consequence, &name_of)?;
+        write!(f, " was violated.")?;
+        if self.entities.is_empty() {
+            write!(
+                f,
+                " There are no entities currently, but the archetype shape is forbidden."
+            )?;
+        } else {
+            write!(f, " Affected entities include {:?}.", self.entities)?;
+        }
+        self.write_location(f)
+    }
+}
+
+/// How seriously a violation of an individual [`UntypedArchetypeInvariant`] is treated by the automatic checks.
+///
+/// Use [`World::add_archetype_invariant_with_severity`] to add an invariant with a severity other than the default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy_reflect", derive(Reflect, FromReflect))]
+#[cfg_attr(
+    feature = "serialize",
+    derive(::serde::Serialize, ::serde::Deserialize)
+)]
+pub enum Severity {
+    /// The violation is handled according to the world's [`ViolationPolicy`], which panics by default.
+    #[default]
+    Error,
+    /// A warning describing the violation is logged, regardless of the world's [`ViolationPolicy`].
+    ///
+    /// This is useful for invariants that should be noticed during development, but are not worth crashing over.
+    /// Without the `archetype_invariant_logging` feature, the violation is ignored.
+    Warning,
+}
+
+/// Controls what happens when the automatic checks find an archetype that violates an archetype invariant.
+///
+/// Set it for a world using [`World::set_archetype_invariant_policy`].
+/// The policy only applies to invariants with [`Severity::Error`]:
+/// violations of invariants with [`Severity::Warning`] are always logged.
+///
+/// Only [`ViolationPolicy::Panic`] and [`ViolationPolicy::PanicWithAllViolations`] guarantee
+/// that no entity in the world violates an archetype invariant.
+/// Under [`ViolationPolicy::Warn`] and [`ViolationPolicy::Event`], the violating entity is still spawned
+/// or modified, and the world may continue to hold entities that violate its invariants.
+/// Each violating archetype is only reported once per invariant,
+/// even if more entities are later added to it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ViolationPolicy {
+    /// Panic with a message describing the violation.
+    ///
+    /// This panics as soon as the first violation is found.
+    #[default]
+    Panic,
+    /// Panic once with a message listing every violation found by a check.
+    ///
+    /// This is useful when adding invariants to a large existing world,
+    /// since every violating archetype can then be fixed at once, rather than one per run.
+    /// As every violation must be found before panicking, a check that fails takes longer than under [`ViolationPolicy::Panic`].
+    PanicWithAllViolations,
+    /// Log a warning describing the violation.
+    ///
+    /// Without the `archetype_invariant_logging` feature, the violation is ignored.
+    Warn,
+    /// Send an [`ArchetypeInvariantViolated`] event to the `Events<ArchetypeInvariantViolated>` resource,
+    /// which is inserted if it does not exist yet.
+    Event,
+}
+
+/// An event sent when an archetype invariant is violated while the world's [`ViolationPolicy`] is
+/// [`ViolationPolicy::Event`].
+///
+/// Register it with `App::add_event` to have these events cleaned up automatically.
+/// The `ArchetypeInvariantDiagnosticsPlugin` of `bevy_diagnostic` counts these events each frame as a diagnostic.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchetypeInvariantViolated {
+    /// The details of the violation.
+    pub violation: ArchetypeInvariantViolation,
+}
+
+/// Formats an [`UntypedArchetypeStatement`] with component names, see [`UntypedArchetypeStatement::display`].
+struct ArchetypeStatementDisplay<'a> {
+    statement: &'a UntypedArchetypeStatement,
+    components: &'a Components,
+}
+
+impl<'a> fmt::Display for ArchetypeStatementDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_statement(f, self.statement, &|id| component_name(self.components, id))
+    }
+}
+
+/// Formats an [`UntypedArchetypeInvariant`] with component names, see [`UntypedArchetypeInvariant::display`].
+struct ArchetypeInvariantDisplay<'a> {
+    invariant: &'a UntypedArchetypeInvariant,
+    components: &'a Components,
+}
+
+impl<'a> fmt::Display for ArchetypeInvariantDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name_of = |id| component_name(self.components, id);
+        write_statement(f, &self.invariant.predicate, &name_of)?;
+        write!(f, " => ")?;
+        write_statement(f, &self.invariant.consequence, &name_of)
+    }
+}
+
+/// Returns a human-readable name for the component with the given [`ComponentId`].
+fn component_name(components: &Components, id: ComponentId) -> String {
+    match components.get_info(id) {
+        Some(info) => get_short_name(info.name()),
+        None => format!("{:?}", id),
+    }
+}
+
+/// Writes a comma-separated list of component names, sorted alphabetically.
+///
+/// [`ComponentId`]s depend on the order in which components were registered,
+/// so sorting by name keeps messages identical across runs.
+fn write_names(
+    f: &mut fmt::Formatter,
+    component_ids: impl Iterator<Item = ComponentId>,
+    name_of: &dyn Fn(ComponentId) -> String,
+) -> fmt::Result {
+    write!(f, "{}", sorted_names(component_ids, name_of).join(", "))
+}
+
+/// Returns the names of the given components, sorted alphabetically.
+pub(crate) fn sorted_names(
+    component_ids: impl Iterator<Item = ComponentId>,
+    name_of: &dyn Fn(ComponentId) -> String,
+) -> Vec<String> {
+    let mut names: Vec<_> = component_ids.map(name_of).collect();
+    names.sort_unstable();
+    names
+}
+
+/// Writes `statement` in the form `AllOf(A, B)`, using `name_of` to render each component.
+fn write_statement(
+    f: &mut fmt::Formatter,
+    statement: &UntypedArchetypeStatement,
+    name_of: &dyn Fn(ComponentId) -> String,
+) -> fmt::Result {
+    let (kind, component_ids, n) = match statement {
+        UntypedArchetypeStatement::AllOf(set) => ("AllOf", set, None),
+        UntypedArchetypeStatement::AtLeastOneOf(set) => ("AtLeastOneOf", set, None),
+        UntypedArchetypeStatement::NoneOf(set) => ("NoneOf", set, None),
+        UntypedArchetypeStatement::ExactlyOneOf(set) => ("ExactlyOneOf", set, None),
+        UntypedArchetypeStatement::AtMostOneOf(set) => ("AtMostOneOf", set, None),
+        UntypedArchetypeStatement::ExactlyNOf(set, n) => ("ExactlyNOf", set, Some(n)),
+        UntypedArchetypeStatement::AtLeastNOf(set, n) => ("AtLeastNOf", set, Some(n)),
+        UntypedArchetypeStatement::AtMostNOf(set, n) => ("AtMostNOf", set, Some(n)),
+        UntypedArchetypeStatement::Exactly(set) => ("Exactly", set, None),
+        UntypedArchetypeStatement::SubsetOf { relevant, allowed } => {
+            write!(f, "SubsetOf([")?;
+            write_names(f, relevant.iter(), name_of)?;
+            write!(f, "], [")?;
+            write_names(f, allowed.iter(), name_of)?;
+            return write!(f, "])");
+        }
+        UntypedArchetypeStatement::ComponentCount { min, max } => {
+            write!(f, "ComponentCount(")?;
+            if let Some(min) = min {
+                write!(f, "{min}")?;
+            }
+            write!(f, "..")?;
+            if let Some(max) = max {
+                write!(f, "={max}")?;
+            }
+            return write!(f, ")");
+        }
+        UntypedArchetypeStatement::HasParent(parent) => {
+            return write!(f, "HasParent({})", name_of(*parent))
+        }
+        UntypedArchetypeStatement::HasChildren(children) => {
+            return write!(f, "HasChildren({})", name_of(*children))
+        }
+        UntypedArchetypeStatement::ResourcePresent(resource) => {
+            return write!(f, "ResourcePresent({})", name_of(*resource))
+        }
+        UntypedArchetypeStatement::StorageType {
+            components,
+            storage,
+            ..
+        } => {
+            write!(f, "StorageType({storage:?}, [")?;
+            write_names(f, components.iter(), name_of)?;
+            return write!(f, "])");
+        }
+        UntypedArchetypeStatement::GroupAllOf(group) => {
+            return write!(f, "GroupAllOf(#{})", group.index())
+        }
+        UntypedArchetypeStatement::GroupAtLeastOneOf(group) => {
+            return write!(f, "GroupAtLeastOneOf(#{})", group.index())
+        }
+        UntypedArchetypeStatement::Always => return write!(f, "Always"),
+        UntypedArchetypeStatement::Empty => return write!(f, "Empty"),
+        UntypedArchetypeStatement::And(statements) => {
+            return write_statements(f, "And", statements, name_of)
+        }
+        UntypedArchetypeStatement::Or(statements) => {
+            return write_statements(f, "Or", statements, name_of)
+        }
+        UntypedArchetypeStatement::Not(statement) => {
+            return write_statements(f, "Not", std::slice::from_ref(statement), name_of)
+        }
+    };
+    let component_ids = component_ids.iter();
+    match n {
+        Some(n) => {
+            write!(f, "{kind}({n}, [")?;
+            write_names(f, component_ids, name_of)?;
+            write!(f, "])")
+        }
+        None => {
+            write!(f, "{kind}(")?;
+            write_names(f, component_ids, name_of)?;
+            write!(f, ")")
+        }
+    }
+}
+
+/// Writes a combinator in the form `And(AllOf(A), NoneOf(B))`.
+fn write_statements(
+    f: &mut fmt::Formatter,
+    kind: &str,
+    statements: &[UntypedArchetypeStatement],
+    name_of: &dyn Fn(ComponentId) -> String,
+) -> fmt::Result {
+    write!(f, "{kind}(")?;
+    for (i, statement) in statements.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write_statement(f, statement, name_of)?;
+    }
+    write!(f, ")")
+}
+
+/// Returns `true` if `statement` is one of the kinds inspected by [`ArchetypeInvariants::validate_consistency`].
+fn is_simple_statement(statement: &UntypedArchetypeStatement) -> bool {
+    matches!(
+        statement,
+        UntypedArchetypeStatement::AllOf(_)
+            | UntypedArchetypeStatement::NoneOf(_)
+            | UntypedArchetypeStatement::AtLeastOneOf(_)
+    )
+}
+
+/// Returns `true` if some archetype satisfies all of the given statements,
+/// each of which must be accepted by [`is_simple_statement`].
+///
+/// This is exact: the statements are satisfiable unless a required component is also forbidden,
+/// or every component of an `AtLeastOneOf` is forbidden, since the archetype made of the required components
+/// and one allowed component of each `AtLeastOneOf` satisfies all of them.
+fn simple_statements_satisfiable(statements: &[&UntypedArchetypeStatement]) -> bool {
+    let mut required = ComponentIdSet::new();
+    let mut forbidden = ComponentIdSet::new();
+    for statement in statements {
+        match statement {
+            UntypedArchetypeStatement::AllOf(set) => required.extend(set.iter()),
+            UntypedArchetypeStatement::NoneOf(set) => forbidden.extend(set.iter()),
+            _ => {}
+        }
+    }
+    required.is_disjoint(&forbidden)
+        && statements.iter().all(|statement| match statement {
+            UntypedArchetypeStatement::AtLeastOneOf(set) => !set.is_subset(&forbidden),
+            _ => true,
+        })
+}
+
+/// A unique identifier for an archetype invariant stored in [`ArchetypeInvariants`].
+///
+/// Identifiers are never reused, even after the invariant they refer to has been removed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ArchetypeInvariantId(u64);
+
+impl ArchetypeInvariantId {
+    #[inline]
+    pub fn index(self) -> u64 {
+        self.0
+    }
+}
+
+/// A unique identifier for a component group registered with [`ArchetypeInvariants::register_component_group`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct GroupId(usize);
+
+impl GroupId {
+    /// Creates a new [`GroupId`] with the given index.
+    ///
+    /// Group identifiers are assigned in the order groups are registered, starting at zero.
+    #[inline]
+    pub const fn new(index: usize) -> GroupId {
+        GroupId(index)
+    }
+
+    #[inline]
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// The result of [`ArchetypeInvariants::compute_implication_closure`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImplicationClosure {
+    /// Invariants of the form `AllOf => AllOf` that follow from chains of the existing invariants,
+    /// but which are not stated by any single one of them.
+    pub derived: Vec<UntypedArchetypeInvariant>,
+    /// The invariants of the form `AllOf => AllOf` whose consequence, through a chain of other invariants,
+    /// implies their own predicate. The components involved in such a cycle must always appear together.
+    pub cycles: Vec<ArchetypeInvariantId>,
+}
+
+/// A callback that is run for each entity that violates an archetype invariant,
+/// instead of handling the violation according to the world's [`ViolationPolicy`].
+///
+/// Add an invariant with a handler using [`World::add_archetype_invariant_with_handler`].
+pub type ArchetypeInvariantHandler =
+    Box<dyn Fn(&mut World, Entity, &UntypedArchetypeInvariant) + Send + Sync>;
+
+/// An [`ArchetypeInvariantHandler`] that can be cloned out of [`ArchetypeInvariants`] to be run on the [`World`] that owns it.
+type SharedHandler = Arc<dyn Fn(&mut World, Entity, &UntypedArchetypeInvariant) + Send + Sync>;
+
+/// Counters describing how much work the automatic archetype invariant checks have done,
+/// returned by [`ArchetypeInvariants::stats`].
+///
+/// Only the incremental checks that run as archetypes and invariants are added are counted,
+/// not explicit calls such as [`ArchetypeInvariants::check_archetypes`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ArchetypeInvariantStats {
+    /// The number of times an archetype was checked against a batch of invariants.
+    ///
+    /// Each new archetype is checked once against all invariants,
+    /// and each existing archetype is checked once against each batch of newly added invariants.
+    pub archetype_checks: u64,
+    /// The number of times an invariant was evaluated against an archetype.
+    pub invariant_evaluations: u64,
+    /// The number of predicates and consequences evaluated.
+    ///
+    /// A consequence is only evaluated if its predicate holds,
+    /// so this is between one and two times [`ArchetypeInvariantStats::invariant_evaluations`].
+    pub statement_evaluations: u64,
+    /// The total time spent checking archetypes.
+    ///
+    /// Only measured if the `archetype_invariant_timing` feature is enabled.
+    #[cfg(feature = "archetype_invariant_timing")]
+    pub time_spent: bevy_utils::Duration,
+}
+
+/// An [`UntypedArchetypeInvariant`] that has been added to [`ArchetypeInvariants`].
+#[derive(Clone)]
+struct StoredArchetypeInvariant {
+    id: ArchetypeInvariantId,
+    invariant: UntypedArchetypeInvariant,
+    /// The group this invariant belongs to, which allows it to be removed together with the rest of its group.
+    label: Option<Cow<'static, str>>,
+    /// The callback that violations of this invariant are passed to, if any.
+    handler: Option<SharedHandler>,
+    /// Whether the invariant depends on the world's resources, see [`UntypedArchetypeInvariant::uses_resources`].
+    uses_resources: bool,
+    /// The invariant with each component group replaced by its current members,
+    /// if it refers to any groups, see [`UntypedArchetypeStatement::uses_groups`].
+    resolved: Option<UntypedArchetypeInvariant>,
+    /// Whether the invariant is skipped by the automatic checks, see [`ArchetypeInvariants::add_deferred`].
+    deferred: bool,
+    /// Whether the invariant is checked at all, see [`ArchetypeInvariants::set_enabled`].
+    enabled: bool,
+    /// The entities whose violations of the invariant are not reported, see [`ArchetypeInvariants::exempt_entity`].
+    exempt_entities: HashSet<Entity>,
+    /// Where in the source code the invariant was added, see [`ArchetypeInvariants::location`].
+    location: &'static Location<'static>,
+}
+
+/// How an invariant added to [`ArchetypeInvariants`] is stored, which differs between the `add` methods.
+struct StoredInvariantOptions {
+    /// See [`ArchetypeInvariants::add_labeled`].
+    label: Option<Cow<'static, str>>,
+    /// See [`ArchetypeInvariants::add_with_handler`].
+    handler: Option<SharedHandler>,
+    /// See [`ArchetypeInvariants::add_deferred`].
+    deferred: bool,
+    /// See [`ArchetypeInvariants::add_disabled`].
+    enabled: bool,
+}
+
+impl Default for StoredInvariantOptions {
+    fn default() -> Self {
+        Self {
+            label: None,
+            handler: None,
+            deferred: false,
+            enabled: true,
+        }
+    }
+}
+
+impl StoredArchetypeInvariant {
+    /// Returns the invariant that archetypes are checked against, with every component group resolved.
+    fn checked(&self) -> &UntypedArchetypeInvariant {
+        self.resolved.as_ref().unwrap_or(&self.invariant)
+    }
+
+    /// Resolves the component groups of the invariant again, after their members changed.
+    fn resolve_groups(&mut self, groups: &[(Cow<'static, str>, ComponentIdSet)]) {
+        if self.invariant.predicate.uses_groups() || self.invariant.consequence.uses_groups() {
+            self.resolved = Some(UntypedArchetypeInvariant {
+                predicate: self.invariant.predicate.resolve_groups(groups),
+                consequence: self.invariant.consequence.resolve_groups(groups),
+                severity: self.invariant.severity,
+            });
+        }
+    }
+
+    /// Returns the entities of `archetype` that are not exempt from this invariant.
+    fn unexempt_entities<'a>(
+        &'a self,
+        archetype: &'a Archetype,
+    ) -> impl Iterator<Item = Entity> + 'a {
+        archetype
+            .entities()
+            .iter()
+            .copied()
+            .filter(|entity| !self.exempt_entities.contains(entity))
+    }
+
+    /// Returns `true` if `archetype` contains entities, all of which are exempt from this invariant,
+    /// so that its violations of the invariant are not reported.
+    fn exempts_every_entity(&self, archetype: &Archetype) -> bool {
+        !archetype.is_empty() && self.unexempt_entities(archetype).next().is_none()
+    }
+
+    /// Creates a violation of this invariant by `archetype`, sampling up to
+    /// [`ArchetypeInvariantViolation::MAX_SAMPLED_ENTITIES`] of its entities that are not exempt from it.
+    ///
+    /// Returns `None` if every entity of the archetype is exempt from this invariant.
+    fn violation(
+        &self,
+        archetype: &Archetype,
+        components: &Components,
+    ) -> Option<ArchetypeInvariantViolation> {
+        if self.exempts_every_entity(archetype) {
+            return None;
+        }
+        let mut violation = ArchetypeInvariantViolation::new(
+            archetype.id(),
+            archetype.components().collect(),
+            self.unexempt_entities(archetype)
+                .take(ArchetypeInvariantViolation::MAX_SAMPLED_ENTITIES)
+                .collect(),
+            self.checked().clone(),
+            components,
+        );
+        violation.location = Some(self.location);
+        Some(violation)
+    }
+}
+
+/// A copy of the archetype invariants of a [`World`], taken with [`ArchetypeInvariants::snapshot`].
+///
+/// Restoring it with [`World::restore_archetype_invariants`] brings back every invariant with its
+/// [`ArchetypeInvariantId`], label and handler, and whether it is enabled or deferred,
+/// which makes it suitable for undo and redo in editors.
+#[derive(Clone)]
+pub struct ArchetypeInvariantsSnapshot {
+    invariants: Vec<StoredArchetypeInvariant>,
+}
+
+impl ArchetypeInvariantsSnapshot {
+    /// Returns an iterator over the invariants in this snapshot, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (ArchetypeInvariantId, &UntypedArchetypeInvariant)> {
+        self.invariants
+            .iter()
+            .map(|stored| (stored.id, &stored.invariant))
+    }
+}
+
+/// An iterator over the archetypes that violate an archetype invariant, returned by [`ArchetypeInvariants::iter_violating_archetypes`].
+///
+/// Archetypes are only checked as the iterator advances, so stopping early avoids checking the remaining ones.
+pub struct ViolatingArchetypes<'a> {
+    invariants: &'a ArchetypeInvariants,
+    archetypes: &'a Archetypes,
+    resources: ComponentIdSet,
+    /// The index of the archetype currently being checked.
+    archetype_index: usize,
+    /// The index in `raw_list` of the next invariant to check the current archetype against.
+    invariant_index: usize,
+}
+
+impl<'a> Iterator for ViolatingArchetypes<'a> {
+    type Item = (ArchetypeId, &'a UntypedArchetypeInvariant);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let raw_list = &self.invariants.raw_list;
+        while let Some(archetype) = self.archetypes.archetypes.get(self.archetype_index) {
+            // The resource archetype never contains any entities.
+            let skipped = archetype.id() == ArchetypeId::RESOURCE
+                || (!self.invariants.check_empty_archetypes && archetype.is_empty());
+            while !skipped && self.invariant_index < raw_list.len() {
+                let stored = &raw_list[self.invariant_index];
+                self.invariant_index += 1;
+                if stored.enabled
+                    && !stored
+                        .checked()
+                        .evaluate_against_archetype_with_resources(archetype, &self.resources)
+                    && !stored.exempts_every_entity(archetype)
+                {
+                    return Some((archetype.id(), stored.checked()));
+                }
+            }
+            self.archetype_index += 1;
+            self.invariant_index = 0;
+        }
+        None
+    }
+}
+
+/// A violation of an invariant with an [`ArchetypeInvariantHandler`], whose handler has not been run yet.
+#[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+pub(crate) struct PendingHandlerCall {
+    pub(crate) handler: SharedHandler,
+    pub(crate) invariant: UntypedArchetypeInvariant,
+    /// The entities in the violating archetype at the time the violation was found.
+    pub(crate) entities: Vec<Entity>,
+}
+
+/// A list of [`UntypedArchetypeInvariant`]s to be stored on a [`World`].
+pub struct ArchetypeInvariants {
+    /// The list of invariants that must be upheld, in the order they were added.
+    raw_list: Vec<StoredArchetypeInvariant>,
+    /// The index of the first archetype that has not yet been checked against these invariants.
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    last_checked_archetype_index: usize,
+    /// The index in `raw_list` of the first invariant that has not yet been checked
+    /// against the archetypes before `last_checked_archetype_index`.
+    first_unchecked_invariant_index: usize,
+    /// The identifier given to the next invariant that is added.
+    next_id: u64,
+    /// The sum of the [`UntypedArchetypeInvariant::complexity`] of every invariant in `raw_list`.
+    total_complexity: usize,
+    /// `total_complexity` times the number of archetypes, as of the last time archetypes were checked.
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    checked_cost: usize,
+    /// The [`ComponentId`]s of each [`Bundle`] used in an [`ArchetypeStatement`], keyed by the bundle's [`TypeId`].
+    bundle_component_ids: HashMap<TypeId, ComponentIdSet>,
+    /// What to do when a violation is found.
+    policy: ViolationPolicy,
+    /// Whether to warn about archetype statements that are valid, but commonly misunderstood.
+    strict: bool,
+    /// Whether archetypes without entities are checked.
+    check_empty_archetypes: bool,
+    /// Whether removals of components are checked against the paired invariants.
+    check_removals: bool,
+    /// Whether archetypes are checked as soon as they are created, rather than only when requested.
+    automatic_checks: bool,
+    /// The components that mark an entity as a child and as a parent, see [`ArchetypeInvariants::set_hierarchy_components`].
+    hierarchy_components: Option<(ComponentId, ComponentId)>,
+    /// The new archetypes and invariants whose violations were already reported by a removal check,
+    /// which are skipped by the next automatic check so that each violation is only reported once.
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    reported_removals: Vec<(ArchetypeId, ArchetypeInvariantId)>,
+    /// The archetypes that were skipped by the automatic checks because they contained no entities,
+    /// and which must be checked against all invariants once they do.
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    skipped_empty_archetypes: Vec<ArchetypeId>,
+    /// The archetypes that contained no entities when an invariant was enabled again,
+    /// and which must be checked against that invariant once they do.
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    skipped_reenabled_archetypes: Vec<(ArchetypeId, ArchetypeInvariantId)>,
+    /// The archetypes whose violations of an invariant were not reported because all of their entities were exempt from it,
+    /// and which must be checked against that invariant again once they contain an entity that is not.
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    skipped_exempt_archetypes: Vec<(ArchetypeId, ArchetypeInvariantId)>,
+    /// Violations found under [`ViolationPolicy::Event`] which have not been sent as events yet.
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    unreported_violations: Vec<ArchetypeInvariantViolation>,
+    /// Violations of invariants with a handler, whose handlers have not been run yet.
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    pending_handler_calls: Vec<PendingHandlerCall>,
+    /// How many handlers are currently running, each one nested in the one before it.
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    pub(crate) handler_depth: usize,
+    /// How much work the automatic checks have done.
+    stats: ArchetypeInvariantStats,
+    /// Whether a resource was inserted or removed since the last check,
+    /// so the invariants that depend on resources must be re-checked against every archetype.
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    resources_changed: bool,
+    /// The name and members of each component group, indexed by [`GroupId`].
+    component_groups: Vec<(Cow<'static, str>, ComponentIdSet)>,
+    /// Whether the members of a component group changed since the last check,
+    /// so the invariants that refer to groups must be re-checked against every archetype.
+    groups_changed: bool,
+    /// The invariants that were re-enabled since the last check, and must be re-checked against every archetype.
+    reenabled_invariants: Vec<ArchetypeInvariantId>,
+    /// The number of archetypes from which a batch of archetypes is checked in parallel.
+    parallel_check_threshold: usize,
+}
+
+impl Default for ArchetypeInvariants {
+    fn default() -> Self {
+        Self {
+            raw_list: Vec::new(),
+            last_checked_archetype_index: 0,
+            first_unchecked_invariant_index: 0,
+            next_id: 0,
+            total_complexity: 0,
+            checked_cost: 0,
+            bundle_component_ids: HashMap::default(),
+            policy: ViolationPolicy::default(),
+            strict: false,
+            check_empty_archetypes: true,
+            check_removals: false,
+            automatic_checks: true,
+            hierarchy_components: None,
+            reported_removals: Vec::new(),
+            skipped_empty_archetypes: Vec::new(),
+            skipped_reenabled_archetypes: Vec::new(),
+            skipped_exempt_archetypes: Vec::new(),
+            unreported_violations: Vec::new(),
+            pending_handler_calls: Vec::new(),
+            handler_depth: 0,
+            stats: ArchetypeInvariantStats::default(),
+            resources_changed: false,
+            component_groups: Vec::new(),
+            groups_changed: false,
+            reenabled_invariants: Vec::new(),
+            parallel_check_threshold: Self::DEFAULT_PARALLEL_CHECK_THRESHOLD,
+        }
+    }
+}
+
+impl ArchetypeInvariants {
+    /// The maximum number of [`ArchetypeInvariantHandler`]s that can run nested inside each other.
+    ///
+    /// A handler that changes the archetype of an entity may cause further violations, whose handlers run
+    /// before it returns. If this goes on for more than this many levels, an error is logged and
+    /// the remaining handlers are not run, rather than recursing forever.
+    pub const MAX_HANDLER_DEPTH: usize = 8;
+
+    /// The default value of [`ArchetypeInvariants::parallel_check_threshold`].
+    pub const DEFAULT_PARALLEL_CHECK_THRESHOLD: usize = 4096;
+
+    /// The estimated cost of checking every archetype against every invariant
+    /// above which checking archetypes logs a warning.
+    ///
+    /// See [`ArchetypeInvariants::complexity`].
+    pub const COMPLEXITY_WARNING_THRESHOLD: usize = 1_000_000;
+
+    /// Creates an empty set of archetype invariants, which has not been checked against any archetypes yet.
+    ///
+    /// This is the same as [`ArchetypeInvariants::default`].
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty set of archetype invariants with room for at least `capacity` invariants,
+    /// which has not been checked against any archetypes yet.
+    ///
+    /// This avoids reallocating while a known number of invariants is added, such as at startup.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            raw_list: Vec::with_capacity(capacity),
+            ..Self::default()
+        }
+    }
+
+    /// Adds a new [`UntypedArchetypeInvariant`] to this set of archetype invariants,
+    /// returning an [`ArchetypeInvariantId`] that can be used to remove it again.
+    ///
+    /// The next time archetypes are checked, all existing archetypes are checked against the new invariant.
+    /// This may include empty archetypes: archetypes that contain no entities,
+    /// unless they are skipped using [`ArchetypeInvariants::set_check_empty_archetypes`].
+    /// Existing archetypes are not re-checked against the invariants that were already present.
+    ///
+    /// If an identical invariant has already been added, a warning is logged and
+    /// the [`ArchetypeInvariantId`] of the existing invariant is returned instead.
+    /// If an existing invariant already makes the new one redundant, as decided by [`UntypedArchetypeInvariant::subsumes`],
+    /// a warning naming both is logged, but the new invariant is still added.
+    ///
+    /// If the invariant can obviously never be satisfied by an entity that matches its predicate,
+    /// a warning naming the offending components is logged.
+    /// `components` is used to look up those names.
+    #[track_caller]
+    pub fn add(
+        &mut self,
+        archetype_invariant: UntypedArchetypeInvariant,
+        components: &Components,
+    ) -> ArchetypeInvariantId {
+        self.insert(
+            archetype_invariant,
+            StoredInvariantOptions::default(),
+            components,
+        )
+    }
+
+    /// Adds a new [`UntypedArchetypeInvariant`] to this set of archetype invariants in its disabled state,
+    /// so that it is not enforced until it is enabled with [`ArchetypeInvariants::set_enabled`].
+    ///
+    /// This is useful for invariants that only hold once some setup has completed,
+    /// such as after the startup systems of an app have run.
+    /// If an identical invariant has already been added, its [`ArchetypeInvariantId`] is returned and it is left unchanged.
+    ///
+    /// See [`ArchetypeInvariants::add`] for more details.
+    #[track_caller]
+    pub fn add_disabled(
+        &mut self,
+        archetype_invariant: UntypedArchetypeInvariant,
+        components: &Components,
+    ) -> ArchetypeInvariantId {
+        let options = StoredInvariantOptions {
+            enabled: false,
+            ..Default::default()
+        };
+        self.insert(archetype_invariant, options, components)
+    }
+
+    /// Adds a new [`UntypedArchetypeInvariant`] to this set of archetype invariants, as part of the group `label`.
+    ///
+    /// All invariants with the same label can be removed together using [`ArchetypeInvariants::remove_by_label`].
+    /// An invariant is only considered a duplicate of an existing invariant if their labels also match.
+    ///
+    /// See [`ArchetypeInvariants::add`] for more details.
+    #[track_caller]
+    pub fn add_labeled(
+        &mut self,
+        archetype_invariant: UntypedArchetypeInvariant,
+        label: impl Into<Cow<'static, str>>,
+        components: &Components,
+    ) -> ArchetypeInvariantId {
+        let options = StoredInvariantOptions {
+            label: Some(label.into()),
+            ..Default::default()
+        };
+        self.insert(archetype_invariant, options, components)
+    }
+
+    /// Adds a new [`UntypedArchetypeInvariant`] to this set of archetype invariants,
+    /// whose violations are passed to `handler` instead of being handled according to the [`ViolationPolicy`].
+    ///
+    /// The handler is run by the [`World`] for each entity in a violating archetype. Handlers can not be compared,
+    /// so an invariant with a handler is never considered a duplicate of an existing invariant.
+    ///
+    /// See [`ArchetypeInvariants::add`] for more details.
+    #[track_caller]
+    pub fn add_with_handler(
+        &mut self,
+        archetype_invariant: UntypedArchetypeInvariant,
+        handler: ArchetypeInvariantHandler,
+        components: &Components,
+    ) -> ArchetypeInvariantId {
+        let options = StoredInvariantOptions {
+            handler: Some(handler.into()),
+            ..Default::default()
+        };
+        self.insert(archetype_invariant, options, components)
+    }
+
+    /// Adds a new [`UntypedArchetypeInvariant`] to this set of archetype invariants,
+    /// which is skipped by the automatic checks.
+    ///
+    /// Deferred invariants are only checked by [`World::check_deferred_archetype_invariants`],
+    /// and only against archetypes which contain entities at that time.
+    /// An invariant is only considered a duplicate of an existing invariant if both are deferred, or neither is.
+    ///
+    /// See [`ArchetypeInvariants::add`] for more details.
+    #[track_caller]
+    pub fn add_deferred(
+        &mut self,
+        archetype_invariant: UntypedArchetypeInvariant,
+        components: &Components,
+    ) -> ArchetypeInvariantId {
+        let options = StoredInvariantOptions {
+            deferred: true,
+            ..Default::default()
+        };
+        self.insert(archetype_invariant, options, components)
+    }
+
+    /// Stores a new invariant as described by `options`, which every `add` method forwards to.
+    #[track_caller]
+    fn insert(
+        &mut self,
+        archetype_invariant: UntypedArchetypeInvariant,
+        options: StoredInvariantOptions,
+        components: &Components,
+    ) -> ArchetypeInvariantId {
+        let StoredInvariantOptions {
+            label,
+            handler,
+            deferred,
+            enabled,
+        } = options;
+        // A disabled invariant does not enforce anything, so it can not stand in for an enabled one
+        if let Some(existing) = self.raw_list.iter().find(|stored| {
+            handler.is_none()
+                && stored.handler.is_none()
+                && stored.invariant == archetype_invariant
+                && stored.label == label
+                && stored.deferred == deferred
+                && (stored.enabled || !enabled)
+        }) {
+            warn!("An archetype invariant was added that is identical to an existing one. The duplicate was ignored.");
+            return existing.id;
+        }
+
+        // Invariants with a handler do more than reject archetypes, so they are never redundant
+        if let Some(existing) = self.raw_list.iter().find(|stored| {
+            handler.is_none()
+                && stored.handler.is_none()
+                && stored.enabled
+                && !stored.deferred
+                && (stored.invariant.severity == Severity::Error
+                    || archetype_invariant.severity == Severity::Warning)
+                && stored.invariant.subsumes(&archetype_invariant)
+        }) {
+            warn!(
+                "The archetype invariant {} was added, but it is implied by the existing invariant {}, so it is redundant.",
+                ArchetypeInvariantDisplay {
+                    invariant: &archetype_invariant,
+                    components
+                },
+                ArchetypeInvariantDisplay {
+                    invariant: &existing.invariant,
+                    components
+                }
+            );
+        }
+
+        let contradictions = archetype_invariant.contradictions();
+        if !contradictions.is_empty() {
+            let names: Vec<String> = contradictions
+                .into_iter()
+                .map(|id| component_name(components, id))
+                .collect();
+            warn!(
+                "An archetype invariant was added that both requires and forbids [{}]. Any entity with these components will violate it.",
+                names.join(", ")
+            );
+        }
+
+        if archetype_invariant.consequence.uses_resources() {
+            warn!("An archetype invariant was added whose consequence depends on whether a resource exists. `ResourcePresent` is only intended for predicates.");
+        }
+
+        let id = ArchetypeInvariantId(self.next_id);
+        self.next_id += 1;
+        let mut stored = StoredArchetypeInvariant {
+            id,
+            uses_resources: archetype_invariant.uses_resources(),
+            invariant: archetype_invariant,
+            resolved: None,
+            label,
+            handler,
+            deferred,
+            enabled,
+            exempt_entities: HashSet::default(),
+            location: Location::caller(),
+        };
+        stored.resolve_groups(&self.component_groups);
+        self.total_complexity += stored.invariant.complexity();
+        self.raw_list.push(stored);
+        id
+    }
+
+    /// Removes the archetype invariant with the given [`ArchetypeInvariantId`], returning it.
+    ///
+    /// Returns `None` if no such invariant exists, for example because it was already removed.
+    /// Removing an invariant can only relax the rules that archetypes must follow,
+    /// so no archetypes are re-checked.
+    pub fn remove(&mut self, id: ArchetypeInvariantId) -> Option<UntypedArchetypeInvariant> {
+        let index = self.raw_list.iter().position(|stored| stored.id == id)?;
+        if index < self.first_unchecked_invariant_index {
+            self.first_unchecked_invariant_index -= 1;
+        }
+        let invariant = self.raw_list.remove(index).invariant;
+        self.total_complexity -= invariant.complexity();
+        Some(invariant)
+    }
+
+    /// Removes all archetype invariants that were added with the given label, returning them in the order they were added.
+    ///
+    /// Like [`ArchetypeInvariants::remove`], this does not cause any archetypes to be re-checked.
+    pub fn remove_by_label(&mut self, label: &str) -> Vec<UntypedArchetypeInvariant> {
+        let mut removed = Vec::new();
+        let mut index = 0;
+        while index < self.raw_list.len() {
+            if self.raw_list[index].label.as_deref() == Some(label) {
+                if index < self.first_unchecked_invariant_index {
+                    self.first_unchecked_invariant_index -= 1;
+                }
+                let invariant = self.raw_list.remove(index).invariant;
+                self.total_complexity -= invariant.complexity();
+                removed.push(invariant);
+            } else {
+                index += 1;
+            }
+        }
+        removed
+    }
+
+    /// Enables or disables the archetype invariant with the given [`ArchetypeInvariantId`],
+    /// returning `false` if no such invariant exists.
+    ///
+    /// Disabled invariants are skipped by every check, but keep their id and their position among the other invariants.
+    /// When an invariant is enabled again, it is re-checked against every archetype the next time archetypes are checked,
+    /// since archetypes may have been created or filled with entities while it was disabled.
+    /// Archetypes that contain no entities at that point, such as the intermediate shapes of entities
+    /// that were built up over several systems while the invariant was disabled, are only checked against it
+    /// once they contain entities again.
+    pub fn set_enabled(&mut self, id: ArchetypeInvariantId, enabled: bool) -> bool {
+        let stored = match self.raw_list.iter_mut().find(|stored| stored.id == id) {
+            Some(stored) => stored,
+            None => return false,
+        };
+        if enabled && !stored.enabled && !self.reenabled_invariants.contains(&id) {
+            self.reenabled_invariants.push(id);
+        }
+        if !enabled {
+            self.skipped_reenabled_archetypes
+                .retain(|&(_, invariant_id)| invariant_id != id);
+        }
+        stored.enabled = enabled;
+        true
+    }
+
+    /// Returns where in the source code the archetype invariant with the given [`ArchetypeInvariantId`] was added,
+    /// or `None` if no such invariant exists.
+    ///
+    /// This is the location of the call to the `add` method of [`World`], [`ArchetypeInvariants`]
+    /// or `App` that added it, and is included in the messages of its violations.
+    /// Derived invariants registered by [`ArchetypeInvariants::compute_implication_closure`]
+    /// are located at the call that computed them.
+    pub fn location(&self, id: ArchetypeInvariantId) -> Option<&'static Location<'static>> {
+        self.raw_list
+            .iter()
+            .find(|stored| stored.id == id)
+            .map(|stored| stored.location)
+    }
+
+    /// Returns whether the archetype invariant with the given [`ArchetypeInvariantId`] is enabled,
+    /// or `None` if no such invariant exists.
+    ///
+    /// See [`ArchetypeInvariants::set_enabled`].
+    pub fn is_enabled(&self, id: ArchetypeInvariantId) -> Option<bool> {
+        self.raw_list
+            .iter()
+            .find(|stored| stored.id == id)
+            .map(|stored| stored.enabled)
+    }
+
+    /// Exempts `entity` from the archetype invariant with the given [`ArchetypeInvariantId`],
+    /// returning `false` if no such invariant exists.
+    ///
+    /// This is meant for the rare entities that legitimately break an otherwise global rule, such as bookkeeping singletons.
+    /// Invariants are checked against the shape of archetypes rather than against entities,
+    /// so an exemption does not change whether an archetype upholds the invariant. Instead, it applies when violations are reported:
+    /// exempt entities are left out of the sampled [`ArchetypeInvariantViolation::entities`] and of the calls to the invariant's handler,
+    /// and a violating archetype is not reported at all while every entity in it is exempt.
+    /// Such an archetype is checked against the invariant again once an entity that is not exempt joins it.
+    ///
+    /// Archetypes are checked once the operation that created them has finished, so exempt an entity before moving it
+    /// into a violating archetype, for example by spawning it with [`World::spawn_empty`] and inserting its components afterwards.
+    /// Archetypes without entities are always reported, as there is no entity to exempt.
+    pub fn exempt_entity(&mut self, id: ArchetypeInvariantId, entity: Entity) -> bool {
+        match self.raw_list.iter_mut().find(|stored| stored.id == id) {
+            Some(stored) => {
+                stored.exempt_entities.insert(entity);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns `true` if `entity` is exempt from the archetype invariant with the given [`ArchetypeInvariantId`].
+    ///
+    /// See [`ArchetypeInvariants::exempt_entity`].
+    pub fn is_entity_exempt(&self, id: ArchetypeInvariantId, entity: Entity) -> bool {
+        self.raw_list
+            .iter()
+            .any(|stored| stored.id == id && stored.exempt_entities.contains(&entity))
+    }
+
+    /// Returns the enabled archetype invariants that an entity with the given components violates, unless it is exempt from them.
+    ///
+    /// See [`World::archetype_invariant_violations_for_entity`].
+    pub(crate) fn violations_for_entity(
+        &self,
+        entity: Entity,
+        component_ids: &ComponentIdSet,
+        resources: &ComponentIdSet,
+    ) -> Vec<&UntypedArchetypeInvariant> {
+        self.raw_list
+            .iter()
+            .filter(|stored| {
+                stored.enabled
+                    && !stored.exempt_entities.contains(&entity)
+                    && !stored
+                        .checked()
+                        .evaluate_with_resources(component_ids, resources)
+            })
+            .map(StoredArchetypeInvariant::checked)
+            .collect()
+    }
+
+    /// Returns the sum of the [`UntypedArchetypeInvariant::complexity`] of every invariant, including disabled ones.
+    ///
+    /// Each new archetype is checked against every invariant, and each new invariant against every archetype,
+    /// so this multiplied by the number of archetypes estimates the cost of checking all of them against each other.
+    /// A warning is logged when archetypes are checked and that estimate has come to exceed
+    /// [`ArchetypeInvariants::COMPLEXITY_WARNING_THRESHOLD`], whether because invariants were added or archetypes were created.
+    #[inline]
+    pub fn complexity(&self) -> usize {
+        self.total_complexity
+    }
+
+    /// Computes the transitive closure of the invariants of the simple `AllOf => AllOf` form,
+    /// logging every implied invariant and every cycle that is found.
+    ///
+    /// For example, if `A` requires `B` and `B` requires `C`, then `A` also requires `C`.
+    /// Invariants of any other form are ignored.
+    ///
+    /// This is a diagnostic: since derived invariants follow from the existing ones,
+    /// they never reject an archetype that the existing invariants accept.
+    /// They are only added to this set if `register_derived` is `true`,
+    /// in which case their [`ArchetypeInvariants::location`] is the call to this method.
+    /// `components` is used to look up the names of the involved components when logging.
+    #[track_caller]
+    pub fn compute_implication_closure(
+        &mut self,
+        register_derived: bool,
+        components: &Components,
+    ) -> ImplicationClosure {
+        let simple: Vec<_> = self
+            .raw_list
+            .iter()
+            .filter_map(|stored| match &stored.invariant {
+                UntypedArchetypeInvariant {
+                    predicate: UntypedArchetypeStatement::AllOf(predicate),
+                    consequence: UntypedArchetypeStatement::AllOf(consequence),
+                    ..
+                } => Some((stored.id, predicate, consequence)),
+                _ => None,
+            })
+            .collect();
+
+        // Every component implied by having all of the `start` components
+        let closure_of = |start: &ComponentIdSet| {
+            let mut closure = start.clone();
+            loop {
+                let mut changed = false;
+                for (_, predicate, consequence) in &simple {
+                    if predicate.is_subset(&closure) && !consequence.is_subset(&closure) {
+                        closure.extend(consequence.iter());
+                        changed = true;
+                    }
+                }
+                if !changed {
+                    return closure;
+                }
+            }
+        };
+
+        let mut closure = ImplicationClosure::default();
+        let mut predicates: Vec<&ComponentIdSet> = Vec::new();
+        for (_, predicate, _) in &simple {
+            if !predicates.contains(predicate) {
+                predicates.push(predicate);
+            }
+        }
+        for predicate in predicates {
+            let mut stated = predicate.clone();
+            for (_, other_predicate, consequence) in &simple {
+                if *other_predicate == predicate {
+                    stated.extend(consequence.iter());
+                }
+            }
+            let implied: ComponentIdSet = closure_of(predicate)
+                .iter()
+                .filter(|&id| !stated.contains(id))
+                .collect();
+            if !implied.is_empty() {
+                closure.derived.push(UntypedArchetypeInvariant {
+                    predicate: UntypedArchetypeStatement::AllOf(predicate.clone()),
+                    consequence: UntypedArchetypeStatement::AllOf(implied),
+                    severity: Severity::Error,
+                });
+            }
+        }
+        for (id, predicate, consequence) in &simple {
+            if !predicate.is_subset(consequence) && predicate.is_subset(&closure_of(consequence)) {
+                closure.cycles.push(*id);
+            }
+        }
+
+        for derived in &closure.derived {
+            let derived = ArchetypeInvariantDisplay {
+                invariant: derived,
+                components,
+            };
+            info!("The archetype invariant {derived} is implied by a chain of existing archetype invariants.");
+        }
+        for id in &closure.cycles {
+            let stored = self
+                .raw_list
+                .iter()
+                .find(|stored| stored.id == *id)
+                .unwrap();
+            let invariant = ArchetypeInvariantDisplay {
+                invariant: &stored.invariant,
+                components,
+            };
+            info!("The archetype invariant {invariant} is part of a cycle: its consequence implies its predicate, so these components must always appear together.");
+        }
+
+        if register_derived {
+            for derived in &closure.derived {
+                self.add(derived.clone(), components);
+            }
+        }
+        closure
+    }
+
+    /// Finds pairs of invariants that can never both hold for an entity that matches both of their predicates.
+    ///
+    /// Only invariants whose predicate and consequence are each an [`UntypedArchetypeStatement::AllOf`],
+    /// [`UntypedArchetypeStatement::NoneOf`] or [`UntypedArchetypeStatement::AtLeastOneOf`] are inspected,
+    /// so an empty result does not guarantee that the invariants are consistent.
+    /// A pair is only returned if some archetype matches both predicates and each invariant could be upheld on its own,
+    /// but every such archetype violates at least one of them.
+    /// For example, `A` requiring `B` conflicts with `C` forbidding `B`, since an entity with both `A` and `C` violates one of them.
+    /// On the other hand, `A` requiring `B` does not conflict with `C` forbidding `A`, since no entity with `C` may have `A` at all.
+    ///
+    /// Each pair lists the invariant that was added first before the other, and pairs are sorted in the order the invariants were added.
+    pub fn validate_consistency(&self) -> Vec<(ArchetypeInvariantId, ArchetypeInvariantId)> {
+        let simple: Vec<_> = self
+            .raw_list
+            .iter()
+            .filter(|stored| {
+                is_simple_statement(&stored.invariant.predicate)
+                    && is_simple_statement(&stored.invariant.consequence)
+            })
+            .collect();
+
+        let mut conflicts = Vec::new();
+        for (i, first) in simple.iter().enumerate() {
+            for second in &simple[i + 1..] {
+                let predicates = [&first.invariant.predicate, &second.invariant.predicate];
+                let with = |consequences: &[&UntypedArchetypeStatement]| {
+                    let statements: Vec<_> =
+                        predicates.iter().chain(consequences).copied().collect();
+                    simple_statements_satisfiable(&statements)
+                };
+                let [first_consequence, second_consequence] =
+                    [&first.invariant.consequence, &second.invariant.consequence];
+                if with(&[first_consequence])
+                    && with(&[second_consequence])
+                    && !with(&[first_consequence, second_consequence])
+                {
+                    conflicts.push((first.id, second.id));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Registers a named group of components that archetype statements can refer to,
+    /// using [`UntypedArchetypeStatement::GroupAllOf`] and [`UntypedArchetypeStatement::GroupAtLeastOneOf`].
+    ///
+    /// If a group with this name already exists, its members are replaced and its existing [`GroupId`] is returned.
+    /// Group membership is dynamic: every invariant referring to a group is re-checked against every archetype
+    /// the next time archetypes are checked after its members change.
+    pub fn register_component_group(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        component_ids: impl IntoIterator<Item = ComponentId>,
+    ) -> GroupId {
+        let name = name.into();
+        let members = component_ids.into_iter().collect();
+        let id = match self.component_group(&name) {
+            Some(id) => {
+                self.component_groups[id.0].1 = members;
+                id
+            }
+            None => {
+                self.component_groups.push((name, members));
+                GroupId(self.component_groups.len() - 1)
+            }
+        };
+        self.component_groups_changed();
+        id
+    }
+
+    /// Adds a component to the component group with the given [`GroupId`],
+    /// returning `false` if the component was already a member.
+    ///
+    /// # Panics
+    /// Panics if no group with the given [`GroupId`] was registered.
+    pub fn add_to_component_group(&mut self, group: GroupId, component_id: ComponentId) -> bool {
+        let added = self.component_groups[group.0].1.insert(component_id);
+        if added {
+            self.component_groups_changed();
+        }
+        added
+    }
+
+    /// Returns the [`GroupId`] of the component group with the given name, if it was registered.
+    pub fn component_group(&self, name: &str) -> Option<GroupId> {
+        self.component_groups
+            .iter()
+            .position(|(group_name, _)| group_name == name)
+            .map(GroupId)
+    }
+
+    /// Returns the name of the component group with the given [`GroupId`], if it was registered.
+    pub fn component_group_name(&self, group: GroupId) -> Option<&str> {
+        self.component_groups
+            .get(group.0)
+            .map(|(name, _)| name.as_ref())
+    }
+
+    /// Returns the current members of the component group with the given [`GroupId`], if it was registered.
+    pub fn component_group_members(&self, group: GroupId) -> Option<&ComponentIdSet> {
+        self.component_groups
+            .get(group.0)
+            .map(|(_, members)| members)
+    }
+
+    /// Resolves the invariants referring to component groups again,
+    /// and schedules them to be re-checked against every archetype.
+    fn component_groups_changed(&mut self) {
+        for stored in &mut self.raw_list {
+            stored.resolve_groups(&self.component_groups);
+        }
+        if self.raw_list.iter().any(|stored| stored.resolved.is_some()) {
+            self.groups_changed = true;
+        }
+    }
+
+    /// Returns an iterator over all archetype invariants, in the order they were added.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &UntypedArchetypeInvariant> {
+        self.raw_list.iter().map(|stored| &stored.invariant)
+    }
+
+    /// Returns an iterator over all enabled archetype invariants as they are checked,
+    /// with every component group replaced by its current members.
+    #[inline]
+    pub(crate) fn iter_checked(&self) -> impl Iterator<Item = &UntypedArchetypeInvariant> {
+        self.raw_list
+            .iter()
+            .filter(|stored| stored.enabled)
+            .map(StoredArchetypeInvariant::checked)
+    }
+
+    /// Returns the number of archetype invariants.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.raw_list.len()
+    }
+
+    /// Returns `true` if there are no archetype invariants.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.raw_list.is_empty()
+    }
+
+    /// Returns the set of [`ComponentId`]s of the components in the bundle `B`, initializing them if needed.
+    ///
+    /// The set is computed once per bundle type and cached for later lowerings of [`ArchetypeStatement`]s.
+    /// Computing the set registers every component in the bundle, and [`ComponentId`]s never change once registered,
+    /// so the cached set remains valid no matter which components are registered afterwards.
+    /// `components` must always belong to the same [`World`].
+    pub(crate) fn bundle_component_ids<B: Bundle>(
+        &mut self,
+        components: &mut Components,
+        storages: &mut Storages,
+    ) -> &ComponentIdSet {
+        self.bundle_component_ids
+            .entry(TypeId::of::<B>())
+            .or_insert_with(|| {
+                let mut component_ids = Vec::new();
+                B::component_ids(components, storages, &mut |id| component_ids.push(id));
+                component_ids.into_iter().collect()
+            })
+    }
+
+    /// Removes all archetype invariants.
+    ///
+    /// Archetype invariant identifiers are not reused after clearing.
+    /// Every existing archetype is checked against the invariants added afterwards,
+    /// while violations that were already found are still reported.
+    pub fn clear(&mut self) {
+        self.raw_list.clear();
+        self.last_checked_archetype_index = 0;
+        self.first_unchecked_invariant_index = 0;
+        self.total_complexity = 0;
+        self.reported_removals.clear();
+        self.reenabled_invariants.clear();
+        self.skipped_empty_archetypes.clear();
+        self.skipped_reenabled_archetypes.clear();
+        self.skipped_exempt_archetypes.clear();
+    }
+
+    /// Captures the current set of invariants, so that it can be restored later with [`ArchetypeInvariants::restore`].
+    ///
+    /// Settings such as the [`ViolationPolicy`] and the component groups are not part of the snapshot.
+    pub fn snapshot(&self) -> ArchetypeInvariantsSnapshot {
+        ArchetypeInvariantsSnapshot {
+            invariants: self.raw_list.clone(),
+        }
+    }
+
+    /// Replaces every invariant with the ones captured in `snapshot`, keeping their ids and labels.
+    ///
+    /// Unlike clearing the invariants and adding them again, the restored invariants can still be referred to
+    /// by the [`ArchetypeInvariantId`]s they had when the snapshot was taken.
+    /// Component groups are resolved to their current members.
+    /// The next time archetypes are checked, every archetype is checked against every restored invariant.
+    pub fn restore(&mut self, snapshot: ArchetypeInvariantsSnapshot) {
+        self.raw_list = snapshot.invariants;
+        for stored in &mut self.raw_list {
+            stored.resolve_groups(&self.component_groups);
+        }
+        self.total_complexity = self
+            .raw_list
+            .iter()
+            .map(|stored| stored.invariant.complexity())
+            .sum();
+        self.first_unchecked_invariant_index = 0;
+        self.reenabled_invariants.clear();
+        self.skipped_reenabled_archetypes.clear();
+        self.skipped_exempt_archetypes.clear();
+    }
+
+    /// Returns the [`ViolationPolicy`] used when a violation is found by the automatic checks.
+    #[inline]
+    pub fn policy(&self) -> ViolationPolicy {
+        self.policy
+    }
+
+    /// Sets the [`ViolationPolicy`] used when a violation is found by the automatic checks.
+    ///
+    /// This does not affect [`ArchetypeInvariants::check_archetype`] or [`ArchetypeInvariants::check_archetypes`],
+    /// which always return the violations they find.
+    #[inline]
+    pub fn set_policy(&mut self, policy: ViolationPolicy) {
+        self.policy = policy;
+    }
+
+    /// Returns `true` if strict mode is enabled.
+    #[inline]
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Enables or disables strict mode, which is disabled by default.
+    ///
+    /// In strict mode, a warning is logged whenever an [`ArchetypeStatement`] that is valid,
+    /// but commonly misunderstood, is converted into an [`UntypedArchetypeStatement`] for this world.
+    /// Currently, this is the case for [`ArchetypeStatement::NoneOf`] with a multi-component bundle,
+    /// which forbids each of the components rather than their combination.
+    #[inline]
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Returns the components used by [`UntypedArchetypeStatement::has_parent`] and [`UntypedArchetypeStatement::has_children`],
+    /// as `(parent, children)`, if they were registered.
+    #[inline]
+    pub fn hierarchy_components(&self) -> Option<(ComponentId, ComponentId)> {
+        self.hierarchy_components
+    }
+
+    /// Sets the components that mark an entity as the child of another entity, `parent`,
+    /// and as the parent of other entities, `children`.
+    ///
+    /// These are used to construct [`UntypedArchetypeStatement::HasParent`] and [`UntypedArchetypeStatement::HasChildren`].
+    /// Statements that were already constructed keep the components they were constructed with.
+    #[inline]
+    pub fn set_hierarchy_components(&mut self, parent: ComponentId, children: ComponentId) {
+        self.hierarchy_components = Some((parent, children));
+    }
+
+    /// Returns `true` if archetypes without entities are checked, which is the default.
+    #[inline]
+    pub fn checks_empty_archetypes(&self) -> bool {
+        self.check_empty_archetypes
+    }
+
+    /// Sets whether archetypes without entities are checked.
+    ///
+    /// New invariants are checked against all existing archetypes, including ones whose entities have
+    /// all been removed or despawned. If a forbidden archetype shape that holds no entities is acceptable,
+    /// set this to `false` to skip those archetypes instead.
+    /// A skipped archetype is checked against all invariants once it contains entities again,
+    /// the next time archetypes are checked: after an entity is spawned, or a component is inserted or removed.
+    #[inline]
+    pub fn set_check_empty_archetypes(&mut self, check_empty_archetypes: bool) {
+        self.check_empty_archetypes = check_empty_archetypes;
+    }
+
+    /// Returns the number of archetypes from which the automatic checks evaluate archetypes in parallel.
+    ///
+    /// This is [`ArchetypeInvariants::DEFAULT_PARALLEL_CHECK_THRESHOLD`] by default.
+    #[inline]
+    pub fn parallel_check_threshold(&self) -> usize {
+        self.parallel_check_threshold
+    }
+
+    /// Sets the number of archetypes from which the automatic checks evaluate archetypes in parallel.
+    ///
+    /// When a batch of at least this many archetypes is checked, such as every existing archetype when an invariant is added,
+    /// the archetypes are split across the threads of the [`ComputeTaskPool`], which is initialized if needed,
+    /// unless it only has a single thread.
+    /// Smaller batches are checked on the current thread, since the overhead of spawning tasks outweighs the gains.
+    /// Violations are handled in the same order either way. Set this to `usize::MAX` to always check sequentially.
+    #[inline]
+    pub fn set_parallel_check_threshold(&mut self, parallel_check_threshold: usize) {
+        self.parallel_check_threshold = parallel_check_threshold;
+    }
+
+    /// Returns `true` if removals of components are checked against the paired invariants,
+    /// which is disabled by default.
+    #[inline]
+    pub fn checks_removals(&self) -> bool {
+        self.check_removals
+    }
+
+    /// Sets whether removals of components are checked against the paired invariants.
+    ///
+    /// Paired invariants have a predicate and a consequence of [`UntypedArchetypeStatement::AllOf`],
+    /// such as the ones created by [`ArchetypeInvariant::requires`].
+    /// When enabled, every removal of components from an entity that breaks such an invariant is reported
+    /// with a message naming the removed components and the components they left orphaned, such as
+    /// `Removing [B] left [A] orphaned`. This also catches removals into archetypes that were created earlier,
+    /// which the automatic checks only report once, when the archetype is first checked.
+    ///
+    /// Invariants with an [`ArchetypeInvariantHandler`] and deferred invariants are not checked on removal.
+    #[inline]
+    pub fn set_check_removals(&mut self, check_removals: bool) {
+        self.check_removals = check_removals;
+    }
+
+    /// Returns `true` if archetypes are checked as soon as they are created, which is enabled by default.
+    #[inline]
+    pub fn checks_automatically(&self) -> bool {
+        self.automatic_checks
+    }
+
+    /// Sets whether archetypes are checked as soon as they are created.
+    ///
+    /// When disabled, the world no longer checks new archetypes, new invariants or removals in the middle
+    /// of the operation that caused them. Nothing is lost: the next call to [`World::check_pending_archetype_invariants`],
+    /// usually made by [`check_archetype_invariants_system`], checks everything that changed since the last check.
+    /// This moves the point at which violations are detected, and at which [`ViolationPolicy::Panic`] panics,
+    /// to a predictable place in the schedule.
+    ///
+    /// Removal checks enabled with [`ArchetypeInvariants::set_check_removals`] are skipped entirely while this is disabled,
+    /// since the archetypes an entity moved between are not known anymore when the check runs.
+    #[inline]
+    pub fn set_automatic_checks(&mut self, automatic_checks: bool) {
+        self.automatic_checks = automatic_checks;
+    }
+
+    /// Returns counters describing how much work the automatic checks have done so far.
+    ///
+    /// This is useful for tuning: for example, adding invariants one at a time when there are many archetypes
+    /// causes many more archetype checks than adding them in a single batch.
+    #[inline]
+    pub fn stats(&self) -> ArchetypeInvariantStats {
+        self.stats
+    }
+
+    /// Removes and returns the violations found under [`ViolationPolicy::Event`] that have not been sent yet.
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    pub(crate) fn drain_unreported_violations(
+        &mut self,
+    ) -> impl Iterator<Item = ArchetypeInvariantViolation> + '_ {
+        self.unreported_violations.drain(..)
+    }
+
+    /// Records that a resource was inserted or removed, so that the invariants depending on resources
+    /// are re-checked against every archetype the next time archetypes are checked.
+    #[inline]
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    pub(crate) fn resources_changed(&mut self) {
+        if self.raw_list.iter().any(|stored| stored.uses_resources) {
+            self.resources_changed = true;
+        }
+    }
+
+    /// Removes and returns the violations of invariants with a handler whose handlers have not been run yet.
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    pub(crate) fn take_pending_handler_calls(&mut self) -> Vec<PendingHandlerCall> {
+        std::mem::take(&mut self.pending_handler_calls)
+    }
+
+    /// Tests the provided [`Archetype`] against every enabled archetype invariant.
+    ///
+    /// `components` is used to look up the names of the involved components when a violation is found.
+    /// Returns the first violation found, if any.
+    ///
+    /// Only the archetype is known here, so every [`UntypedArchetypeStatement::ResourcePresent`] is false.
+    // Violations are rare and are reported rather than propagated, so their size is not a concern.
+    #[allow(clippy::result_large_err)]
+    pub fn check_archetype(
+        &self,
+        archetype: &Archetype,
+        components: &Components,
+    ) -> Result<(), ArchetypeInvariantViolation> {
+        let component_ids: ComponentIdSet = archetype.components().collect();
+        match self
+            .raw_list
+            .iter()
+            .filter(|stored| stored.enabled && !stored.checked().evaluate(&component_ids))
+            .find_map(|stored| stored.violation(archetype, components))
+        {
+            Some(violation) => Err(violation),
+            None => Ok(()),
+        }
+    }
+
+    /// Tests an entity with the provided set of [`ComponentId`]s against every enabled archetype invariant,
+    /// in a world where exactly the `resources` exist.
+    ///
+    /// This is [`ArchetypeInvariants::check_archetype`] for callers that only have a set of components,
+    /// such as a [`HashSet<ComponentId>`] converted with [`Into::into`], rather than an existing [`Archetype`].
+    /// Since no archetype is involved, the violation's [`ArchetypeInvariantViolation::archetype_id`] is
+    /// [`ArchetypeId::INVALID`], it samples no entities, and no entity can be exempt from an invariant.
+    /// Returns the first violation found, if any.
+    #[allow(clippy::result_large_err)]
+    pub fn check_component_ids(
+        &self,
+        component_ids: &ComponentIdSet,
+        resources: &ComponentIdSet,
+        components: &Components,
+    ) -> Result<(), ArchetypeInvariantViolation> {
+        match self.raw_list.iter().find(|stored| {
+            stored.enabled
+                && !stored
+                    .checked()
+                    .evaluate_with_resources(component_ids, resources)
+        }) {
+            Some(stored) => {
+                let mut violation = ArchetypeInvariantViolation::new(
+                    ArchetypeId::INVALID,
+                    component_ids.iter().collect(),
+                    Vec::new(),
+                    stored.checked().clone(),
+                    components,
+                );
+                violation.location = Some(stored.location);
+                Err(violation)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Tests every archetype in `archetypes` against every enabled archetype invariant, returning all violations found.
+    ///
+    /// Unlike [`ArchetypeInvariants::check_archetype`], this does not stop at the first violation:
+    /// an archetype that violates several invariants is reported once for each of them.
+    /// Archetypes without entities are skipped if [`ArchetypeInvariants::checks_empty_archetypes`] is `false`.
+    pub fn check_archetypes(
+        &self,
+        archetypes: &Archetypes,
+        components: &Components,
+    ) -> Vec<ArchetypeInvariantViolation> {
+        let resources = present_resources(archetypes);
+        let mut violations = Vec::new();
+        for archetype in archetypes.iter() {
+            // The resource archetype never contains any entities.
+            if archetype.id() == ArchetypeId::RESOURCE
+                || (!self.check_empty_archetypes && archetype.is_empty())
+            {
+                continue;
+            }
+
+            let component_ids: ComponentIdSet = archetype.components().collect();
+            for stored in self.raw_list.iter().filter(|stored| stored.enabled) {
+                if stored
+                    .checked()
+                    .evaluate_with_resources(&component_ids, &resources)
+                {
+                    continue;
+                }
+                violations.extend(stored.violation(archetype, components));
+            }
+        }
+        violations
+    }
+
+    /// Returns an iterator over every archetype in `archetypes` that violates an enabled archetype invariant,
+    /// together with the invariant it violates.
+    ///
+    /// This finds the same violations as [`ArchetypeInvariants::check_archetypes`], in the same order,
+    /// but checks the archetypes lazily instead of collecting every violation up front,
+    /// so that violations can be streamed, or only the first few of them found.
+    ///
+    /// ```
+    /// # use bevy_ecs::{archetype_invariants::{ArchetypeInvariant, ViolationPolicy}, prelude::*};
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Health;
+    ///
+    /// let mut world = World::new();
+    /// world.set_archetype_invariant_policy(ViolationPolicy::Warn);
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(Player,), (Health,)>::requires());
+    /// let entity = world.spawn(Player).id();
+    ///
+    /// let (archetype_id, _) = world.iter_violating_archetypes().next().unwrap();
+    /// assert_eq!(archetype_id, world.entity(entity).location().archetype_id);
+    /// ```
+    pub fn iter_violating_archetypes<'a>(
+        &'a self,
+        archetypes: &'a Archetypes,
+    ) -> ViolatingArchetypes<'a> {
+        let resources = if self.raw_list.iter().any(|stored| stored.uses_resources) {
+            present_resources(archetypes)
+        } else {
+            ComponentIdSet::new()
+        };
+        ViolatingArchetypes {
+            invariants: self,
+            archetypes,
+            resources,
+            archetype_index: 0,
+            invariant_index: 0,
+        }
+    }
+
+    /// Returns `true` if there are archetypes or invariants which have not been checked against each other.
+    #[inline]
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    pub(crate) fn has_unchecked_archetypes(&self, archetypes: &Archetypes) -> bool {
+        self.resources_changed
+            || self.groups_changed
+            || !self.reenabled_invariants.is_empty()
+            || self.last_checked_archetype_index != archetypes.len()
+            || self.first_unchecked_invariant_index < self.raw_list.len()
+            || self
+                .skipped_empty_archetypes
+                .iter()
+                .any(|&id| self.check_empty_archetypes || !archetypes[id].is_empty())
+            || self
+                .skipped_reenabled_archetypes
+                .iter()
+                .any(|&(id, _)| !archetypes[id].is_empty())
+            || self
+                .skipped_exempt_archetypes
+                .iter()
+                .any(|&pair| self.joined_by_unexempt_entity(pair, archetypes))
+    }
+
+    /// Returns `true` if the archetype of a pair in `skipped_exempt_archetypes` should be checked against its invariant again,
+    /// because it now contains an entity which is not exempt from it, or because the invariant was removed.
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    fn joined_by_unexempt_entity(
+        &self,
+        (archetype_id, invariant_id): (ArchetypeId, ArchetypeInvariantId),
+        archetypes: &Archetypes,
+    ) -> bool {
+        match self
+            .raw_list
+            .iter()
+            .find(|stored| stored.id == invariant_id)
+        {
+            Some(stored) => !stored.exempts_every_entity(&archetypes[archetype_id]),
+            None => true,
+        }
+    }
+
+    /// Checks that every archetype created since the last call upholds all archetype invariants,
+    /// and that every other archetype upholds all archetype invariants added since the last call,
+    /// handling any violations according to the [`ViolationPolicy`].
+    ///
+    /// Each archetype is only ever checked once against each invariant,
+    /// except that archetypes without entities which were skipped because of
+    /// [`ArchetypeInvariants::set_check_empty_archetypes`] are checked against all invariants once they have entities.
+    /// Returns the number of invariant evaluations that were performed.
+    ///
+    /// Under [`ViolationPolicy::Event`], violations are stored until they are
+    /// taken with [`ArchetypeInvariants::drain_unreported_violations`].
+    /// Violations of invariants with a handler are stored until they are
+    /// taken with [`ArchetypeInvariants::take_pending_handler_calls`].
+    ///
+    /// # Panics
+    /// Panics if any of these archetypes violates an archetype invariant with [`Severity::Error`]
+    /// and the policy is [`ViolationPolicy::Panic`] or [`ViolationPolicy::PanicWithAllViolations`].
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    pub(crate) fn check_new_archetypes(
+        &mut self,
+        archetypes: &Archetypes,
+        components: &Components,
+    ) -> usize {
+        #[cfg(feature = "trace")]
+        let _span = bevy_utils::tracing::info_span!(
+            "archetype_invariant_checks",
+            archetypes = archetypes.len(),
+            invariants = self.raw_list.len(),
+        )
+        .entered();
+        #[cfg(feature = "archetype_invariant_timing")]
+        let start = bevy_utils::Instant::now();
+        let previous_evaluations = self.stats.invariant_evaluations;
+
+        // Archetypes are never removed, so this only happens if a different `Archetypes` is passed in.
+        // Nothing is known about those archetypes, so every one of them must be checked.
+        if self.last_checked_archetype_index > archetypes.len() {
+            self.last_checked_archetype_index = 0;
+            self.first_unchecked_invariant_index = self.raw_list.len();
+            self.skipped_empty_archetypes.clear();
+            self.skipped_reenabled_archetypes.clear();
+            self.skipped_exempt_archetypes.clear();
+        }
+
+        let check_empty_archetypes = self.check_empty_archetypes;
+        let (skipped_archetypes, still_skipped): (Vec<_>, Vec<_>) = self
+            .skipped_empty_archetypes
+            .drain(..)
+            .partition(|&id| check_empty_archetypes || !archetypes[id].is_empty());
+        self.skipped_empty_archetypes = still_skipped;
+        let (populated_reenabled_archetypes, still_skipped): (Vec<_>, Vec<_>) = self
+            .skipped_reenabled_archetypes
+            .drain(..)
+            .partition(|&(id, _)| !archetypes[id].is_empty());
+        self.skipped_reenabled_archetypes = still_skipped;
+        let (populated_exempt_archetypes, still_skipped): (Vec<_>, Vec<_>) =
+            mem::take(&mut self.skipped_exempt_archetypes)
+                .into_iter()
+                .partition(|&pair| self.joined_by_unexempt_entity(pair, archetypes));
+        self.skipped_exempt_archetypes = still_skipped;
+
+        let checked_archetypes = &archetypes.archetypes[..self.last_checked_archetype_index];
+        let unchecked_archetypes = &archetypes.archetypes[self.last_checked_archetype_index..];
+        let new_invariants = &self.raw_list[self.first_unchecked_invariant_index..];
+
+        let skipped_empty_archetypes = &mut self.skipped_empty_archetypes;
+        let mut should_check = |archetype: &&Archetype| {
+            if check_empty_archetypes
+                || archetype.id() == ArchetypeId::RESOURCE
+                || !archetype.is_empty()
+            {
+                return true;
+            }
+            if !skipped_empty_archetypes.contains(&archetype.id()) {
+                skipped_empty_archetypes.push(archetype.id());
+            }
+            false
+        };
+
+        let policy = self.policy;
+        let unreported_violations = &mut self.unreported_violations;
+        let pending_handler_calls = &mut self.pending_handler_calls;
+        let reported_removals = &self.reported_removals;
+        let skipped_exempt_archetypes = &mut self.skipped_exempt_archetypes;
+        let mut report = |stored: &StoredArchetypeInvariant, archetype: &Archetype| {
+            if reported_removals.contains(&(archetype.id(), stored.id)) {
+                return;
+            }
+            let reported = report_violation(
+                stored,
+                archetype,
+                components,
+                policy,
+                unreported_violations,
+                pending_handler_calls,
+            );
+            let pair = (archetype.id(), stored.id);
+            if !reported && !skipped_exempt_archetypes.contains(&pair) {
+                skipped_exempt_archetypes.push(pair);
+            }
+        };
+        let resources = if self.raw_list.iter().any(|stored| stored.uses_resources) {
+            present_resources(archetypes)
+        } else {
+            ComponentIdSet::new()
+        };
+        // The invariants that depend on resources or component groups may have changed their verdict for any archetype,
+        // and re-enabled invariants have not been checked against the archetypes added while they were disabled,
+        // apart from the ones that are checked against all invariants below anyway.
+        let (resources_changed, groups_changed) = (self.resources_changed, self.groups_changed);
+        let reenabled_invariants = &self.reenabled_invariants;
+        let (reenabled, changed_invariants): (Vec<&StoredArchetypeInvariant>, Vec<_>) = self
+            .raw_list[..self.first_unchecked_invariant_index]
+            .iter()
+            .filter(|stored| {
+                (resources_changed && stored.uses_resources)
+                    || (groups_changed && stored.resolved.is_some())
+                    || reenabled_invariants.contains(&stored.id)
+            })
+            .partition(|stored| reenabled_invariants.contains(&stored.id));
+        self.resources_changed = false;
+        self.groups_changed = false;
+
+        let stats = &mut self.stats;
+        let parallel_threshold = self.parallel_check_threshold;
+        check_invariants(
+            skipped_archetypes.iter().map(|&id| &archetypes[id]),
+            &self.raw_list,
+            &resources,
+            false,
+            parallel_threshold,
+            stats,
+            &mut report,
+        );
+        check_invariants(
+            checked_archetypes
+                .iter()
+                .filter(|archetype| !skipped_archetypes.contains(&archetype.id()))
+                .filter(&mut should_check),
+            new_invariants,
+            &resources,
+            false,
+            parallel_threshold,
+            stats,
+            &mut report,
+        );
+        check_invariants(
+            checked_archetypes
+                .iter()
+                .filter(|archetype| !skipped_archetypes.contains(&archetype.id()))
+                .filter(&mut should_check),
+            &changed_invariants,
+            &resources,
+            false,
+            parallel_threshold,
+            stats,
+            &mut report,
+        );
+        let skipped_reenabled_archetypes = &mut self.skipped_reenabled_archetypes;
+        check_invariants(
+            checked_archetypes
+                .iter()
+                .filter(|archetype| !skipped_archetypes.contains(&archetype.id()))
+                .filter(&mut should_check)
+                .filter(|archetype| {
+                    if archetype.id() == ArchetypeId::RESOURCE || !archetype.is_empty() {
+                        return true;
+                    }
+                    for stored in &reenabled {
+                        let pair = (archetype.id(), stored.id);
+                        if !skipped_reenabled_archetypes.contains(&pair) {
+                            skipped_reenabled_archetypes.push(pair);
+                        }
+                    }
+                    false
+                }),
+            &reenabled,
+            &resources,
+            false,
+            parallel_threshold,
+            stats,
+            &mut report,
+        );
+        for (archetype_id, invariant_id) in populated_reenabled_archetypes
+            .into_iter()
+            .chain(populated_exempt_archetypes)
+        {
+            if skipped_archetypes.contains(&archetype_id) {
+                continue;
+            }
+            if let Some(stored) = self
+                .raw_list
+                .iter()
+                .find(|stored| stored.id == invariant_id && stored.enabled)
+            {
+                check_invariants(
+                    [&archetypes[archetype_id]],
+                    &[stored],
+                    &resources,
+                    false,
+                    parallel_threshold,
+                    stats,
+                    &mut report,
+                );
+            }
+        }
+        check_invariants(
+            unchecked_archetypes.iter().filter(&mut should_check),
+            &self.raw_list,
+            &resources,
+            false,
+            parallel_threshold,
+            stats,
+            &mut report,
+        );
+
+        self.last_checked_archetype_index = archetypes.len();
+        self.first_unchecked_invariant_index = self.raw_list.len();
+        self.reported_removals.clear();
+        self.reenabled_invariants.clear();
+
+        // The warning is only logged when the threshold is crossed, not on every check above it
+        let cost = self.total_complexity.saturating_mul(archetypes.len());
+        let threshold = Self::COMPLEXITY_WARNING_THRESHOLD;
+        if self.checked_cost <= threshold && cost > threshold {
+            warn!(
+                "The archetype invariants now have a total complexity of {}, which is costly to check against {} archetypes. Consider combining or removing some of them.",
+                self.total_complexity,
+                archetypes.len()
+            );
+        }
+        self.checked_cost = cost;
+        #[cfg(feature = "archetype_invariant_timing")]
+        {
+            self.stats.time_spent += start.elapsed();
+        }
+        self.panic_with_collected_violations();
+        (self.stats.invariant_evaluations - previous_evaluations) as usize
+    }
+
+    /// Checks the removal of components that moved `entity` from the `old` archetype to the `new` one
+    /// against the paired invariants, handling any violations according to the [`ViolationPolicy`].
+    ///
+    /// See [`ArchetypeInvariants::set_check_removals`].
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    pub(crate) fn check_removal(
+        &mut self,
+        entity: Entity,
+        old: &Archetype,
+        new: &Archetype,
+        components: &Components,
+    ) {
+        let old_component_ids: ComponentIdSet = old.components().collect();
+        let new_component_ids: ComponentIdSet = new.components().collect();
+        for stored in &self.raw_list {
+            if stored.deferred
+                || !stored.enabled
+                || stored.handler.is_some()
+                || stored.exempt_entities.contains(&entity)
+            {
+                continue;
+            }
+            let (orphaned, required) =
+                match (&stored.checked().predicate, &stored.checked().consequence) {
+                    (
+                        UntypedArchetypeStatement::AllOf(orphaned),
+                        UntypedArchetypeStatement::AllOf(required),
+                    ) => (orphaned, required),
+                    _ => continue,
+                };
+            // Only report violations that were caused by this removal
+            if orphaned.is_empty()
+                || !orphaned.is_subset(&new_component_ids)
+                || required.is_subset(&new_component_ids)
+                || !required.is_subset(&old_component_ids)
+            {
+                continue;
+            }
+
+            let mut violation = ArchetypeInvariantViolation::new(
+                new.id(),
+                new.components().collect(),
+                vec![entity],
+                stored.checked().clone(),
+                components,
+            );
+            violation.removed_component_ids = required
+                .iter()
+                .filter(|&id| !new_component_ids.contains(id))
+                .collect();
+            violation.location = Some(stored.location);
+            if new.id().index() >= self.last_checked_archetype_index {
+                self.reported_removals.push((new.id(), stored.id));
+            }
+            dispatch_violation(violation, self.policy, &mut self.unreported_violations);
+        }
+        self.panic_with_collected_violations();
+    }
+
+    /// Under [`ViolationPolicy::PanicWithAllViolations`], panics with a message listing every violation
+    /// found since the last call.
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    fn panic_with_collected_violations(&mut self) {
+        if self.policy != ViolationPolicy::PanicWithAllViolations
+            || self.unreported_violations.is_empty()
+        {
+            return;
+        }
+        let violations = mem::take(&mut self.unreported_violations);
+        let mut message = format!(
+            "{} archetype invariant violation(s) were found:",
+            violations.len()
+        );
+        for violation in violations {
+            message.push_str(&format!("\n- {violation}"));
+        }
+        panic!("{message}");
+    }
+
+    /// Checks every archetype that currently contains entities against the deferred invariants,
+    /// handling any violations according to the [`ViolationPolicy`], like [`ArchetypeInvariants::check_new_archetypes`].
+    ///
+    /// Unlike the automatic checks, this always checks every archetype, as deferred invariants are
+    /// not tracked as already checked against an archetype.
+    ///
+    /// # Panics
+    /// Panics if any of these archetypes violates a deferred archetype invariant with [`Severity::Error`]
+    /// and the policy is [`ViolationPolicy::Panic`] or [`ViolationPolicy::PanicWithAllViolations`].
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+    pub(crate) fn check_deferred(&mut self, archetypes: &Archetypes, components: &Components) {
+        if !self.raw_list.iter().any(|stored| stored.deferred) {
+            return;
+        }
+        #[cfg(feature = "trace")]
+        let _span = bevy_utils::tracing::info_span!(
+            "deferred_archetype_invariant_checks",
+            archetypes = archetypes.len(),
+        )
+        .entered();
+        #[cfg(feature = "archetype_invariant_timing")]
+        let start = bevy_utils::Instant::now();
+
+        let policy = self.policy;
+        let unreported_violations = &mut self.unreported_violations;
+        let pending_handler_calls = &mut self.pending_handler_calls;
+        let mut report = |stored: &StoredArchetypeInvariant, archetype: &Archetype| {
+            report_violation(
+                stored,
+                archetype,
+                components,
+                policy,
+                unreported_violations,
+                pending_handler_calls,
+            );
+        };
+        let resources = present_resources(archetypes);
+        check_invariants(
+            archetypes.iter().filter(|archetype| !archetype.is_empty()),
+            &self.raw_list,
+            &resources,
+            true,
+            self.parallel_check_threshold,
+            &mut self.stats,
+            &mut report,
+        );
+        #[cfg(feature = "archetype_invariant_timing")]
+        {
+            self.stats.time_spent += start.elapsed();
+        }
+        self.panic_with_collected_violations();
+    }
+}
+
+/// Collects invariants into a new [`ArchetypeInvariants`], as if each one was passed to [`ArchetypeInvariants::add`].
+///
+/// No archetypes have been checked against the collected invariants,
+/// so the first check validates every archetype against all of them.
+/// Without access to [`Components`], warnings about contradictory invariants identify components by [`ComponentId`].
+impl FromIterator<UntypedArchetypeInvariant> for ArchetypeInvariants {
+    fn from_iter<I: IntoIterator<Item = UntypedArchetypeInvariant>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut archetype_invariants = ArchetypeInvariants::with_capacity(iter.size_hint().0);
+        archetype_invariants.extend(iter);
+        archetype_invariants.last_checked_archetype_index = 0;
+        archetype_invariants.first_unchecked_invariant_index = 0;
+        archetype_invariants
+    }
+}
+
+/// Adds each invariant as if it was passed to [`ArchetypeInvariants::add`].
+///
+/// Without access to [`Components`], warnings about contradictory invariants identify components by [`ComponentId`].
+impl Extend<UntypedArchetypeInvariant> for ArchetypeInvariants {
+    fn extend<I: IntoIterator<Item = UntypedArchetypeInvariant>>(&mut self, iter: I) {
+        let components = Components::default();
+        for archetype_invariant in iter {
+            self.add(archetype_invariant, &components);
+        }
+    }
+}
+
+/// An exclusive system that checks the world's archetype invariants, including the deferred ones.
+///
+/// Add this to a schedule after disabling the automatic checks with [`World::set_automatic_archetype_invariant_checks`],
+/// so that violations are detected, and [`ViolationPolicy::Panic`] panics, at the same point every frame
+/// instead of in the middle of whichever operation created the offending archetype.
+///
+/// Entities spawned and components inserted through [`Commands`](crate::system::Commands) only change archetypes
+/// once the command buffers of their systems are applied, which happens at the end of each stage.
+/// This system only sees those changes if it runs after that point, so it is best added at the start of a stage,
+/// with [`.at_start()`](crate::schedule::IntoSystemDescriptor::at_start), or in a stage of its own after the stages it should cover.
+/// Changes made directly through [`World`] by exclusive systems earlier in the same stage are always seen.
+///
+/// ```
+/// # use bevy_ecs::{archetype_invariants::*, prelude::*};
+/// #[derive(Component)]
+/// struct Player;
+/// #[derive(Component)]
+/// struct Camera;
+///
+/// let mut world = World::new();
+/// world.set_automatic_archetype_invariant_checks(false);
+/// world.set_archetype_invariant_policy(ViolationPolicy::Event);
+/// world.add_archetype_invariant(ArchetypeInvariant::<(Player,), (Camera,)>::requires());
+/// // The violation is not detected yet
+/// world.spawn(Player);
+/// assert!(!world.contains_resource::<Events<ArchetypeInvariantViolated>>());
+///
+/// let mut stage = SystemStage::single_threaded();
+/// stage.add_system(check_archetype_invariants_system.at_start());
+/// stage.run(&mut world);
+/// assert_eq!(
+///     world.resource::<Events<ArchetypeInvariantViolated>>().len(),
+///     1
+/// );
+/// ```
+pub fn check_archetype_invariants_system(world: &mut World) {
+    world.check_pending_archetype_invariants();
+    world.check_deferred_archetype_invariants();
+}
+
+/// Handles a violation found by the automatic checks: the violated invariant's handler is queued if it has one,
+/// otherwise the violation is handled according to its [`Severity`] and the `policy`.
+///
+/// Returns `false` if the violation was not reported because every entity of the archetype is exempt from the invariant.
+#[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+fn report_violation(
+    stored: &StoredArchetypeInvariant,
+    archetype: &Archetype,
+    components: &Components,
+    policy: ViolationPolicy,
+    unreported_violations: &mut Vec<ArchetypeInvariantViolation>,
+    pending_handler_calls: &mut Vec<PendingHandlerCall>,
+) -> bool {
+    if let Some(handler) = &stored.handler {
+        if stored.exempts_every_entity(archetype) {
+            return false;
+        }
+        pending_handler_calls.push(PendingHandlerCall {
+            handler: handler.clone(),
+            invariant: stored.checked().clone(),
+            entities: stored.unexempt_entities(archetype).collect(),
+        });
+        return true;
+    }
+
+    match stored.violation(archetype, components) {
+        Some(violation) => {
+            dispatch_violation(violation, policy, unreported_violations);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Handles a violation of an invariant without a handler according to its [`Severity`] and the `policy`.
+fn dispatch_violation(
+    violation: ArchetypeInvariantViolation,
+    policy: ViolationPolicy,
+    unreported_violations: &mut Vec<ArchetypeInvariantViolation>,
+) {
+    match (violation.invariant.severity, policy) {
+        (Severity::Warning, _) | (Severity::Error, ViolationPolicy::Warn) => {
+            warn!("{violation}");
+        }
+        (Severity::Error, ViolationPolicy::Panic) => panic!("{violation}"),
+        (Severity::Error, ViolationPolicy::Event | ViolationPolicy::PanicWithAllViolations) => {
+            unreported_violations.push(violation);
+        }
+    }
+}
+
+/// Tests each of the provided archetypes against each of the provided invariants, in a world with the given `resources`,
+/// passing every violated invariant and its archetype to `report` and counting the work done in `stats`.
+/// Only the enabled invariants which are `deferred` or not, as requested, are evaluated.
+///
+/// If there are at least `parallel_threshold` archetypes, they are checked in parallel on the [`ComputeTaskPool`],
+/// unless it only has a single thread.
+/// Violations are reported in the same order either way: archetype by archetype, in the order the invariants are provided.
+#[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+fn check_invariants<'a, S: Borrow<StoredArchetypeInvariant> + Sync>(
+    archetypes: impl IntoIterator<Item = &'a Archetype>,
+    invariants: &[S],
+    resources: &ComponentIdSet,
+    deferred: bool,
+    parallel_threshold: usize,
+    stats: &mut ArchetypeInvariantStats,
+    report: &mut impl FnMut(&StoredArchetypeInvariant, &Archetype),
+) {
+    if invariants.is_empty() {
+        return;
+    }
+
+    // The resource archetype never contains any entities.
+    let archetypes: Vec<&Archetype> = archetypes
+        .into_iter()
+        .filter(|archetype| archetype.id() != ArchetypeId::RESOURCE)
+        .collect();
+    // The thread waiting for the tasks keeps polling them, so a single worker thread would only compete with it
+    let task_pool = (archetypes.len() >= parallel_threshold)
+        .then(|| ComputeTaskPool::init(TaskPool::default))
+        .filter(|task_pool| task_pool.thread_num() > 1);
+    let task_pool = match task_pool {
+        Some(task_pool) => task_pool,
+        None => {
+            // Archetypes can look up their components directly, which avoids collecting them for each check
+            for archetype in archetypes {
+                check_archetype_against(
+                    archetype.id(),
+                    archetype,
+                    invariants,
+                    resources,
+                    deferred,
+                    stats,
+                    |index| report(invariants[index].borrow(), archetype),
+                );
+            }
+            return;
+        }
+    };
+
+    // The invariants are only read while evaluating, so each task checks a chunk of the archetypes' components
+    // and collects the indices of the violating archetypes and invariants, which are reported afterwards.
+    // Archetypes themselves cannot be shared across threads, so only their component lists are.
+    let shapes: Vec<(ArchetypeId, &[ComponentId], &[ComponentId])> = archetypes
+        .iter()
+        .map(|archetype| {
+            (
+                archetype.id(),
+                archetype.table_components(),
+                archetype.sparse_set_components(),
+            )
+        })
+        .collect();
+    let chunk_size = archetypes.len() / task_pool.thread_num() + 1;
+    let chunks = shapes.par_chunk_map(task_pool, chunk_size, |chunk| {
+        let mut chunk_stats = ArchetypeInvariantStats::default();
+        let mut violations = Vec::new();
+        for (archetype_index, (archetype_id, table, sparse_set)) in chunk.iter().enumerate() {
+            let component_ids: ComponentIdSet = table.iter().chain(*sparse_set).copied().collect();
+            check_archetype_against(
+                *archetype_id,
+                &component_ids,
+                invariants,
+                resources,
+                deferred,
+                &mut chunk_stats,
+                |invariant_index| violations.push((archetype_index, invariant_index)),
+            );
+        }
+        (chunk_stats, violations)
+    });
+    for (chunk_index, (chunk_stats, violations)) in chunks.into_iter().enumerate() {
+        stats.archetype_checks += chunk_stats.archetype_checks;
+        stats.invariant_evaluations += chunk_stats.invariant_evaluations;
+        stats.statement_evaluations += chunk_stats.statement_evaluations;
+        for (archetype_index, invariant_index) in violations {
+            report(
+                invariants[invariant_index].borrow(),
+                archetypes[chunk_index * chunk_size + archetype_index],
+            );
+        }
+    }
+}
+
+/// Tests the archetype with the given components against each of the provided invariants, like [`check_invariants`],
+/// passing the index of every violated invariant to `report`.
+#[cfg_attr(not(feature = "archetype_invariants"), allow(dead_code))]
+fn check_archetype_against<S: Borrow<StoredArchetypeInvariant>>(
+    archetype_id: ArchetypeId,
+    component_ids: &(impl ArchetypeComponents + ?Sized),
+    invariants: &[S],
+    resources: &ComponentIdSet,
+    deferred: bool,
+    stats: &mut ArchetypeInvariantStats,
+    mut report: impl FnMut(usize),
+) {
+    #[cfg(feature = "trace")]
+    let _span = bevy_utils::tracing::trace_span!(
+        "archetype_invariant_check",
+        archetype = archetype_id.index(),
+        invariants = invariants.len(),
+    )
+    .entered();
+    stats.archetype_checks += 1;
+    for (index, stored) in invariants.iter().enumerate() {
+        let stored = stored.borrow();
+        if stored.deferred != deferred || !stored.enabled {
+            continue;
+        }
+        stats.invariant_evaluations += 1;
+        stats.statement_evaluations += 1;
+        if !stored
+            .checked()
+            .predicate
+            .evaluate_components(component_ids, resources)
+        {
+            continue;
+        }
+        stats.statement_evaluations += 1;
+        if !stored
+            .checked()
+            .consequence
+            .evaluate_components(component_ids, resources)
+        {
+            debug!(
+                archetype = archetype_id.index(),
+                invariant = stored.id.index(),
+                "An archetype violates an archetype invariant."
+            );
+            report(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "archetype_invariant_logging")]
+    use std::sync::atomic;
+
+    #[cfg(feature = "archetype_invariant_logging")]
+    use bevy_utils::tracing::{
+        span, subscriber::with_default, Event, Id, Level, Metadata, Subscriber,
+    };
+    use bevy_utils::HashSet;
+
+    use crate as bevy_ecs;
+    use crate::{
+        archetype_invariants::{
+            ArchetypeInvariant, ArchetypeInvariantViolation, ArchetypeInvariants,
+            ArchetypeStatement, ComponentIdSet, Severity, UnregisteredComponentError,
+            UntypedArchetypeInvariant, UntypedArchetypeStatement,
+        },
+        component::{Component, ComponentId, StorageType},
+        entity::Entity,
+        system::Resource,
+        world::World,
+    };
+
+    #[derive(Component)]
+    struct A;
+
+    #[derive(Component)]
+    struct B;
+
+    #[derive(Component)]
+    struct C;
+
+    #[derive(Component)]
+    struct D;
+
+    fn set(ids: &[ComponentId]) -> ComponentIdSet {
+        ids.iter().copied().collect()
+    }
+
+    #[test]
+    fn exactly_one_of() {
+        let mut world = World::new();
+        let statement = ArchetypeStatement::<(A, B, C)>::exactly_one_of().into_untyped(&mut world);
+        let a = world.init_component::<A>();
+        let b = world.init_component::<B>();
+        let c = world.init_component::<C>();
+        let d = world.init_component::<D>();
+
+        assert_eq!(
+            statement,
+            UntypedArchetypeStatement::ExactlyOneOf(set(&[a, b, c]))
+        );
+
+        // Zero present
+        assert!(!statement.evaluate(&set(&[])));
+        assert!(!statement.evaluate(&set(&[d])));
+        // One present
+        assert!(statement.evaluate(&set(&[a])));
+        assert!(statement.evaluate(&set(&[c, d])));
+        // Two or more present
+        assert!(!statement.evaluate(&set(&[a, b])));
+        assert!(!statement.evaluate(&set(&[a, b, c])));
+    }
+
+    #[test]
+    fn at_most_one_of() {
+        let mut world = World::new();
+        let statement = ArchetypeStatement::<(A, B, C)>::at_most_one_of().into_untyped(&mut world);
+        let a = world.init_component::<A>();
+        let b = world.init_component::<B>();
+        let c = world.init_component::<C>();
+        let d = world.init_component::<D>();
+
+        assert_eq!(
+            statement,
+            UntypedArchetypeStatement::AtMostOneOf(set(&[a, b, c]))
+        );
+
+        // Zero present
+        assert!(statement.evaluate(&set(&[])));
+        assert!(statement.evaluate(&set(&[d])));
+        // One present
+        assert!(statement.evaluate(&set(&[b])));
+        assert!(statement.evaluate(&set(&[c, d])));
+        // Two or more present
+        assert!(!statement.evaluate(&set(&[a, c])));
+        assert!(!statement.evaluate(&set(&[a, b, c, d])));
+    }
+
+    #[test]
+    fn counting_statements() {
+        let mut world = World::new();
+        let exactly_two = ArchetypeStatement::<(A, B, C)>::exactly_n_of(2).into_untyped(&mut world);
+        let at_least_two =
+            ArchetypeStatement::<(A, B, C)>::at_least_n_of(2).into_untyped(&mut world);
+        let at_most_two = ArchetypeStatement::<(A, B, C)>::at_most_n_of(2).into_untyped(&mut world);
+        let a = world.init_component::<A>();
+        let b = world.init_component::<B>();
+        let c = world.init_component::<C>();
+        let d = world.init_component::<D>();
+
+        assert_eq!(
+            exactly_two,
+            UntypedArchetypeStatement::ExactlyNOf(set(&[a, b, c]), 2)
+        );
+
+        let none = set(&[d]);
+        let one = set(&[a, d]);
+        let two = set(&[a, c, d]);
+        let three = set(&[a, b, c]);
+
+        assert!(!exactly_two.evaluate(&none));
+        assert!(!exactly_two.evaluate(&one));
+        assert!(exactly_two.evaluate(&two));
+        assert!(!exactly_two.evaluate(&three));
+
+        assert!(!at_least_two.evaluate(&none));
+        assert!(!at_least_two.evaluate(&one));
+        assert!(at_least_two.evaluate(&two));
+        assert!(at_least_two.evaluate(&three));
+
+        assert!(at_most_two.evaluate(&none));
+        assert!(at_most_two.evaluate(&one));
+        assert!(at_most_two.evaluate(&two));
+        assert!(!at_most_two.evaluate(&three));
+    }
+
+    #[test]
+    fn counting_statements_with_zero() {
+        let mut world = World::new();
+        let exactly_zero = ArchetypeStatement::<(A, B)>::exactly_n_of(0).into_untyped(&mut world);
+        let at_least_zero = ArchetypeStatement::<(A, B)>::at_least_n_of(0).into_untyped(&mut world);
+        let at_most_zero = ArchetypeStatement::<(A, B)>::at_most_n_of(0).into_untyped(&mut world);
+        let a = world.init_component::<A>();
+
+        assert!(exactly_zero.evaluate(&set(&[])));
+        assert!(!exactly_zero.evaluate(&set(&[a])));
+        assert!(at_least_zero.evaluate(&set(&[])));
+        assert!(at_least_zero.evaluate(&set(&[a])));
+        assert!(at_most_zero.evaluate(&set(&[])));
+        assert!(!at_most_zero.evaluate(&set(&[a])));
+    }
+
+    #[test]
+    fn full_bundle() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B, C)>::full_bundle());
+
+        world.spawn((A, B, C));
+        world.spawn((A, B, C, D));
+        world.spawn(D);
+        world.spawn_empty();
+        world.spawn_batch(vec![(A, B, C), (A, B, C)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn full_bundle_on_spawn() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B, C)>::full_bundle());
+
+        world.spawn((A, B));
+    }
+
+    #[test]
+    #[should_panic]
+    fn full_bundle_on_spawn_batch() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B, C)>::full_bundle());
+
+        world.spawn_batch(vec![(B, D), (B, D)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn full_bundle_on_insert() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B, C)>::full_bundle());
+
+        world.spawn(D).insert(C);
+    }
+
+    #[test]
+    #[should_panic]
+    fn full_bundle_on_remove() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B, C)>::full_bundle());
+
+        world.spawn((A, B, C)).remove::<A>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn full_bundle_on_remove_intersection() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B, C)>::full_bundle());
+
+        world.spawn((A, B, C)).remove_intersection::<(A, D)>();
+    }
+
+    #[test]
+    fn at_least_k() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B, C, D)>::at_least_k(2));
+
+        world.spawn_empty();
+        world.spawn((A, B));
+        world.spawn((B, D));
+        world.spawn((A, C, D));
+        world.spawn((A, B, C, D));
+    }
+
+    #[test]
+    #[should_panic]
+    fn at_least_k_with_too_few() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B, C, D)>::at_least_k(2));
+
+        world.spawn((A, B, C)).remove::<(A, B)>();
+    }
+
+    #[test]
+    fn at_least_k_generalizes_full_bundle() {
+        let mut world = World::new();
+        let full_bundle =
+            ArchetypeInvariant::<(A, B, C, D)>::full_bundle().into_untyped(&mut world);
+        let at_least_four =
+            ArchetypeInvariant::<(A, B, C, D)>::at_least_k(4).into_untyped(&mut world);
+        let ids = [
+            world.init_component::<A>(),
+            world.init_component::<B>(),
+            world.init_component::<C>(),
+            world.init_component::<D>(),
+        ];
+        for mask in 0..16 {
+            let component_ids: ComponentIdSet = ids
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| mask & (1 << i) != 0)
+                .map(|(_, &id)| id)
+                .collect();
+            assert_eq!(
+                at_least_four.evaluate(&component_ids),
+                full_bundle.evaluate(&component_ids)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn existing_archetypes_are_checked() {
+        let mut world = World::new();
+        world.spawn((A, B));
+
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B, C)>::full_bundle());
+    }
+
+    #[test]
+    fn untyped_invariant_evaluate() {
+        let mut world = World::new();
+        let invariant = ArchetypeInvariant::<(A, B, C)>::full_bundle().into_untyped(&mut world);
+        let a = world.init_component::<A>();
+        let b = world.init_component::<B>();
+        let c = world.init_component::<C>();
+        let d = world.init_component::<D>();
+
+        assert!(invariant.evaluate(&set(&[])));
+        assert!(invariant.evaluate(&set(&[d])));
+        assert!(invariant.evaluate(&set(&[a, b, c])));
+        assert!(!invariant.evaluate(&set(&[a])));
+        assert!(!invariant.evaluate(&set(&[b, c, d])));
+    }
+
+    #[test]
+    fn violation_details() {
+        let mut world = World::new();
+        let invariant = ArchetypeInvariant::<(A,), (B,)> {
+            predicate: ArchetypeStatement::all_of(),
+            consequence: ArchetypeStatement::all_of(),
+        }
+        .into_untyped(&mut world);
+        let mut archetype_invariants = ArchetypeInvariants::default();
+        let line = line!() + 1;
+        archetype_invariants.add(invariant.clone(), world.components());
+
+        let entity = world.spawn((A, C)).id();
+        let archetype = &world.archetypes()[world.entity(entity).location().archetype_id];
+        let violation = archetype_invariants
+            .check_archetype(archetype, world.components())
+            .unwrap_err();
+
+        assert_eq!(violation.archetype_id, archetype.id());
+        assert_eq!(
+            violation.component_ids,
+            vec![
+                world.component_id::<A>().unwrap(),
+                world.component_id::<C>().unwrap()
+            ]
+        );
+        assert_eq!(violation.entities, vec![entity]);
+        assert_eq!(violation.invariant, invariant);
+        assert_eq!(
+            violation.to_string(),
+            format!(
+                "Entity in archetype {} has components [A, C] but invariant AllOf(A) => AllOf(B) was violated. Affected entities include [{:?}]. \
+                The invariant was registered at {}:{line}.",
+                archetype.id().index(),
+                entity,
+                file!()
+            )
+        );
+
+        let valid_entity = world.spawn((A, B)).id();
+        let valid_archetype =
+            &world.archetypes()[world.entity(valid_entity).location().archetype_id];
+        assert!(archetype_invariants
+            .check_archetype(valid_archetype, world.components())
+            .is_ok());
+    }
+
+    #[test]
+    fn check_component_ids() {
+        #[derive(Resource)]
+        struct R;
+
+        let mut world = World::new();
+        let [a, b, c] = [
+            world.init_component::<A>(),
+            world.init_component::<B>(),
+            world.init_component::<C>(),
+        ];
+        world.insert_resource(R);
+        let r = world
+            .components()
+            .get_resource_id(std::any::TypeId::of::<R>())
+            .unwrap();
+        let mut archetype_invariants = ArchetypeInvariants::default();
+        let requires = ArchetypeInvariant::<(A,), (B,)>::requires().into_untyped(&mut world);
+        archetype_invariants.add(requires.clone(), world.components());
+        let disabled = archetype_invariants.add(
+            ArchetypeInvariant::<(A,), (C,)>::forbids().into_untyped(&mut world),
+            world.components(),
+        );
+        archetype_invariants.set_enabled(disabled, false);
+        archetype_invariants.add(
+            UntypedArchetypeInvariant {
+                predicate: UntypedArchetypeStatement::AllOf(set(&[c])),
+                consequence: UntypedArchetypeStatement::ResourcePresent(r),
+                severity: Severity::Error,
+            },
+            world.components(),
+        );
+
+        let hashed: HashSet<ComponentId> = [a, c].into_iter().collect();
+        let violation = archetype_invariants
+            .check_component_ids(&hashed.into(), &ComponentIdSet::new(), world.components())
+            .unwrap_err();
+        assert_eq!(
+            violation.archetype_id,
+            crate::archetype::ArchetypeId::INVALID
+        );
+        assert_eq!(violation.component_ids, vec![a, c]);
+        assert!(violation.entities.is_empty());
+        assert_eq!(violation.invariant, requires);
+        assert!(violation.location.is_some());
+
+        assert!(archetype_invariants
+            .check_component_ids(&set(&[a, b]), &ComponentIdSet::new(), world.components())
+            .is_ok());
+        assert!(archetype_invariants
+            .check_component_ids(&set(&[c]), &ComponentIdSet::new(), world.components())
+            .is_err());
+        assert!(archetype_invariants
+            .check_component_ids(&set(&[c]), &set(&[r]), world.components())
+            .is_ok());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "has components [A, D] but invariant AtLeastNOf(1, [A]) => NoneOf(D)"
+    )]
+    fn violation_panic_message() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (D,)> {
+            predicate: ArchetypeStatement::at_least_n_of(1),
+            consequence: ArchetypeStatement::none_of(),
+        });
+
+        world.spawn((A, D));
+    }
+
+    #[test]
+    fn check_archetype_invariants() {
+        let mut world = World::new();
+        world.spawn((A, B));
+        world.spawn((A, B, C));
+        world.spawn(C);
+
+        let full_bundle = ArchetypeInvariant::<(A, B, C)>::full_bundle().into_untyped(&mut world);
+        let forbid_c = ArchetypeInvariant::<(C,), (A,)> {
+            predicate: ArchetypeStatement::all_of(),
+            consequence: ArchetypeStatement::none_of(),
+        }
+        .into_untyped(&mut world);
+        world
+            .archetype_invariants
+            .add(full_bundle.clone(), &world.components);
+        world
+            .archetype_invariants
+            .add(forbid_c.clone(), &world.components);
+
+        let violations = world.check_archetype_invariants();
+        assert_eq!(violations.len(), 3);
+        assert_eq!(
+            violations
+                .iter()
+                .filter(|violation| violation.invariant == full_bundle)
+                .count(),
+            2
+        );
+        assert_eq!(
+            violations
+                .iter()
+                .filter(|violation| violation.invariant == forbid_c)
+                .count(),
+            1
+        );
+
+        // Checking is read-only, and can be repeated
+        assert_eq!(world.check_archetype_invariants(), violations);
+    }
+
+    #[test]
+    fn remove_archetype_invariant() {
+        let mut world = World::new();
+        let full_bundle =
+            world.add_archetype_invariant(ArchetypeInvariant::<(A, B, C)>::full_bundle());
+        let forbid_d = world.add_archetype_invariant(ArchetypeInvariant::<(D,)> {
+            predicate: ArchetypeStatement::all_of(),
+            consequence: ArchetypeStatement::none_of(),
+        });
+        assert_ne!(full_bundle, forbid_d);
+
+        let removed = world.remove_archetype_invariant(full_bundle).unwrap();
+        assert_eq!(
+            removed,
+            ArchetypeInvariant::<(A, B, C)>::full_bundle().into_untyped(&mut world)
+        );
+        assert!(world.remove_archetype_invariant(full_bundle).is_none());
+
+        // The removed invariant is no longer enforced
+        world.spawn(A);
+        world.spawn((B, C));
+        assert!(world.check_archetype_invariants().is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn remaining_archetype_invariant_is_enforced() {
+        let mut world = World::new();
+        let full_bundle =
+            world.add_archetype_invariant(ArchetypeInvariant::<(A, B, C)>::full_bundle());
+        world.add_archetype_invariant(ArchetypeInvariant::<(D,)> {
+            predicate: ArchetypeStatement::all_of(),
+            consequence: ArchetypeStatement::none_of(),
+        });
+        world.remove_archetype_invariant(full_bundle);
+
+        world.spawn(D);
+    }
+
+    #[test]
+    fn clear_archetype_invariants() {
+        let mut world = World::new();
+        // Clearing an empty set of invariants is fine
+        world.clear_archetype_invariants();
+
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B, C)>::full_bundle());
+        world.add_archetype_invariant(ArchetypeInvariant::<(D,)> {
+            predicate: ArchetypeStatement::all_of(),
+            consequence: ArchetypeStatement::none_of(),
+        });
+        world.clear_archetype_invariants();
+
+        world.spawn((A, D));
+        assert!(world.check_archetype_invariants().is_empty());
+    }
+
+    #[test]
+    fn add_archetype_invariants_after_clear() {
+        use crate::{
+            archetype_invariants::{ArchetypeInvariantViolated, ViolationPolicy},
+            event::Events,
+        };
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Event);
+        world.archetype_invariants.set_check_empty_archetypes(false);
+        let id = world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        let exempt = world.spawn_empty().id();
+        world.exempt_entity_from_invariant(exempt, id);
+        world.entity_mut(exempt).insert(A);
+        let empty = world.spawn((A, C)).id();
+        world.despawn(empty);
+        world.spawn((A, D));
+        world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .clear();
+
+        world.clear_archetype_invariants();
+        let invariants = &world.archetype_invariants;
+        assert_eq!(invariants.last_checked_archetype_index, 0);
+        assert!(invariants.skipped_empty_archetypes.is_empty());
+        assert!(invariants.skipped_exempt_archetypes.is_empty());
+
+        // Every archetype that already exists is checked against the new invariants exactly once
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (D,)>::forbids());
+        let violations: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .collect();
+        assert_eq!(violations.len(), 1);
+        world.spawn((A, D));
+        world.spawn(A);
+        assert!(world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .next()
+            .is_none());
+        assert_eq!(world.check_archetype_invariants().len(), 1);
+    }
+
+    #[test]
+    fn iterate_archetype_invariants() {
+        let mut world = World::new();
+        assert_eq!(world.archetype_invariants().count(), 0);
+
+        let full_bundle =
+            world.add_archetype_invariant(ArchetypeInvariant::<(A, B, C)>::full_bundle());
+        let forbid_d = ArchetypeInvariant::<(D,)> {
+            predicate: ArchetypeStatement::all_of(),
+            consequence: ArchetypeStatement::none_of(),
+        }
+        .into_untyped(&mut world);
+        world.add_untyped_archetype_invariant(forbid_d.clone());
+        assert_eq!(world.archetype_invariants().count(), 2);
+        assert_eq!(world.archetype_invariants.len(), 2);
+
+        world.remove_archetype_invariant(full_bundle);
+        assert_eq!(
+            world.archetype_invariants().collect::<Vec<_>>(),
+            vec![&forbid_d]
+        );
+
+        world.clear_archetype_invariants();
+        assert!(world.archetype_invariants.is_empty());
+    }
+
+    #[test]
+    fn and_or() {
+        let mut world = World::new();
+        let a_and_not_b = ArchetypeStatement::<(A,)>::all_of()
+            .and(ArchetypeStatement::<(A,)>::at_least_one_of())
+            .and(ArchetypeStatement::<(A,)>::exactly_one_of())
+            .into_untyped(&mut world);
+        let none_or_all = ArchetypeStatement::<(A, B)>::none_of()
+            .or(ArchetypeStatement::<(A, B)>::all_of())
+            .into_untyped(&mut world);
+        let a = world.init_component::<A>();
+        let b = world.init_component::<B>();
+        let d = world.init_component::<D>();
+
+        assert_eq!(
+            a_and_not_b,
+            UntypedArchetypeStatement::And(vec![
+                UntypedArchetypeStatement::AllOf(set(&[a])),
+                UntypedArchetypeStatement::AtLeastOneOf(set(&[a])),
+                UntypedArchetypeStatement::ExactlyOneOf(set(&[a])),
+            ])
+        );
+        assert!(a_and_not_b.evaluate(&set(&[a, b])));
+        assert!(!a_and_not_b.evaluate(&set(&[b])));
+
+        assert!(none_or_all.evaluate(&set(&[])));
+        assert!(none_or_all.evaluate(&set(&[a, b, d])));
+        assert!(!none_or_all.evaluate(&set(&[a, d])));
+        assert_eq!(none_or_all.component_ids(), set(&[a, b]));
+    }
+
+    #[test]
+    fn heterogeneous_and_or() {
+        let mut world = World::new();
+        let a = world.init_component::<A>();
+        let b = world.init_component::<B>();
+        let c = world.init_component::<C>();
+
+        // (A and B) or (not C)
+        let statement = UntypedArchetypeStatement::Or(vec![
+            UntypedArchetypeStatement::And(vec![
+                UntypedArchetypeStatement::AllOf(set(&[a])),
+                UntypedArchetypeStatement::AllOf(set(&[b])),
+            ]),
+            UntypedArchetypeStatement::NoneOf(set(&[c])),
+        ]);
+        assert!(statement.evaluate(&set(&[])));
+        assert!(statement.evaluate(&set(&[a, b, c])));
+        assert!(!statement.evaluate(&set(&[a, c])));
+        assert_eq!(statement.component_ids(), set(&[a, b, c]));
+
+        // Empty combinators
+        assert!(UntypedArchetypeStatement::And(Vec::new()).evaluate(&set(&[a])));
+        assert!(!UntypedArchetypeStatement::Or(Vec::new()).evaluate(&set(&[a])));
+    }
+
+    #[test]
+    fn not() {
+        let mut world = World::new();
+        let not_all = ArchetypeStatement::<(A, B)>::all_of()
+            .not()
+            .into_untyped(&mut world);
+        let a = world.init_component::<A>();
+        let b = world.init_component::<B>();
+        let c = world.init_component::<C>();
+
+        assert_eq!(
+            not_all,
+            UntypedArchetypeStatement::Not(Box::new(UntypedArchetypeStatement::AllOf(set(&[
+                a, b
+            ]))))
+        );
+        assert!(not_all.evaluate(&set(&[])));
+        assert!(not_all.evaluate(&set(&[a])));
+        assert!(not_all.evaluate(&set(&[b, c])));
+        assert!(!not_all.evaluate(&set(&[a, b])));
+        assert!(!not_all.evaluate(&set(&[a, b, c])));
+        assert_eq!(not_all.component_ids(), set(&[a, b]));
+    }
+
+    #[test]
+    fn statements_about_different_bundles() {
+        let mut world = World::new();
+        let statement = || {
+            ArchetypeStatement::<(A, B)>::all_of()
+                .or_statement(ArchetypeStatement::<(C,)>::none_of())
+        };
+        let untyped = statement().into_untyped(&mut world);
+        let a = world.init_component::<A>();
+        let b = world.init_component::<B>();
+        let c = world.init_component::<C>();
+
+        assert_eq!(
+            untyped,
+            UntypedArchetypeStatement::Or(vec![
+                UntypedArchetypeStatement::AllOf(set(&[a, b])),
+                UntypedArchetypeStatement::NoneOf(set(&[c])),
+            ])
+        );
+        assert!(untyped.evaluate(&set(&[])));
+        assert!(untyped.evaluate(&set(&[a, b, c])));
+        assert!(!untyped.evaluate(&set(&[a, c])));
+        assert_eq!(statement().try_into_untyped(&world), Ok(untyped));
+
+        // Foreign statements are only equal to statements about the same bundle
+        assert!(statement() == statement());
+        assert!(
+            ArchetypeStatement::<(A,)>::always()
+                .and_statement(ArchetypeStatement::<(B,)>::all_of())
+                != ArchetypeStatement::<(A,)>::always()
+                    .and_statement(ArchetypeStatement::<(C,)>::all_of())
+        );
+
+        let invariant = ArchetypeInvariant::<(D,), (A, B)> {
+            predicate: ArchetypeStatement::all_of(),
+            consequence: statement(),
+        };
+        world.add_archetype_invariant(invariant);
+        world.spawn((D, A, B));
+        world.spawn((D, B));
+        assert!(world.check_archetype_invariants().is_empty());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "has components [A, B, C] but invariant AllOf(C) => Not(AllOf(A, B))"
+    )]
+    fn not_consequence() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(C,), (A, B)> {
+            predicate: ArchetypeStatement::all_of(),
+            consequence: ArchetypeStatement::all_of().not(),
+        });
+        world.spawn((A, C));
+        world.spawn((B, C));
+        world.spawn((A, B));
+
+        world.spawn((A, B, C));
+    }
+
+    #[test]
+    #[should_panic(expected = "has components [B] but invariant And(AtLeastOneOf(")]
+    fn compound_predicate() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B), (C,)> {
+            predicate: ArchetypeStatement::at_least_one_of()
+                .and(ArchetypeStatement::at_most_one_of()),
+            consequence: ArchetypeStatement::all_of(),
+        });
+        world.spawn((A, B));
+        world.spawn((A, C));
+
+        world.spawn(B);
+    }
+
+    #[test]
+    fn requires() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        world.spawn((A, B));
+        world.spawn(B);
+        world.spawn(C);
+        let entity = world.spawn(B).id();
+        world.entity_mut(entity).insert(A);
+    }
+
+    #[test]
+    #[should_panic]
+    fn requires_on_spawn() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        world.spawn((A, C));
+    }
+
+    #[test]
+    #[should_panic]
+    fn requires_on_remove() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        let entity = world.spawn((A, B)).id();
+        world.entity_mut(entity).remove::<B>();
+    }
+
+    #[test]
+    fn forbids() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::forbids());
+        world.spawn(A);
+        world.spawn(B);
+        world.spawn((A, C));
+        world.spawn((B, C));
+    }
+
+    #[test]
+    #[should_panic]
+    fn forbids_on_insert() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::forbids());
+        let entity = world.spawn(B).id();
+        world.entity_mut(entity).insert(A);
+    }
+
+    #[test]
+    fn mutually_exclusive() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B, C)>::mutually_exclusive());
+        world.spawn(A);
+        world.spawn(B);
+        world.spawn((C, D));
+        world.spawn(D);
+        let entity = world.spawn(A).id();
+        world.entity_mut(entity).remove::<A>().unwrap();
+        world.entity_mut(entity).insert(B);
+    }
+
+    #[test]
+    #[should_panic]
+    fn mutually_exclusive_two_present() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B, C)>::mutually_exclusive());
+        world.spawn((A, C));
+    }
+
+    #[test]
+    fn disjoint() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B), (C, D)>::disjoint());
+        world.spawn(A);
+        world.spawn((A, B));
+        world.spawn(C);
+        world.spawn((C, D));
+    }
+
+    #[test]
+    #[should_panic]
+    fn disjoint_mixed() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B), (C, D)>::disjoint());
+        world.spawn((A, D));
+    }
+
+    #[test]
+    #[should_panic]
+    fn disjoint_is_symmetric() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B), (C, D)>::disjoint());
+        let entity = world.spawn((C, D)).id();
+        world.entity_mut(entity).insert(B);
+    }
+
+    #[test]
+    fn contradictions() {
+        let mut world = World::new();
+        let contradiction = ArchetypeInvariant::<(A, B), (B, C, A)> {
+            predicate: ArchetypeStatement::all_of(),
+            consequence: ArchetypeStatement::none_of(),
+        }
+        .into_untyped(&mut world);
+        let forbids = ArchetypeInvariant::<(A,), (B,)>::forbids().into_untyped(&mut world);
+        let full_bundle = ArchetypeInvariant::<(A, B)>::full_bundle().into_untyped(&mut world);
+        let a = world.init_component::<A>();
+        let b = world.init_component::<B>();
+
+        assert_eq!(contradiction.contradictions(), vec![a, b]);
+        assert!(forbids.contradictions().is_empty());
+        assert!(full_bundle.contradictions().is_empty());
+
+        // Contradictory invariants can still be added
+        world.add_untyped_archetype_invariant(contradiction);
+        world.spawn(C);
+    }
+
+    #[test]
+    fn duplicate_archetype_invariant() {
+        let mut world = World::new();
+        let first = world.add_archetype_invariant(ArchetypeInvariant::<(A, B)>::full_bundle());
+        let other = world.add_archetype_invariant(ArchetypeInvariant::<(A,), (C,)>::forbids());
+        let duplicate = world.add_archetype_invariant(ArchetypeInvariant::<(A, B)>::full_bundle());
+
+        assert_eq!(first, duplicate);
+        assert_ne!(first, other);
+        assert_eq!(world.archetype_invariants().count(), 2);
+
+        // The handle of the duplicate refers to the original invariant
+        world.remove_archetype_invariant(duplicate).unwrap();
+        assert_eq!(world.archetype_invariants().count(), 1);
+        assert!(world.remove_archetype_invariant(first).is_none());
+    }
+
+    #[test]
+    fn incremental_checking() {
+        #[derive(Component)]
+        struct E;
+
+        // The invariants are kept outside of the world, so that they are only checked explicitly
+        let mut invariants = ArchetypeInvariants::default();
+        let mut world = World::new();
+        world.spawn(A);
+        world.spawn((A, B));
+        world.spawn((A, C));
+        // Empty, resource, [A], [A, B] and [A, C]
+        assert_eq!(world.archetypes().len(), 5);
+        assert_eq!(check(&mut invariants, &world), 0);
+
+        // Only the new invariant is checked against the existing archetypes, skipping the resource archetype
+        let forbids = ArchetypeInvariant::<(A,), (D,)>::forbids().into_untyped(&mut world);
+        let forbids = invariants.add(forbids, &world.components);
+        assert_eq!(check(&mut invariants, &world), 4);
+        assert_eq!(check(&mut invariants, &world), 0);
+
+        let requires = ArchetypeInvariant::<(D,), (E,)>::requires().into_untyped(&mut world);
+        invariants.add(requires, &world.components);
+        assert_eq!(check(&mut invariants, &world), 4);
+
+        // New archetypes are checked against every invariant exactly once
+        world.spawn(B);
+        world.spawn(C);
+        world.spawn((B, C, E));
+        assert_eq!(world.archetypes().len(), 8);
+        assert_eq!(check(&mut invariants, &world), 6);
+        assert_eq!(check(&mut invariants, &world), 0);
+
+        // Removing an invariant does not cause anything to be re-checked
+        let unchecked = ArchetypeInvariant::<(E,), (A,)>::forbids().into_untyped(&mut world);
+        let unchecked = invariants.add(unchecked, &world.components);
+        invariants.remove(forbids);
+        invariants.remove(unchecked);
+        assert_eq!(check(&mut invariants, &world), 0);
+
+        // Invariants added after a removal are still checked against existing archetypes
+        let mutually_exclusive =
+            ArchetypeInvariant::<(A, E)>::mutually_exclusive().into_untyped(&mut world);
+        invariants.add(mutually_exclusive, &world.components);
+        world.spawn(E);
+        // 7 existing archetypes against the new invariant, and the new archetype against both invariants
+        assert_eq!(check(&mut invariants, &world), 9);
+
+        fn check(invariants: &mut ArchetypeInvariants, world: &World) -> usize {
+            invariants.check_new_archetypes(&world.archetypes, &world.components)
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_invariant_checks_existing_archetypes_after_removal() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (D,)>::forbids());
+        let removed = world.add_archetype_invariant(ArchetypeInvariant::<(B,), (D,)>::forbids());
+        world.spawn((A, B));
+        world.remove_archetype_invariant(removed);
+
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B)>::mutually_exclusive());
+    }
+
+    #[test]
+    fn bundle_component_ids_are_cached() {
+        let mut world = World::new();
+        let b = world.init_component::<B>();
+        let first = ArchetypeStatement::<(A, B)>::all_of().into_untyped(&mut world);
+        let a = world.init_component::<A>();
+        world.init_component::<C>();
+        let second = ArchetypeStatement::<(A, B)>::none_of().into_untyped(&mut world);
+
+        assert_eq!(first.component_ids(), set(&[a, b]));
+        assert_eq!(second.component_ids(), set(&[a, b]));
+        assert_eq!(world.archetype_invariants.bundle_component_ids.len(), 1);
+
+        ArchetypeStatement::<(C,)>::all_of().into_untyped(&mut world);
+        assert_eq!(world.archetype_invariants.bundle_component_ids.len(), 2);
+    }
+
+    #[test]
+    fn component_id_set() {
+        let [a, b, c, d, e, f] = [5, 1, 3, 7, 0, 9].map(ComponentId::new);
+        let mut component_ids: ComponentIdSet = [c, a, b, a].into_iter().collect();
+        assert_eq!(component_ids.as_slice(), &[b, c, a]);
+        assert!(component_ids.insert(e));
+        assert!(!component_ids.insert(c));
+        component_ids.extend([f, a, d]);
+        assert_eq!(component_ids.as_slice(), &[e, b, c, a, d, f]);
+        assert!(component_ids.contains(d));
+        assert!(!component_ids.contains(ComponentId::new(2)));
+
+        let hashed: HashSet<ComponentId> = [d, a, e].into_iter().collect();
+        let subset = ComponentIdSet::from(hashed);
+        assert_eq!(subset.iter().collect::<Vec<_>>(), vec![e, a, d]);
+        assert!(subset.is_subset(&component_ids));
+        assert!(!component_ids.is_subset(&subset));
+        assert!(ComponentIdSet::new().is_subset(&subset));
+
+        let other = set(&[b, ComponentId::new(4), f]);
+        assert!(other.is_disjoint(&subset));
+        assert!(!other.is_disjoint(&component_ids));
+        assert_eq!(other.count_common(&component_ids, 5), 2);
+        assert_eq!(other.count_common(&component_ids, 1), 1);
+        assert_eq!(other.count_common(&component_ids, 0), 0);
+    }
+
+    #[cfg(feature = "bevy_reflect")]
+    #[test]
+    fn reflect_untyped_statement() {
+        use bevy_reflect::{FromReflect, Reflect, ReflectRef, TypeInfo, TypeRegistry};
+        use std::any::TypeId;
+
+        let mut registry = TypeRegistry::default();
+        registry.register::<UntypedArchetypeStatement>();
+        let info = match registry.get_type_info(TypeId::of::<UntypedArchetypeStatement>()) {
+            Some(TypeInfo::Enum(info)) => info,
+            _ => panic!("expected enum type info"),
+        };
+        assert!(info.contains_variant("AllOf"));
+        assert!(info.contains_variant("AtMostNOf"));
+        assert!(info.contains_variant("Not"));
+
+        let mut world = World::new();
+        let statement = ArchetypeStatement::<(A, B)>::exactly_n_of(1)
+            .or(ArchetypeStatement::all_of().not())
+            .into_untyped(&mut world);
+        match statement.reflect_ref() {
+            ReflectRef::Enum(reflected) => {
+                assert_eq!(reflected.variant_name(), "Or");
+                assert_eq!(reflected.field_len(), 1);
+            }
+            _ => panic!("expected a reflected enum"),
+        }
+        let cloned = UntypedArchetypeStatement::from_reflect(&*statement.clone_value()).unwrap();
+        assert_eq!(cloned, statement);
+    }
+
+    #[cfg(feature = "bevy_reflect")]
+    #[test]
+    fn reflect_component_ids_and_negations() {
+        use bevy_reflect::{DynamicList, DynamicTupleStruct, FromReflect, Reflect, ReflectRef};
+
+        let mut world = World::new();
+        let [a, b] = [world.init_component::<A>(), world.init_component::<B>()];
+        let statement =
+            UntypedArchetypeStatement::Not(Box::new(UntypedArchetypeStatement::AllOf(set(&[
+                a, b,
+            ]))));
+
+        // The negated statement and the components of its set can be inspected
+        let negated = match statement.reflect_ref() {
+            ReflectRef::Enum(not) => {
+                assert_eq!(not.variant_name(), "Not");
+                not.field_at(0).unwrap()
+            }
+            _ => panic!("expected a reflected enum"),
+        };
+        let component_ids = match negated.reflect_ref() {
+            ReflectRef::Enum(all_of) => {
+                assert_eq!(all_of.variant_name(), "AllOf");
+                all_of.field_at(0).unwrap()
+            }
+            _ => panic!("expected a reflected enum"),
+        };
+        let list = match component_ids.reflect_ref() {
+            ReflectRef::TupleStruct(set) => match set.field(0).unwrap().reflect_ref() {
+                ReflectRef::List(list) => list,
+                _ => panic!("expected a reflected list"),
+            },
+            _ => panic!("expected a reflected tuple struct"),
+        };
+        let listed: Vec<_> = list
+            .iter()
+            .map(|id| *id.downcast_ref::<ComponentId>().unwrap())
+            .collect();
+        assert_eq!(listed, [a, b]);
+
+        let mut other = UntypedArchetypeStatement::Not(Box::new(UntypedArchetypeStatement::Always));
+        other.apply(&statement);
+        assert_eq!(other, statement);
+
+        // Sets built through reflection are sorted
+        let mut list = DynamicList::default();
+        list.push(b);
+        list.push(a);
+        let mut reflected = DynamicTupleStruct::default();
+        reflected.insert(list);
+        assert_eq!(ComponentIdSet::from_reflect(&reflected), Some(set(&[a, b])));
+    }
+
+    #[test]
+    fn display() {
+        let mut world = World::new();
+        let invariant = ArchetypeInvariant::<(A,), (B, C)> {
+            predicate: ArchetypeStatement::all_of(),
+            consequence: ArchetypeStatement::at_least_n_of(2)
+                .and(ArchetypeStatement::none_of().not()),
+        }
+        .into_untyped(&mut world);
+
+        assert_eq!(invariant.predicate.display(&world).to_string(), "AllOf(A)");
+        assert_eq!(
+            invariant.display(&world).to_string(),
+            "AllOf(A) => And(AtLeastNOf(2, [B, C]), Not(NoneOf(B, C)))"
+        );
+    }
+
+    #[test]
+    fn when_then() {
+        let mut world = World::new();
+        let built = ArchetypeInvariant::when(ArchetypeStatement::<(A,)>::all_of())
+            .then(ArchetypeStatement::<(B, C)>::none_of())
+            .into_untyped(&mut world);
+        let literal = ArchetypeInvariant::<(A,), (B, C)> {
+            predicate: ArchetypeStatement::all_of(),
+            consequence: ArchetypeStatement::none_of(),
+        }
+        .into_untyped(&mut world);
+        assert_eq!(built, literal);
+
+        let required = ArchetypeInvariant::when(ArchetypeStatement::<(A,)>::all_of())
+            .require(ArchetypeStatement::<(B,)>::all_of())
+            .into_untyped(&mut world);
+        assert_eq!(
+            required,
+            ArchetypeInvariant::<(A,), (B,)>::requires().into_untyped(&mut world)
+        );
+    }
+
+    #[test]
+    fn add_archetype_invariants() {
+        let mut world = World::new();
+        world.spawn((A, B));
+        world.spawn(C);
+        let invariants = vec![
+            ArchetypeInvariant::<(A, B)>::full_bundle().into_untyped(&mut world),
+            ArchetypeInvariant::<(C,), (A,)>::forbids().into_untyped(&mut world),
+        ];
+        let ids = world.add_archetype_invariants(invariants.clone());
+
+        assert_eq!(ids.len(), 2);
+        assert_eq!(
+            world.archetype_invariants().cloned().collect::<Vec<_>>(),
+            invariants
+        );
+        assert_eq!(
+            world
+                .archetype_invariants
+                .check_new_archetypes(&world.archetypes, &world.components),
+            0
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn add_archetype_invariants_checks_existing_archetypes() {
+        let mut world = World::new();
+        world.spawn((A, C));
+        let invariants = vec![
+            ArchetypeInvariant::<(A, B)>::full_bundle().into_untyped(&mut world),
+            ArchetypeInvariant::<(C,), (A,)>::forbids().into_untyped(&mut world),
+        ];
+        world.add_archetype_invariants(invariants);
+    }
+
+    #[test]
+    fn labeled_archetype_invariants() {
+        let mut world = World::new();
+        world.add_archetype_invariant_labeled(
+            "physics",
+            ArchetypeInvariant::<(A, B)>::full_bundle(),
+        );
+        world.add_archetype_invariant_labeled(
+            "physics",
+            ArchetypeInvariant::<(A,), (C,)>::forbids(),
+        );
+        world.add_archetype_invariant_labeled(
+            "render",
+            ArchetypeInvariant::<(D,), (C,)>::requires(),
+        );
+        // The same rule in a different group is kept, so that each group can be removed independently
+        world
+            .add_archetype_invariant_labeled("render", ArchetypeInvariant::<(A, B)>::full_bundle());
+        world.add_archetype_invariant(ArchetypeInvariant::<(D,), (B,)>::forbids());
+        assert_eq!(world.archetype_invariants().count(), 5);
+
+        let removed = world.remove_archetype_invariants_by_label("physics");
+        assert_eq!(removed.len(), 2);
+        assert_eq!(world.archetype_invariants().count(), 3);
+        assert!(world
+            .remove_archetype_invariants_by_label("physics")
+            .is_empty());
+
+        world.spawn((A, B, C));
+    }
+
+    #[test]
+    #[should_panic]
+    fn remaining_labeled_archetype_invariants_are_enforced() {
+        let mut world = World::new();
+        world.add_archetype_invariant_labeled(
+            "physics",
+            ArchetypeInvariant::<(A, B)>::full_bundle(),
+        );
+        world.add_archetype_invariant_labeled(
+            "render",
+            ArchetypeInvariant::<(D,), (C,)>::requires(),
+        );
+        world.remove_archetype_invariants_by_label("physics");
+        world.spawn(A);
+
+        world.spawn(D);
+    }
+
+    #[test]
+    fn event_violation_policy() {
+        use crate::{
+            archetype_invariants::{ArchetypeInvariantViolated, ViolationPolicy},
+            event::Events,
+        };
+
+        let mut world = World::new();
+        assert_eq!(world.archetype_invariant_policy(), ViolationPolicy::Panic);
+        world.set_archetype_invariant_policy(ViolationPolicy::Event);
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::forbids());
+        world.spawn(A);
+        assert!(!world.contains_resource::<Events<ArchetypeInvariantViolated>>());
+
+        let entity = world.spawn((A, B)).id();
+        world.spawn((A, B));
+        world.spawn_batch([(A, B, C)]).for_each(drop);
+        assert!(world.get::<B>(entity).is_some());
+
+        let events = world.resource::<Events<ArchetypeInvariantViolated>>();
+        let mut reader = events.get_reader();
+        let violations: Vec<_> = reader.iter(events).map(|event| &event.violation).collect();
+        // Each violating archetype is only reported once
+        assert_eq!(violations.len(), 2);
+        assert_eq!(
+            violations[0].archetype_id,
+            world.entity(entity).location().archetype_id
+        );
+        assert_eq!(world.check_archetype_invariants().len(), 2);
+    }
+
+    #[test]
+    fn warn_violation_policy() {
+        use crate::archetype_invariants::ViolationPolicy;
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Warn);
+        world.spawn((A, B));
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::forbids());
+        world.spawn_empty().insert(A).insert(B);
+        assert_eq!(world.check_archetype_invariants().len(), 1);
+
+        world.set_archetype_invariant_policy(ViolationPolicy::Panic);
+        world.spawn((A, B));
+    }
+
+    #[test]
+    #[should_panic]
+    fn panic_violation_policy() {
+        use crate::archetype_invariants::ViolationPolicy;
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Warn);
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::forbids());
+        world.set_archetype_invariant_policy(ViolationPolicy::Panic);
+
+        world.spawn((A, B, C));
+    }
+
+    /// A [`Subscriber`] that counts the events of a single level logged while it is active.
+    #[cfg(feature = "archetype_invariant_logging")]
+    struct EventCounter(Level, atomic::AtomicUsize);
+
+    #[cfg(feature = "archetype_invariant_logging")]
+    impl EventCounter {
+        fn new(level: Level) -> Self {
+            Self(level, atomic::AtomicUsize::new(0))
+        }
+
+        fn count(&self) -> usize {
+            self.1.load(atomic::Ordering::SeqCst)
+        }
+    }
+
+    #[cfg(feature = "archetype_invariant_logging")]
+    impl Subscriber for EventCounter {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            if *event.metadata().level() == self.0 {
+                self.1.fetch_add(1, atomic::Ordering::SeqCst);
+            }
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    #[cfg(feature = "archetype_invariant_logging")]
+    fn warning_severity() {
+        use crate::archetype_invariants::Severity;
+        use std::sync::Arc;
+
+        let counter = Arc::new(EventCounter::new(Level::WARN));
+        with_default(counter.clone(), || {
+            let mut world = World::new();
+            world.spawn((A, B));
+            world.add_archetype_invariant_with_severity(
+                ArchetypeInvariant::<(A,), (B,)>::forbids(),
+                Severity::Warning,
+            );
+            assert_eq!(counter.count(), 1);
+
+            world.spawn((A, B, C));
+            assert_eq!(counter.count(), 2);
+            // The archetype has already been checked
+            world.spawn((A, B, C));
+            assert_eq!(counter.count(), 2);
+            assert_eq!(world.check_archetype_invariants().len(), 2);
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn error_severity() {
+        use crate::archetype_invariants::Severity;
+
+        let mut world = World::new();
+        world.add_archetype_invariant_with_severity(
+            ArchetypeInvariant::<(A,), (B,)>::forbids(),
+            Severity::Warning,
+        );
+        world.add_archetype_invariant_with_severity(
+            ArchetypeInvariant::<(C,), (D,)>::forbids(),
+            Severity::Error,
+        );
+        world.spawn((A, B));
+
+        world.spawn((C, D));
+    }
+
+    #[test]
+    fn component_count() {
+        let mut world = World::new();
+        let statement =
+            ArchetypeStatement::<(A,)>::component_count(Some(2), Some(3)).into_untyped(&mut world);
+        let [a, b, c, d] = [
+            world.init_component::<A>(),
+            world.init_component::<B>(),
+            world.init_component::<C>(),
+            world.init_component::<D>(),
+        ];
+        assert!(statement.component_ids().is_empty());
+        assert!(!statement.evaluate(&set(&[a])));
+        assert!(statement.evaluate(&set(&[c, d])));
+        assert!(statement.evaluate(&set(&[a, b, c])));
+        assert!(!statement.evaluate(&set(&[a, b, c, d])));
+
+        let unbounded = UntypedArchetypeStatement::ComponentCount {
+            min: None,
+            max: None,
+        };
+        assert!(unbounded.evaluate(&set(&[])));
+        assert_eq!(
+            UntypedArchetypeStatement::ComponentCount {
+                min: Some(17),
+                max: None
+            }
+            .display(&world)
+            .to_string(),
+            "ComponentCount(17..)"
+        );
+        assert_eq!(
+            statement.display(&world).to_string(),
+            "ComponentCount(2..=3)"
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "has components [A, B, C] but invariant ComponentCount(3..) => AllOf(D)"
+    )]
+    fn component_count_predicate() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(D,)> {
+            predicate: ArchetypeStatement::component_count(Some(3), None),
+            consequence: ArchetypeStatement::all_of(),
+        });
+        world.spawn((A, B));
+        world.spawn((A, B, C, D));
+
+        world.spawn((A, B, C));
+    }
+
+    #[test]
+    fn check_world_now() {
+        let mut world = World::new();
+        let valid = world.spawn(A).id();
+        let first = world.spawn((A, B)).id();
+        let second = world.spawn((A, B)).id();
+        let third = world.spawn((A, B, C)).id();
+
+        let violations = ArchetypeInvariant::<(A,), (B,)>::forbids().check_world_now(&mut world);
+        let mut entities: Vec<_> = violations.iter().map(|(entity, _)| *entity).collect();
+        entities.sort();
+        assert_eq!(entities, [first, second, third]);
+        assert!(!entities.contains(&valid));
+        for (entity, violation) in &violations {
+            assert_eq!(
+                violation.archetype_id,
+                world.entity(*entity).location().archetype_id
+            );
+        }
+        // The invariant was not added
+        assert_eq!(world.archetype_invariants().count(), 0);
+    }
+
+    #[test]
+    fn check_world_now_ignores_empty_archetypes() {
+        let mut world = World::new();
+        let entity = world.spawn((A, B)).id();
+        world.entity_mut(entity).remove::<B>();
+        let despawned = world.spawn((A, C)).id();
+        world.despawn(despawned);
+
+        let invariant = ArchetypeInvariant::<(A,), (B, C)>::forbids().into_untyped(&mut world);
+        assert_eq!(
+            world
+                .archetypes()
+                .iter()
+                .filter(|archetype| !invariant.evaluate(&archetype.components().collect()))
+                .count(),
+            2
+        );
+        assert!(invariant.check_world_now(&world).is_empty());
+    }
+
+    #[test]
+    fn violation_entity_sample() {
+        let mut world = World::new();
+        let entities: Vec<_> = world.spawn_batch((0..10).map(|_| (A, B))).collect();
+        world.entity_mut(entities[0]).remove::<B>();
+        world.entity_mut(entities[0]).insert(B);
+
+        let invariant = ArchetypeInvariant::<(A,), (B,)>::forbids().into_untyped(&mut world);
+        let mut archetype_invariants = ArchetypeInvariants::default();
+        archetype_invariants.add(invariant, world.components());
+        let violations =
+            archetype_invariants.check_archetypes(world.archetypes(), world.components());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].entities.len(),
+            ArchetypeInvariantViolation::MAX_SAMPLED_ENTITIES
+        );
+        assert!(violations[0]
+            .entities
+            .iter()
+            .all(|entity| entities.contains(entity)));
+
+        // The archetype of `A` alone is now empty
+        let invariant = ArchetypeInvariant::<(A,), (B,)>::requires().into_untyped(&mut world);
+        let mut archetype_invariants = ArchetypeInvariants::default();
+        archetype_invariants.add(invariant, world.components());
+        let violations =
+            archetype_invariants.check_archetypes(world.archetypes(), world.components());
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].entities.is_empty());
+        assert!(violations[0]
+            .to_string()
+            .contains("There are no entities currently, but the archetype shape is forbidden."));
+    }
+
+    #[test]
+    fn always_and_empty() {
+        let mut world = World::new();
+        let always = ArchetypeStatement::<(A,)>::always().into_untyped(&mut world);
+        let empty = ArchetypeStatement::<(A,)>::empty().into_untyped(&mut world);
+        let a = world.init_component::<A>();
+        let b = world.init_component::<B>();
+
+        assert!(always.evaluate(&set(&[])));
+        assert!(always.evaluate(&set(&[a, b])));
+        assert!(empty.evaluate(&set(&[])));
+        assert!(!empty.evaluate(&set(&[a])));
+        assert!(always.component_ids().is_empty());
+        assert!(empty.component_ids().is_empty());
+        assert_eq!(always.display(&world).to_string(), "Always");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "has components [B] but invariant And(Always, Not(Empty)) => AtLeastOneOf(A, C) was violated"
+    )]
+    fn always_predicate() {
+        let mut world = World::new();
+        // The empty archetype always exists, so it is exempted
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, C)> {
+            predicate: ArchetypeStatement::always().and(ArchetypeStatement::empty().not()),
+            consequence: ArchetypeStatement::at_least_one_of(),
+        });
+        world.spawn((A, B));
+        world.spawn(C);
+
+        world.spawn(B);
+    }
+
+    #[test]
+    fn partition() {
+        let mut world = World::new();
+        let [a, b, c] = [
+            world.init_component::<A>(),
+            world.init_component::<B>(),
+            world.init_component::<C>(),
+        ];
+        let partition = ArchetypeInvariant::<(A, B, C)>::partition(&[&[a], &[b, c]], &mut world);
+        assert_eq!(
+            partition,
+            UntypedArchetypeInvariant::partition([a, b, c], &[&[a], &[b, c]])
+        );
+
+        // Exactly one group is fully present
+        assert!(partition.evaluate(&set(&[])));
+        assert!(partition.evaluate(&set(&[a])));
+        assert!(partition.evaluate(&set(&[b, c])));
+        assert!(partition.evaluate(&set(&[a, b])));
+        // No group is fully present
+        assert!(!partition.evaluate(&set(&[b])));
+        assert!(!partition.evaluate(&set(&[c])));
+        // Several groups are fully present
+        assert!(!partition.evaluate(&set(&[a, b, c])));
+    }
+
+    #[test]
+    #[should_panic(expected = "was violated")]
+    fn partition_with_several_groups_present() {
+        let mut world = World::new();
+        let [a, b] = [world.init_component::<A>(), world.init_component::<B>()];
+        let partition = ArchetypeInvariant::<(A, B)>::partition(&[&[a], &[b]], &mut world);
+        world.add_untyped_archetype_invariant(partition);
+        world.spawn(A);
+        world.spawn(B);
+
+        world.spawn((A, B));
+    }
+
+    #[test]
+    #[cfg(feature = "archetype_invariant_logging")]
+    fn overlapping_partition_warning() {
+        use std::sync::Arc;
+
+        let counter = Arc::new(EventCounter::new(Level::WARN));
+        with_default(counter.clone(), || {
+            let [a, b, c] = [
+                ComponentId::new(0),
+                ComponentId::new(1),
+                ComponentId::new(2),
+            ];
+            UntypedArchetypeInvariant::partition([a, b, c], &[&[a], &[b, c]]);
+            assert_eq!(counter.count(), 0);
+            let overlapping = UntypedArchetypeInvariant::partition([a, b, c], &[&[a, b], &[b, c]]);
+            assert_eq!(counter.count(), 1);
+
+            // The invariant still requires exactly one group to be present
+            assert!(overlapping.evaluate(&set(&[a, b])));
+            assert!(!overlapping.evaluate(&set(&[a, b, c])));
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "archetype_invariant_logging")]
+    fn strict_none_of_warning() {
+        use std::sync::Arc;
+
+        let counter = Arc::new(EventCounter::new(Level::WARN));
+        with_default(counter.clone(), || {
+            let mut world = World::new();
+            ArchetypeStatement::<(A, B)>::none_of().into_untyped(&mut world);
+            assert_eq!(counter.count(), 0);
+
+            world.set_strict_archetype_invariants(true);
+            ArchetypeStatement::<(A,)>::none_of().into_untyped(&mut world);
+            assert_eq!(counter.count(), 0);
+            ArchetypeStatement::<(A, B)>::none_of().into_untyped(&mut world);
+            assert_eq!(counter.count(), 1);
+            ArchetypeStatement::<(A, B)>::all_of()
+                .not()
+                .into_untyped(&mut world);
+            assert_eq!(counter.count(), 1);
+        });
+    }
+
+    #[test]
+    fn implication_closure() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        world.add_archetype_invariant(ArchetypeInvariant::<(B,), (C,)>::requires());
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (D,)>::forbids());
+        let [a, c] = [
+            world.component_id::<A>().unwrap(),
+            world.component_id::<C>().unwrap(),
+        ];
+
+        let closure = world.compute_archetype_invariant_implication_closure(false);
+        assert_eq!(
+            closure.derived,
+            vec![UntypedArchetypeInvariant {
+                predicate: UntypedArchetypeStatement::AllOf(set(&[a])),
+                consequence: UntypedArchetypeStatement::AllOf(set(&[c])),
+                severity: Severity::Error,
+            }]
+        );
+        assert!(closure.cycles.is_empty());
+        assert_eq!(world.archetype_invariants().count(), 3);
+
+        let line = line!() + 1;
+        world.compute_archetype_invariant_implication_closure(true);
+        assert_eq!(world.archetype_invariants().count(), 4);
+        let derived_id = world.archetype_invariants.raw_list.last().unwrap().id;
+        let location = world.archetype_invariant_location(derived_id).unwrap();
+        assert_eq!((location.file(), location.line()), (file!(), line));
+        let closure = world.compute_archetype_invariant_implication_closure(true);
+        assert!(closure.derived.is_empty());
+        assert_eq!(world.archetype_invariants().count(), 4);
+
+        let cycle_start =
+            world.add_archetype_invariant(ArchetypeInvariant::<(C,), (A,)>::requires());
+        let closure = world.compute_archetype_invariant_implication_closure(false);
+        // `C` now requires `B` through `A`, and `B` requires `A` through `C`
+        assert_eq!(closure.derived.len(), 2);
+        assert!(closure
+            .derived
+            .contains(&ArchetypeInvariant::<(B,), (A,)>::requires().into_untyped(&mut world)));
+        assert_eq!(closure.cycles.len(), 4);
+        assert!(closure.cycles.contains(&cycle_start));
+    }
+
+    #[test]
+    fn skip_empty_archetypes() {
+        let mut world = World::new();
+        let entity = world.spawn((A, B)).id();
+        world.entity_mut(entity).remove::<B>();
+
+        world.set_check_empty_archetypes(false);
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::forbids());
+        assert!(world.check_archetype_invariants().is_empty());
+        world.spawn(A);
+
+        world.set_check_empty_archetypes(true);
+        assert_eq!(world.check_archetype_invariants().len(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn skipped_empty_archetypes_are_checked_once_populated() {
+        let mut world = World::new();
+        let entity = world.spawn((A, B)).id();
+        world.entity_mut(entity).remove::<B>();
+        world.set_check_empty_archetypes(false);
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::forbids());
+
+        world.spawn((A, B));
+    }
+
+    #[test]
+    fn populated_archetypes_are_checked_once_against_new_invariants() {
+        use crate::{
+            archetype_invariants::{ArchetypeInvariantViolated, ViolationPolicy},
+            event::Events,
+        };
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Event);
+        world.set_automatic_archetype_invariant_checks(false);
+        world.set_check_empty_archetypes(false);
+        let entity = world.spawn((A, C)).id();
+        world.entity_mut(entity).remove::<C>();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (C,)>::forbids());
+        world.check_pending_archetype_invariants();
+        assert!(!world.contains_resource::<Events<ArchetypeInvariantViolated>>());
+
+        // The populated archetype is checked against every invariant, including the one added along with it
+        world.spawn((A, C));
+        world.add_archetype_invariant(ArchetypeInvariant::<(C,), (D,)>::requires());
+        world.check_pending_archetype_invariants();
+        let violations: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .collect();
+        assert_eq!(violations.len(), 2);
+        assert_ne!(
+            violations[0].violation.invariant,
+            violations[1].violation.invariant
+        );
+        assert_eq!(world.check_archetype_invariants().len(), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_archetypes_are_checked_by_default() {
+        let mut world = World::new();
+        let entity = world.spawn((A, B)).id();
+        world.entity_mut(entity).remove::<B>();
+
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::forbids());
+    }
+
+    #[test]
+    fn violations_for_entity() {
+        use crate::archetype_invariants::ViolationPolicy;
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Warn);
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (D,)>::requires());
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::forbids());
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (C,)>::forbids());
+        let disabled = world.add_archetype_invariant(ArchetypeInvariant::<(A,), (D,)>::forbids());
+        world.set_archetype_invariant_enabled(disabled, false);
+        let valid = world.spawn((A, D)).id();
+        let entity = world.spawn((A, B, C, D)).id();
+
+        assert!(world
+            .archetype_invariant_violations_for_entity(valid)
+            .is_empty());
+        let violations = world.archetype_invariant_violations_for_entity(entity);
+        // Disabled invariants are not reported
+        let expected: Vec<_> = world.archetype_invariants().skip(1).take(2).collect();
+        assert_eq!(violations, expected[..]);
+        assert_eq!(violations.len(), 2);
+
+        // Component groups are resolved to their members, as in the automatic checks
+        let b = world.init_component::<B>();
+        let group = world.register_component_group("group", &[b]);
+        let a = world.init_component::<A>();
+        world.add_untyped_archetype_invariant(UntypedArchetypeInvariant {
+            predicate: UntypedArchetypeStatement::all_of_ids([a]),
+            consequence: UntypedArchetypeStatement::GroupAtLeastOneOf(group),
+            severity: Severity::Error,
+        });
+        assert_eq!(
+            world
+                .archetype_invariant_violations_for_entity(entity)
+                .len(),
+            2
+        );
+        assert_eq!(
+            world.archetype_invariant_violations_for_entity(valid),
+            [&UntypedArchetypeInvariant {
+                predicate: UntypedArchetypeStatement::all_of_ids([a]),
+                consequence: UntypedArchetypeStatement::at_least_one_of_ids([b]),
+                severity: Severity::Error,
+            }]
+        );
+
+        world.despawn(entity);
+        assert!(world
+            .archetype_invariant_violations_for_entity(entity)
+            .is_empty());
+    }
+
+    #[test]
+    fn entities_matching_statement() {
+        let mut world = World::new();
+        let a = world.spawn(A).id();
+        let ab = world.spawn((A, B)).id();
+        let b = world.spawn(B).id();
+        let c = world.spawn_empty().insert(C).id();
+        let abc = world.spawn((A, B, C)).id();
+        world.spawn_empty();
+
+        let mut matching = |statement: ArchetypeStatement<(A, B)>| {
+            let statement = statement.into_untyped(&mut world);
+            let mut entities: Vec<_> = world.entities_matching_statement(&statement).collect();
+            entities.sort();
+            entities
+        };
+        assert_eq!(
+            matching(ArchetypeStatement::at_least_one_of()),
+            [a, ab, b, abc]
+        );
+        assert_eq!(matching(ArchetypeStatement::exactly_one_of()), [a, b]);
+        assert_eq!(matching(ArchetypeStatement::all_of()), [ab, abc]);
+        assert_eq!(
+            matching(ArchetypeStatement::none_of().and(ArchetypeStatement::empty().not())),
+            [c]
+        );
+    }
+
+    #[derive(Component)]
+    #[component(requires(A, B), forbids(C))]
+    #[component(forbids(D))]
+    struct WithInvariants;
+
+    #[test]
+    fn component_attribute_invariants() {
+        let mut world = World::new();
+        world.register_component_invariants::<A>();
+        assert_eq!(world.archetype_invariants().count(), 0);
+
+        world.register_component_invariants::<WithInvariants>();
+        let expected = [
+            ArchetypeInvariant::<(WithInvariants,), (A,)>::requires().into_untyped(&mut world),
+            ArchetypeInvariant::<(WithInvariants,), (B,)>::requires().into_untyped(&mut world),
+            ArchetypeInvariant::<(WithInvariants,), (C,)>::forbids().into_untyped(&mut world),
+            ArchetypeInvariant::<(WithInvariants,), (D,)>::forbids().into_untyped(&mut world),
+        ];
+        assert!(world.archetype_invariants().eq(expected.iter()));
+        world.spawn((WithInvariants, A, B));
+    }
+
+    #[test]
+    #[should_panic]
+    fn component_attribute_requires() {
+        let mut world = World::new();
+        world.register_component_invariants::<WithInvariants>();
+
+        world.spawn((WithInvariants, A));
+    }
+
+    #[test]
+    fn id_constructors() {
+        let [a, b, c] = [0, 1, 2].map(ComponentId::new);
+        assert_eq!(
+            UntypedArchetypeStatement::all_of_ids([b, a, b]),
+            UntypedArchetypeStatement::AllOf(set(&[a, b]))
+        );
+        assert_eq!(
+            UntypedArchetypeStatement::at_least_one_of_ids([c]),
+            UntypedArchetypeStatement::AtLeastOneOf(set(&[c]))
+        );
+        assert_eq!(
+            UntypedArchetypeStatement::none_of_ids([]),
+            UntypedArchetypeStatement::NoneOf(set(&[]))
+        );
+        assert_eq!(
+            UntypedArchetypeStatement::exactly_one_of_ids(vec![a, c]),
+            UntypedArchetypeStatement::ExactlyOneOf(set(&[a, c]))
+        );
+        assert_eq!(
+            UntypedArchetypeStatement::at_most_one_of_ids([a, b]),
+            UntypedArchetypeStatement::AtMostOneOf(set(&[a, b]))
+        );
+        assert_eq!(
+            UntypedArchetypeStatement::exactly_n_of_ids([a, b, c], 2),
+            UntypedArchetypeStatement::ExactlyNOf(set(&[a, b, c]), 2)
+        );
+        assert_eq!(
+            UntypedArchetypeStatement::at_least_n_of_ids([a, b, c], 3),
+            UntypedArchetypeStatement::AtLeastNOf(set(&[a, b, c]), 3)
+        );
+        assert_eq!(
+            UntypedArchetypeStatement::at_most_n_of_ids([a], 2),
+            UntypedArchetypeStatement::AtMostNOf(set(&[a]), 2)
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "requires at least one component")]
+    fn empty_at_least_one_of_ids() {
+        UntypedArchetypeStatement::at_least_one_of_ids([]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "requires 3 components, but only 2 were given")]
+    fn too_few_exactly_n_of_ids() {
+        let ids = [0, 1].map(ComponentId::new);
+        UntypedArchetypeStatement::exactly_n_of_ids(ids, 3);
+    }
+
+    #[test]
+    fn add_archetype_invariant_by_name() {
+        use crate::archetype_invariants::{ArchetypeStatementKind, UnknownComponentNames};
+        use std::any::type_name;
+
+        let mut world = World::new();
+        let a = world.init_component::<A>();
+        let b = world.init_component::<B>();
+        world
+            .add_archetype_invariant_by_name(
+                (ArchetypeStatementKind::AllOf, &[type_name::<A>()]),
+                (ArchetypeStatementKind::NoneOf, &[type_name::<B>()]),
+            )
+            .unwrap();
+        assert_eq!(
+            world.archetype_invariants().next().unwrap(),
+            &UntypedArchetypeInvariant {
+                predicate: UntypedArchetypeStatement::AllOf(set(&[a])),
+                consequence: UntypedArchetypeStatement::NoneOf(set(&[b])),
+                severity: Severity::Error,
+            }
+        );
+
+        let error = world
+            .add_archetype_invariant_by_name(
+                (
+                    ArchetypeStatementKind::AtLeastNOf(1),
+                    &["Unknown", type_name::<A>()],
+                ),
+                (
+                    ArchetypeStatementKind::AllOf,
+                    &[type_name::<C>(), type_name::<B>()],
+                ),
+            )
+            .unwrap_err();
+        assert_eq!(
+            error,
+            UnknownComponentNames {
+                names: vec!["Unknown".to_string(), type_name::<C>().to_string()]
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "no component is registered with the name `Unknown`, `{}`",
+                type_name::<C>()
+            )
+        );
+        assert_eq!(world.archetype_invariants().count(), 1);
+    }
+
+    #[test]
+    fn handler_fixes_violations() {
+        let mut world = World::new();
+        world.add_archetype_invariant_with_handler(
+            ArchetypeInvariant::<(A,), (B,)>::forbids(),
+            Box::new(|world, entity, _| {
+                world.entity_mut(entity).remove::<B>();
+            }),
+        );
+
+        let mut entity = world.spawn((A, C));
+        entity.insert(B);
+        // The handler is left pending while the `EntityMut` may still refer to the entity
+        assert!(entity.contains::<B>());
+        let entity = entity.id();
+        world.run_pending_archetype_invariant_handlers();
+        let entity = world.entity(entity);
+        assert!(entity.contains::<A>());
+        assert!(!entity.contains::<B>());
+        assert!(entity.contains::<C>());
+    }
+
+    #[test]
+    fn handler_despawns_entity() {
+        use crate::system::{CommandQueue, Commands};
+        #[derive(Component)]
+        struct E;
+        #[derive(Component)]
+        struct F;
+
+        let mut world = World::new();
+        world.add_archetype_invariant_with_handler(
+            ArchetypeInvariant::<(A,), (B,)>::forbids(),
+            Box::new(|world, entity, _| {
+                world.despawn(entity);
+            }),
+        );
+
+        // Neither spawning, inserting nor removing panics, since the handlers are left pending
+        let spawned = world.spawn((A, B)).id();
+        let inserted = world.spawn((A, D)).insert(B).id();
+        let mut entity = world.spawn((A, C));
+        entity.insert(B).remove::<C>();
+        let removed = entity.id();
+        assert!(world.get_entity(removed).is_some());
+        world.run_pending_archetype_invariant_handlers();
+        for entity in [spawned, inserted, removed] {
+            assert!(world.get_entity(entity).is_none());
+        }
+
+        // The next spawn runs the pending handlers first
+        let pending = world.spawn((A, B, C, D)).id();
+        let valid = world.spawn(A).id();
+        assert!(world.get_entity(pending).is_none());
+        // A pending handler is skipped if its entity is despawned first
+        world.spawn((A, B, E)).despawn();
+        world.run_pending_archetype_invariant_handlers();
+
+        // Handlers for entities spawned through commands run once the commands are applied
+        let mut queue = CommandQueue::default();
+        let commanded = Commands::new(&mut queue, &world).spawn((A, B, F)).id();
+        queue.apply(&mut world);
+        assert!(world.get_entity(commanded).is_none());
+        assert_eq!(world.entities().len(), 1);
+        assert!(world.get_entity(valid).is_some());
+
+        // Violations caused by a handler through an `EntityMut` are handled right after it returns
+        world.add_archetype_invariant_with_handler(
+            ArchetypeInvariant::<(C,), (D,)>::forbids(),
+            Box::new(|world, entity, _| {
+                world.entity_mut(entity).insert(B);
+            }),
+        );
+        let entity = world.spawn((A, C, D, E, F)).id();
+        world.run_pending_archetype_invariant_handlers();
+        assert!(world.get_entity(entity).is_none());
+    }
+
+    #[test]
+    fn handler_runs_for_spawn_batch() {
+        let mut world = World::new();
+        world.add_archetype_invariant_with_handler(
+            ArchetypeInvariant::<(A,), (B,)>::requires(),
+            Box::new(|world, entity, _| {
+                world.entity_mut(entity).insert(B);
+            }),
+        );
+
+        // The batch's archetype is checked once its entities have been spawned
+        let entities: Vec<_> = world.spawn_batch((0..3).map(|_| (A, C))).collect();
+        for entity in entities {
+            assert!(world.entity(entity).contains::<B>());
+        }
+
+        // Dropping the iterator early spawns the remaining entities before checking them
+        let first = world.spawn_batch((0..3).map(|_| (A, D))).next().unwrap();
+        assert!(world.entity(first).contains::<B>());
+        assert_eq!(world.query::<(&A, &D, &B)>().iter(&world).count(), 3);
+    }
+
+    #[test]
+    fn handler_runs_for_existing_entities() {
+        let mut world = World::new();
+        let violating = world.spawn((A, B)).id();
+        let other = world.spawn((A, B, C)).id();
+        let valid = world.spawn(A).id();
+
+        world.add_archetype_invariant_with_handler(
+            ArchetypeInvariant::<(A,), (B,)>::forbids(),
+            Box::new(|world, entity, _| {
+                world.despawn(entity);
+            }),
+        );
+        assert!(world.get_entity(violating).is_none());
+        assert!(world.get_entity(other).is_none());
+        assert!(world.get_entity(valid).is_some());
+    }
+
+    #[test]
+    fn recursive_handlers_are_limited() {
+        // Every time this runs, it adds another invariant that the entity violates, calling itself again
+        fn re_add(world: &mut World, _: Entity, _: &UntypedArchetypeInvariant) {
+            world.add_archetype_invariant_with_handler(
+                ArchetypeInvariant::<(A,), (B,)>::requires(),
+                Box::new(re_add),
+            );
+        }
+
+        let mut world = World::new();
+        world.spawn(A);
+        world.add_archetype_invariant_with_handler(
+            ArchetypeInvariant::<(A,), (B,)>::requires(),
+            Box::new(re_add),
+        );
+        // The first invariant, plus one more for each level of nested handlers
+        assert_eq!(
+            world.archetype_invariants().count(),
+            ArchetypeInvariants::MAX_HANDLER_DEPTH + 1
+        );
+    }
+
+    #[test]
+    fn stats_count_incremental_checks() {
+        let mut world = World::new();
+        world.spawn(A);
+        world.spawn((A, B));
+        let invariants = vec![
+            ArchetypeInvariant::<(A,), (C,)>::forbids().into_untyped(&mut world),
+            ArchetypeInvariant::<(B,), (A,)>::requires().into_untyped(&mut world),
+        ];
+        world.add_archetype_invariants(invariants);
+        // The empty archetype and the two spawned archetypes, checked against both invariants
+        let stats = world.archetype_invariant_stats();
+        assert_eq!(stats.archetype_checks, 3);
+        assert_eq!(stats.invariant_evaluations, 6);
+        // Only the predicates that hold have their consequences evaluated: `A` twice and `B` once
+        assert_eq!(stats.statement_evaluations, 9);
+
+        // A new archetype is checked once against all invariants
+        world.spawn((A, B, D));
+        let stats = world.archetype_invariant_stats();
+        assert_eq!(stats.archetype_checks, 4);
+        assert_eq!(stats.invariant_evaluations, 8);
+
+        // A new invariant is checked against each existing archetype once
+        world.add_archetype_invariant(ArchetypeInvariant::<(D,), (C,)>::forbids());
+        let stats = world.archetype_invariant_stats();
+        assert_eq!(stats.archetype_checks, 8);
+        assert_eq!(stats.invariant_evaluations, 12);
+
+        // Checking explicitly is not counted
+        world.check_archetype_invariants();
+        assert_eq!(world.archetype_invariant_stats(), stats);
+    }
+
+    #[test]
+    fn negated_invariants_never_both_hold_when_predicate_holds() {
+        let mut world = World::new();
+        let invariants = vec![
+            ArchetypeInvariant::<(A,), (B,)>::requires().into_untyped(&mut world),
+            ArchetypeInvariant::<(A, B), (C,)>::forbids().into_untyped(&mut world),
+            ArchetypeInvariant::<(B, C, D)>::mutually_exclusive().into_untyped(&mut world),
+            ArchetypeInvariant::when(ArchetypeStatement::<(A, D)>::at_least_one_of())
+                .then(ArchetypeStatement::<(B, C)>::exactly_one_of().not())
+                .into_untyped(&mut world),
+        ];
+        let ids: Vec<ComponentId> = world.components().iter().map(|info| info.id()).collect();
+        assert_eq!(ids.len(), 4);
+
+        for invariant in invariants {
+            let negated = invariant.clone().negate();
+            assert_eq!(negated.clone().negate(), invariant);
+
+            // Every possible archetype of the four components
+            for mask in 0..1 << ids.len() {
+                let archetype: ComponentIdSet = (0..ids.len())
+                    .filter(|i| mask & (1 << i) != 0)
+                    .map(|i| ids[i])
+                    .collect();
+                if invariant.predicate.evaluate(&archetype) {
+                    assert_ne!(invariant.evaluate(&archetype), negated.evaluate(&archetype));
+                } else {
+                    assert!(invariant.evaluate(&archetype) && negated.evaluate(&archetype));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn negate_typed_invariant() {
+        let mut world = World::new();
+        let negated = ArchetypeInvariant::<(A,), (B,)>::requires()
+            .negate()
+            .into_untyped(&mut world);
+        let a = world.init_component::<A>();
+        let b = world.init_component::<B>();
+        assert_eq!(
+            negated.predicate,
+            UntypedArchetypeStatement::AllOf(set(&[a]))
+        );
+        assert_eq!(
+            negated.consequence,
+            UntypedArchetypeStatement::Not(Box::new(UntypedArchetypeStatement::AllOf(set(&[b]))))
+        );
+    }
+
+    #[test]
+    fn collect_archetype_invariants() {
+        let mut world = World::new();
+        world.spawn((A, B));
+        world.spawn(C);
+        let requires = ArchetypeInvariant::<(A,), (B,)>::requires().into_untyped(&mut world);
+        let forbids = ArchetypeInvariant::<(C,), (A,)>::forbids().into_untyped(&mut world);
+
+        let mut invariants: ArchetypeInvariants =
+            [requires.clone(), requires.clone()].into_iter().collect();
+        // The duplicate is ignored, just like with `add`
+        assert_eq!(invariants.len(), 1);
+        invariants.extend([forbids.clone()]);
+        assert_eq!(
+            invariants.iter().cloned().collect::<Vec<_>>(),
+            vec![requires, forbids]
+        );
+
+        // The empty archetype, `(A, B)` and `C`, each against both invariants
+        assert_eq!(
+            invariants.check_new_archetypes(&world.archetypes, &world.components),
+            6
+        );
+    }
+
+    #[test]
+    fn subset_of() {
+        let mut world = World::new();
+        let statement =
+            ArchetypeStatement::<(A, B)>::subset_of::<(A, B, C)>().into_untyped(&mut world);
+        let a = world.init_component::<A>();
+        let b = world.init_component::<B>();
+        let c = world.init_component::<C>();
+        let d = world.init_component::<D>();
+        assert_eq!(
+            statement,
+            UntypedArchetypeStatement::subset_of_ids([a, b, c], [a, b])
+        );
+        assert_eq!(statement.component_ids(), set(&[a, b, c]));
+
+        assert!(statement.evaluate(&set(&[])));
+        assert!(statement.evaluate(&set(&[a])));
+        assert!(statement.evaluate(&set(&[a, b])));
+        // Components that are not relevant are ignored
+        assert!(statement.evaluate(&set(&[a, d])));
+        assert!(!statement.evaluate(&set(&[c])));
+        assert!(!statement.evaluate(&set(&[a, b, c])));
+
+        assert_eq!(
+            statement.display(&world).to_string(),
+            "SubsetOf([A, B, C], [A, B])"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "archetype_invariant_logging")]
+    fn violations_emit_debug_events() {
+        use crate::archetype_invariants::ViolationPolicy;
+        use std::sync::Arc;
+
+        let counter = Arc::new(EventCounter::new(Level::DEBUG));
+        with_default(counter.clone(), || {
+            let mut world = World::new();
+            world.set_archetype_invariant_policy(ViolationPolicy::Warn);
+            world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::forbids());
+            world.add_archetype_invariant(ArchetypeInvariant::<(A,), (C,)>::requires());
+            assert_eq!(counter.count(), 0);
+
+            // One event for each violated invariant
+            world.spawn((A, B));
+            assert_eq!(counter.count(), 2);
+            world.spawn((A, C));
+            assert_eq!(counter.count(), 2);
+        });
+    }
+
+    #[test]
+    fn exactly() {
+        let mut world = World::new();
+        let statement = ArchetypeStatement::<(A, B)>::exactly().into_untyped(&mut world);
+        let a = world.init_component::<A>();
+        let b = world.init_component::<B>();
+        let c = world.init_component::<C>();
+        assert_eq!(statement, UntypedArchetypeStatement::exactly_ids([b, a]));
+
+        // Exact
+        assert!(statement.evaluate(&set(&[a, b])));
+        // Missing
+        assert!(!statement.evaluate(&set(&[])));
+        assert!(!statement.evaluate(&set(&[a])));
+        // Extra
+        assert!(!statement.evaluate(&set(&[a, b, c])));
+        assert!(!statement.evaluate(&set(&[a, c])));
+
+        assert_eq!(statement.display(&world).to_string(), "Exactly(A, B)");
+    }
+
+    #[test]
+    #[should_panic]
+    fn exactly_forbids_extra_components() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (A, B)> {
+            predicate: ArchetypeStatement::all_of(),
+            consequence: ArchetypeStatement::exactly(),
+        });
+        world.spawn((A, B));
+        world.spawn((A, B, C));
+    }
+
+    #[derive(Resource)]
+    struct R;
+
+    #[test]
+    fn resource_present() {
+        let mut world = World::new();
+        let statement =
+            ArchetypeStatement::<(A,)>::resource_present::<R>().into_untyped(&mut world);
+        let r = world.initialize_resource::<R>();
+        let a = world.init_component::<A>();
+
+        assert!(statement.uses_resources());
+        assert!(statement.component_ids().is_empty());
+        assert!(!statement.evaluate_with_resources(&set(&[a]), &set(&[])));
+        assert!(statement.evaluate_with_resources(&set(&[]), &set(&[r])));
+        // Components with the same id as the resource do not count
+        assert!(!statement.evaluate(&set(&[r])));
+        assert_eq!(statement.display(&world).to_string(), "ResourcePresent(R)");
+    }
+
+    #[test]
+    #[should_panic]
+    fn inserting_a_resource_rechecks_invariants() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (A,)> {
+            predicate: ArchetypeStatement::resource_present::<R>(),
+            consequence: ArchetypeStatement::none_of(),
+        });
+        world.spawn(A);
+        world.insert_resource(R);
+    }
+
+    #[test]
+    #[should_panic]
+    fn resource_scope_rechecks_invariants() {
+        let mut world = World::new();
+        world.insert_resource(R);
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)> {
+            predicate: ArchetypeStatement::resource_present::<R>()
+                .and(ArchetypeStatement::all_of()),
+            consequence: ArchetypeStatement::all_of(),
+        });
+        world.resource_scope(|world, _: crate::world::Mut<R>| {
+            world.spawn(A);
+        });
+    }
+
+    #[test]
+    fn removing_a_resource_rechecks_invariants() {
+        let mut world = World::new();
+        world.insert_resource(R);
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)> {
+            predicate: ArchetypeStatement::resource_present::<R>().not(),
+            consequence: ArchetypeStatement::none_of(),
+        });
+        world.spawn(A);
+        let checks = world.archetype_invariant_stats().archetype_checks;
+
+        world.remove_resource::<R>();
+        // The empty archetype and the archetype of `A` are checked again now that the predicate holds
+        assert_eq!(
+            world.archetype_invariant_stats().archetype_checks,
+            checks + 2
+        );
+    }
+
+    #[test]
+    fn assert_archetype_invariants_passes() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::forbids());
+        world.spawn(A);
+        world.spawn(B);
+        world.assert_archetype_invariants();
+    }
+
+    #[test]
+    fn assert_archetype_invariants_lists_every_violation() {
+        use crate::archetype_invariants::ViolationPolicy;
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Warn);
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::forbids());
+        world.add_archetype_invariant_with_severity(
+            ArchetypeInvariant::<(C,), (D,)>::requires(),
+            Severity::Warning,
+        );
+        world.spawn((A, B));
+        world.spawn(C);
+
+        let message = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            world.assert_archetype_invariants();
+        }))
+        .unwrap_err()
+        .downcast::<String>()
+        .unwrap();
+        assert!(message.starts_with("2 archetype invariant violation(s) found:"));
+        assert_eq!(message.lines().count(), 3);
+    }
+
+    #[test]
+    fn require_at_least_one() {
+        let mut world = World::new();
+        world.set_check_empty_archetypes(false);
+        world.add_archetype_invariant(ArchetypeInvariant::<(A, B)>::require_at_least_one());
+        world.spawn(A);
+        let bc = world.spawn((B, C)).id();
+        assert!(world.check_archetype_invariants().is_empty());
+
+        let violations =
+            ArchetypeInvariant::<(A,)>::require_at_least_one().check_world_now(&mut world);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].0, bc);
+    }
+
+    #[test]
+    fn hash_set_deduplicates_invariants() {
+        let mut typed = HashSet::new();
+        assert!(typed.insert(ArchetypeInvariant::<(A,), (B,)>::requires()));
+        assert!(!typed.insert(ArchetypeInvariant::<(A,), (B,)>::requires()));
+        assert!(typed.insert(ArchetypeInvariant::<(A,), (B,)>::forbids()));
+        assert!(typed.insert(ArchetypeInvariant {
+            predicate: ArchetypeStatement::resource_present::<R>(),
+            consequence: ArchetypeStatement::at_least_n_of(1),
+        }));
+        assert!(!typed.insert(ArchetypeInvariant {
+            predicate: ArchetypeStatement::resource_present::<R>(),
+            consequence: ArchetypeStatement::at_least_n_of(1),
+        }));
+        assert!(typed.insert(ArchetypeInvariant {
+            predicate: ArchetypeStatement::resource_present::<R>(),
+            consequence: ArchetypeStatement::at_least_n_of(2),
+        }));
+        assert_eq!(typed.len(), 4);
+
+        let mut world = World::new();
+        let untyped: HashSet<UntypedArchetypeInvariant> = typed
+            .into_iter()
+            .chain([ArchetypeInvariant::<(A,), (B,)>::requires()])
+            .map(|invariant| invariant.into_untyped(&mut world))
+            .collect();
+        assert_eq!(untyped.len(), 4);
+    }
+
+    #[test]
+    fn map_component_ids() {
+        let [a, b, c, d] = [0, 1, 2, 3].map(ComponentId::new);
+        let statement = UntypedArchetypeStatement::And(vec![
+            UntypedArchetypeStatement::all_of_ids([a, b]),
+            UntypedArchetypeStatement::Or(vec![
+                UntypedArchetypeStatement::Not(Box::new(UntypedArchetypeStatement::none_of_ids([
+                    c,
+                ]))),
+                UntypedArchetypeStatement::SubsetOf {
+                    relevant: set(&[a, c]),
+                    allowed: set(&[a]),
+                },
+                UntypedArchetypeStatement::ResourcePresent(d),
+                UntypedArchetypeStatement::Always,
+            ]),
+        ]);
+
+        let mut visited = Vec::new();
+        statement.for_each_component_id(|id| visited.push(id));
+        assert_eq!(visited, [a, b, c, a, c, a, d]);
+        assert_eq!(statement.component_ids(), set(&[a, b, c]));
+
+        // Reverse the order of the ids
+        let remapped = statement.map_component_ids(|id| ComponentId::new(3 - id.index()));
+        let expected = UntypedArchetypeStatement::And(vec![
+            UntypedArchetypeStatement::all_of_ids([d, c]),
+            UntypedArchetypeStatement::Or(vec![
+                UntypedArchetypeStatement::Not(Box::new(UntypedArchetypeStatement::none_of_ids([
+                    b,
+                ]))),
+                UntypedArchetypeStatement::SubsetOf {
+                    relevant: set(&[d, b]),
+                    allowed: set(&[d]),
+                },
+                UntypedArchetypeStatement::ResourcePresent(a),
+                UntypedArchetypeStatement::Always,
+            ]),
+        ]);
+        assert_eq!(remapped, expected);
+    }
+
+    #[test]
+    fn remap() {
+        let mut old_world = World::new();
+        let invariant = ArchetypeInvariant::<(A, B), (C,)> {
+            predicate: ArchetypeStatement::resource_present::<R>()
+                .and(ArchetypeStatement::at_least_one_of()),
+            consequence: ArchetypeStatement::subset_of::<(A, B)>().not(),
+        }
+        .into_untyped(&mut old_world);
+
+        let mut new_world = World::new();
+        new_world.init_component::<C>();
+        new_world.init_component::<B>();
+        new_world.init_component::<A>();
+        // The resource is still missing
+        assert_eq!(invariant.remap(&old_world, &new_world), None);
+
+        new_world.insert_resource(R);
+        let expected = ArchetypeInvariant::<(A, B), (C,)> {
+            predicate: ArchetypeStatement::resource_present::<R>()
+                .and(ArchetypeStatement::at_least_one_of()),
+            consequence: ArchetypeStatement::subset_of::<(A, B)>().not(),
+        }
+        .into_untyped(&mut new_world);
+        assert_ne!(invariant, expected);
+        assert_eq!(invariant.remap(&old_world, &new_world), Some(expected));
+    }
+
+    #[test]
+    fn archetype_invariant_macro() {
+        let mut world = World::new();
+        let expected = ArchetypeInvariant::<(A, B), (C,)> {
+            predicate: ArchetypeStatement::all_of(),
+            consequence: ArchetypeStatement::none_of(),
+        };
+        assert!(archetype_invariant!(all_of(A, B) => none_of(C)) == expected);
+
+        let expected = ArchetypeInvariant::<(A,), (B, C)> {
+            predicate: ArchetypeStatement::at_least_one_of(),
+            consequence: ArchetypeStatement::all_of(),
+        };
+        assert!(archetype_invariant!(at_least_one_of(A,) => all_of(B, C,)) == expected);
+
+        world.add_archetype_invariant(archetype_invariant!(all_of(A) => none_of(B)));
+        world.spawn(A);
+        assert!(world.check_archetype_invariants().is_empty());
+    }
+
+    #[test]
+    fn deferred_invariants_skip_transient_archetypes() {
+        let mut world = World::new();
+        world.add_deferred_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        world.spawn(A).insert(B);
+        world.spawn(B);
+        world.check_deferred_archetype_invariants();
+        // The read-only check also covers the archetype of only `A`, which has no entities
+        assert_eq!(world.check_archetype_invariants().len(), 1);
+    }
+
+    #[test]
+    fn deferred_invariants_are_checked_on_demand() {
+        use crate::{
+            archetype_invariants::{ArchetypeInvariantViolated, ViolationPolicy},
+            event::Events,
+        };
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Event);
+        world.add_deferred_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        let a = world.spawn(A).id();
+        assert!(!world.contains_resource::<Events<ArchetypeInvariantViolated>>());
+
+        world.check_deferred_archetype_invariants();
+        let violations: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .collect();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].violation.entities, [a]);
+
+        // Checking again reports the violation again
+        world.check_deferred_archetype_invariants();
+        assert_eq!(
+            world.resource::<Events<ArchetypeInvariantViolated>>().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn matching_archetypes() {
+        let mut world = World::new();
+        let invariant = UntypedArchetypeInvariant {
+            predicate: ArchetypeStatement::<(A, B)>::at_least_one_of().into_untyped(&mut world),
+            consequence: ArchetypeStatement::<(C,)>::all_of().into_untyped(&mut world),
+            severity: Severity::Error,
+        };
+        let a = world.spawn(A).id();
+        let abc = world.spawn((A, B, C)).id();
+        world.spawn(C);
+        world.spawn((C, D));
+        let b = world.spawn(B).id();
+
+        let expected: Vec<_> = [a, abc, b]
+            .map(|entity| world.entities().get(entity).unwrap().archetype_id)
+            .into();
+        assert_eq!(invariant.matching_archetypes(&world), expected);
+    }
+
+    #[test]
+    fn combine_all() {
+        let mut world = World::new();
+        let consequences = || {
+            vec![
+                ArchetypeStatement::<(B, C)>::at_least_one_of(),
+                ArchetypeStatement::at_most_one_of(),
+            ]
+        };
+        let combined = ArchetypeInvariant::<(A,), (B, C)>::combine_all(
+            ArchetypeStatement::all_of(),
+            consequences(),
+        )
+        .into_untyped(&mut world);
+        let separate: Vec<_> = consequences()
+            .into_iter()
+            .map(|consequence| {
+                ArchetypeInvariant::<(A,), (B, C)> {
+                    predicate: ArchetypeStatement::all_of(),
+                    consequence,
+                }
+                .into_untyped(&mut world)
+            })
+            .collect();
+
+        let ids = [
+            world.init_component::<A>(),
+            world.init_component::<B>(),
+            world.init_component::<C>(),
+        ];
+        for mask in 0..1 << ids.len() {
+            let archetype: ComponentIdSet = (0..ids.len())
+                .filter(|index| mask & (1 << index) != 0)
+                .map(|index| ids[index])
+                .collect();
+            assert_eq!(
+                combined.evaluate(&archetype),
+                separate
+                    .iter()
+                    .all(|invariant| invariant.evaluate(&archetype)),
+            );
+        }
+    }
+
+    #[test]
+    fn try_spawn() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        world.add_archetype_invariant_with_severity(
+            ArchetypeInvariant::<(C,), (D,)>::requires(),
+            Severity::Warning,
+        );
+
+        let ab = world.try_spawn((A, B)).unwrap();
+        assert!(world.get::<B>(ab).is_some());
+        // Only violates an invariant with `Severity::Warning`
+        world.try_spawn(C).unwrap();
+
+        let archetypes = world.archetypes().len();
+        let violation = world.try_spawn((A, C)).unwrap_err();
+        assert_eq!(
+            violation.archetype_id,
+            crate::archetype::ArchetypeId::INVALID
+        );
+        assert!(violation.entities.is_empty());
+        assert_eq!(violation.component_ids.len(), 2);
+        assert_eq!(world.entities().len(), 2);
+        assert_eq!(world.archetypes().len(), archetypes);
+
+        // The archetype already exists
+        world.set_archetype_invariant_policy(crate::archetype_invariants::ViolationPolicy::Warn);
+        let a = world.spawn(A).id();
+        let violation = world.try_spawn(A).unwrap_err();
+        assert_eq!(
+            violation.archetype_id,
+            world.entities().get(a).unwrap().archetype_id
+        );
+    }
+
+    #[derive(Component)]
+    #[component(storage = "SparseSet")]
+    struct Sparse;
+
+    #[test]
+    fn storage_type() {
+        let mut world = World::new();
+        let statement = ArchetypeStatement::<(A, Sparse)>::storage_type(StorageType::SparseSet)
+            .into_untyped(&mut world);
+        let a = world.init_component::<A>();
+        let sparse = world.init_component::<Sparse>();
+
+        assert_eq!(statement.component_ids(), set(&[a, sparse]));
+        assert!(statement.evaluate(&set(&[])));
+        assert!(statement.evaluate(&set(&[sparse])));
+        assert!(!statement.evaluate(&set(&[a])));
+        assert!(!statement.evaluate(&set(&[a, sparse])));
+        assert_eq!(
+            statement.display(&world).to_string(),
+            "StorageType(SparseSet, [A, Sparse])"
+        );
+
+        let table = UntypedArchetypeStatement::storage_type_ids(
+            [a, sparse],
+            StorageType::Table,
+            world.components(),
+        );
+        assert!(table.evaluate(&set(&[a])));
+        assert!(!table.evaluate(&set(&[sparse])));
+    }
+
+    #[test]
+    fn validate_consistency_finds_conflicts() {
+        let mut world = World::new();
+        let requires = world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        let forbids = world.add_archetype_invariant(ArchetypeInvariant::<(C,), (B,)>::forbids());
+        // Only conflicts with the invariants above for entities that have `D`, which it forbids itself
+        world.add_archetype_invariant(ArchetypeInvariant::<(D,), (A, C)>::forbids());
+
+        assert_eq!(
+            world.validate_archetype_invariant_consistency(),
+            vec![(requires, forbids)]
+        );
+    }
+
+    #[test]
+    fn validate_consistency_ignores_compatible_invariants() {
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        world.add_archetype_invariant(ArchetypeInvariant::<(C,), (A,)>::forbids());
+        world.add_archetype_invariant(ArchetypeInvariant::<(B,), (C, D)> {
+            predicate: ArchetypeStatement::all_of(),
+            consequence: ArchetypeStatement::at_least_one_of(),
+        });
+
+        assert!(world.validate_archetype_invariant_consistency().is_empty());
+    }
+
+    #[test]
+    fn try_into_untyped() {
+        let mut world = World::new();
+        let statement = ArchetypeStatement::<(A, B)>::all_of()
+            .and(ArchetypeStatement::subset_of::<(C,)>())
+            .or(ArchetypeStatement::resource_present::<R>());
+        assert_eq!(
+            statement.try_into_untyped(&world),
+            Err(UnregisteredComponentError {
+                name: std::any::type_name::<A>()
+            })
+        );
+
+        world.init_component::<A>();
+        world.init_component::<B>();
+        world.init_component::<C>();
+        assert_eq!(
+            statement.try_into_untyped(&world),
+            Err(UnregisteredComponentError {
+                name: std::any::type_name::<R>()
+            })
+        );
+
+        world.initialize_resource::<R>();
+        let untyped = statement.try_into_untyped(&world).unwrap();
+        assert_eq!(untyped, statement.into_untyped(&mut world));
+    }
+
+    #[test]
+    fn removal_checks_name_orphaned_components() {
+        use crate::{
+            archetype_invariants::{ArchetypeInvariantViolated, ViolationPolicy},
+            event::Events,
+        };
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Event);
+        world.set_check_archetype_invariant_removals(true);
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        let first = world.spawn((A, B, C)).id();
+        let second = world.spawn((A, B, C)).id();
+        let b = world.component_id::<B>().unwrap();
+
+        world.entity_mut(first).remove::<B>();
+        let violations: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .map(|event| event.violation)
+            .collect();
+        // The new archetype is only reported once, by the removal check
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].removed_component_ids, [b]);
+        assert_eq!(violations[0].entities, [first]);
+        assert!(violations[0]
+            .to_string()
+            .starts_with("Removing [B] left [A] orphaned"));
+
+        // Removals into an archetype that was already checked are reported too
+        world.entity_mut(second).remove_intersection::<(B,)>();
+        let violations: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .map(|event| event.violation)
+            .collect();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].entities, [second]);
+
+        // Removing the orphaned component is not a violation
+        world.entity_mut(second).remove::<A>();
+        assert!(world
+            .resource::<Events<ArchetypeInvariantViolated>>()
+            .is_empty());
+    }
+
+    #[test]
+    fn removals_are_not_checked_by_default() {
+        use crate::{
+            archetype_invariants::{ArchetypeInvariantViolated, ViolationPolicy},
+            event::Events,
+        };
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Event);
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        let first = world.spawn((A, B)).id();
+        let second = world.spawn((A, B)).id();
+        world.entity_mut(first).remove::<B>();
+        // The first removal creates a new archetype, which the automatic checks report
+        let violations: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .collect();
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].violation.removed_component_ids.is_empty());
+
+        world.entity_mut(second).remove::<B>();
+        assert!(world
+            .resource::<Events<ArchetypeInvariantViolated>>()
+            .is_empty());
+    }
+
+    #[test]
+    fn component_groups() {
+        use crate::{
+            archetype_invariants::{ArchetypeInvariantViolated, GroupId, ViolationPolicy},
+            event::Events,
+        };
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Event);
+        let [a, b, c, d] = [
+            world.init_component::<A>(),
+            world.init_component::<B>(),
+            world.init_component::<C>(),
+            world.init_component::<D>(),
+        ];
+        let ui = world.register_component_group("ui", &[a, b]);
+        assert_eq!(ui, GroupId::new(0));
+        // Every UI element must have `D`
+        world.add_untyped_archetype_invariant(UntypedArchetypeInvariant {
+            predicate: UntypedArchetypeStatement::GroupAtLeastOneOf(ui),
+            consequence: UntypedArchetypeStatement::AllOf(set(&[d])),
+            severity: Severity::Error,
+        });
+        world.spawn((A, D));
+        world.spawn((B, D));
+        let c_entity = world.spawn(C).id();
+        assert!(!world.contains_resource::<Events<ArchetypeInvariantViolated>>());
+
+        // Existing archetypes are re-checked once `C` joins the group
+        assert!(world.add_to_component_group(ui, c));
+        assert!(!world.add_to_component_group(ui, c));
+        let violations: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .map(|event| event.violation)
+            .collect();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].entities, [c_entity]);
+        assert_eq!(
+            violations[0].invariant.predicate,
+            UntypedArchetypeStatement::AtLeastOneOf(set(&[a, b, c]))
+        );
+
+        // Re-registering a group replaces its members
+        assert_eq!(world.register_component_group("ui", &[a]), ui);
+        assert_eq!(
+            world.archetype_invariants.component_group_members(ui),
+            Some(&set(&[a]))
+        );
+        world.spawn(B);
+        assert!(world
+            .resource::<Events<ArchetypeInvariantViolated>>()
+            .is_empty());
+    }
+
+    #[test]
+    fn report() {
+        use crate::archetype_invariants::ViolationPolicy;
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Warn);
+        let a = world.spawn(A).id();
+        world.spawn((A, B));
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        let forbids_c = world.add_archetype_invariant(ArchetypeInvariant::<(A,), (C,)>::forbids());
+        world.set_archetype_invariant_enabled(forbids_c, false);
+        world.spawn((A, C));
+
+        let report = world.archetype_invariant_report();
+        let lines: Vec<_> = report.lines().collect();
+        assert_eq!(lines.len(), 6, "{report}");
+        assert_eq!(lines[0], "archetype invariants: 2");
+        assert!(lines[1].starts_with("invariant #0: For every entity that has all of [A], it must also have all of [B]. [matches 3 archetype(s)]"));
+        assert!(lines[2].starts_with("invariant #1 (disabled): "));
+        assert_eq!(lines[3], "violations: 2");
+        // Violations are listed by invariant, then by archetype
+        assert!(lines[4].starts_with("violation of invariant #0: Entity in archetype"));
+        assert!(lines[4].contains(&format!(
+            "Affected entities include [{a:?}]. The invariant was registered at {}:",
+            file!()
+        )));
+        assert!(lines[5].starts_with("violation of invariant #0: "));
+        assert_eq!(report, world.archetype_invariant_report());
+    }
+
+    #[test]
+    fn explain() {
+        let mut world = World::new();
+        // Typed invariants can be explained before their components are registered
+        assert_eq!(
+            ArchetypeInvariant::<(A,), (B, C)>::requires().explain(&world),
+            "For every entity that has all of [A], it must also have all of [B, C]."
+        );
+
+        let [a, b, c, d] = [
+            world.init_component::<A>(),
+            world.init_component::<B>(),
+            world.init_component::<C>(),
+            world.init_component::<D>(),
+        ];
+        assert_eq!(
+            ArchetypeInvariant::<(A, B)>::require_at_least_one().explain(&world),
+            "Every entity must have at least one of [A, B]."
+        );
+
+        let invariant = UntypedArchetypeInvariant {
+            predicate: UntypedArchetypeStatement::Or(vec![
+                UntypedArchetypeStatement::AllOf(set(&[a])),
+                UntypedArchetypeStatement::ComponentCount {
+                    min: Some(3),
+                    max: None,
+                },
+            ]),
+            consequence: UntypedArchetypeStatement::Not(Box::new(UntypedArchetypeStatement::And(
+                vec![
+                    UntypedArchetypeStatement::AllOf(set(&[b])),
+                    UntypedArchetypeStatement::NoneOf(set(&[c, d])),
+                ],
+            ))),
+            severity: Severity::Warning,
+        };
+        assert_eq!(
+            invariant.explain(&world),
+            "For every entity that has all of [A] or has at least 3 components in total, \
+            it must also not (have all of [B] and have none of [C, D]). Violations only cause a warning."
+        );
+
+        let ui = world.register_component_group("ui", &[a, b]);
+        let invariant = UntypedArchetypeInvariant {
+            predicate: UntypedArchetypeStatement::GroupAtLeastOneOf(ui),
+            consequence: UntypedArchetypeStatement::ExactlyOneOf(set(&[c, d])),
+            severity: Severity::Error,
+        };
+        assert_eq!(
+            invariant.explain(&world),
+            "For every entity that has at least one of the components in the group \"ui\", \
+            it must also have exactly one of [C, D]."
+        );
+    }
+
+    #[test]
+    fn disabled_invariants_are_skipped_until_enabled() {
+        use crate::{
+            archetype_invariants::{ArchetypeInvariantViolated, ViolationPolicy},
+            event::Events,
+        };
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Event);
+        let requires_b =
+            world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        let forbids_d = world.add_archetype_invariant(ArchetypeInvariant::<(C,), (D,)>::forbids());
+        assert!(world.set_archetype_invariant_enabled(requires_b, false));
+        assert_eq!(
+            world.archetype_invariants.is_enabled(requires_b),
+            Some(false)
+        );
+
+        let a = world.spawn(A).id();
+        assert!(world.check_archetype_invariants().is_empty());
+        assert!(!world.contains_resource::<Events<ArchetypeInvariantViolated>>());
+
+        // Re-enabling re-checks the archetypes created in the meantime against just that invariant
+        world.spawn((C, D));
+        world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .clear();
+        assert!(world.set_archetype_invariant_enabled(requires_b, true));
+        let violations: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .collect();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].violation.entities, [a]);
+
+        // The invariant kept its id and position while it was disabled
+        let requires = ArchetypeInvariant::<(A,), (B,)>::requires().into_untyped(&mut world);
+        assert_eq!(world.archetype_invariants().next(), Some(&requires));
+        assert_eq!(world.remove_archetype_invariant(requires_b), Some(requires));
+        assert!(world.remove_archetype_invariant(forbids_d).is_some());
+        assert!(!world.set_archetype_invariant_enabled(requires_b, true));
+        assert_eq!(world.archetype_invariants.is_enabled(requires_b), None);
+    }
+
+    #[test]
+    fn parallel_checks_match_sequential_checks() {
+        use crate::{
+            archetype_invariants::{ArchetypeInvariantViolated, ViolationPolicy},
+            event::Events,
+        };
+
+        // Single-threaded pools are not used, so make sure the pool has several threads unless another test created it
+        bevy_tasks::ComputeTaskPool::init(|| {
+            bevy_tasks::TaskPoolBuilder::new().num_threads(4).build()
+        });
+        let violations = |parallel_check_threshold| {
+            let mut world = World::new();
+            world.set_archetype_invariant_policy(ViolationPolicy::Event);
+            world.set_archetype_invariant_parallel_check_threshold(parallel_check_threshold);
+            world.spawn(A);
+            world.spawn((A, B));
+            world.spawn((A, C));
+            world.spawn((B, C));
+            world.spawn((A, B, C, D));
+            let invariants = [
+                ArchetypeInvariant::<(A,), (B,)>::requires().into_untyped(&mut world),
+                ArchetypeInvariant::<(C,), (D,)>::requires().into_untyped(&mut world),
+            ];
+            world.add_archetype_invariants(invariants);
+            let stats = world.archetype_invariant_stats();
+            let violations: Vec<_> = world
+                .resource_mut::<Events<ArchetypeInvariantViolated>>()
+                .drain()
+                .map(|event| (event.violation.archetype_id, event.violation.entities))
+                .collect();
+            (violations, stats)
+        };
+
+        let (sequential, sequential_stats) = violations(usize::MAX);
+        assert_eq!(sequential.len(), 4);
+        let (parallel, parallel_stats) = violations(1);
+        assert_eq!(parallel, sequential);
+        // The time spent may differ, but the same work must have been done
+        assert_eq!(
+            (
+                parallel_stats.archetype_checks,
+                parallel_stats.invariant_evaluations,
+                parallel_stats.statement_evaluations,
+            ),
+            (
+                sequential_stats.archetype_checks,
+                sequential_stats.invariant_evaluations,
+                sequential_stats.statement_evaluations,
+            )
+        );
+    }
+
+    #[test]
+    fn statements_from_query_filters() {
+        use crate::query::{Added, Changed, Or, With, Without};
+
+        let mut world = World::new();
+        let statement = UntypedArchetypeStatement::from_filter::<(
+            With<A>,
+            Or<(Without<B>, With<C>)>,
+        )>(&mut world)
+        .unwrap();
+        let [a, b, c] = [
+            world.init_component::<A>(),
+            world.init_component::<B>(),
+            world.init_component::<C>(),
+        ];
+        assert_eq!(
+            statement,
+            UntypedArchetypeStatement::And(vec![
+                UntypedArchetypeStatement::AllOf(set(&[a])),
+                UntypedArchetypeStatement::Or(vec![
+                    UntypedArchetypeStatement::NoneOf(set(&[b])),
+                    UntypedArchetypeStatement::AllOf(set(&[c])),
+                ]),
+            ])
+        );
+        assert!(statement.evaluate(&set(&[a])));
+        assert!(statement.evaluate(&set(&[a, b, c])));
+        assert!(!statement.evaluate(&set(&[a, b])));
+        assert!(!statement.evaluate(&set(&[c])));
+
+        assert_eq!(
+            UntypedArchetypeStatement::from_filter::<()>(&mut world),
+            Ok(UntypedArchetypeStatement::And(Vec::new()))
+        );
+        let error =
+            UntypedArchetypeStatement::from_filter::<(With<A>, Or<(Added<B>,)>)>(&mut world)
+                .unwrap_err();
+        assert_eq!(error.name, std::any::type_name::<Added<B>>());
+        assert!(UntypedArchetypeStatement::from_filter::<Changed<A>>(&mut world).is_err());
+    }
+
+    #[test]
+    fn messages_do_not_depend_on_registration_order() {
+        let render = |register_backwards: bool| {
+            let mut world = World::new();
+            if register_backwards {
+                world.init_component::<D>();
+                world.init_component::<C>();
+                world.init_component::<B>();
+                world.init_component::<A>();
+            }
+            world.spawn((D, C, B, A));
+            let invariant = ArchetypeInvariant::<(B, A), (D, C)>::forbids();
+            let explanation = invariant.explain(&world);
+            let (_, violation) = invariant.check_world_now(&mut world).pop().unwrap();
+            (violation.to_string(), explanation)
+        };
+
+        let (message, explanation) = render(false);
+        assert_eq!(
+            message,
+            "Entity in archetype 2 has components [A, B, C, D] but invariant AllOf(A, B) => NoneOf(C, D) was violated. Affected entities include [0v0]."
+        );
+        assert_eq!(
+            explanation,
+            "For every entity that has all of [A, B], it must also have none of [C, D]."
+        );
+        assert_eq!(render(true), (message, explanation));
+    }
+
+    #[test]
+    fn disabled_invariants_skip_emptied_archetypes_when_enabled() {
+        use crate::{
+            archetype_invariants::{ArchetypeInvariantViolated, ViolationPolicy},
+            event::Events,
+        };
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Event);
+        let id =
+            world.add_disabled_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        assert_eq!(world.is_archetype_invariant_enabled(id), Some(false));
+        // An identical enabled invariant is not a duplicate of the disabled one
+        let enabled = ArchetypeInvariant::<(A,), (B,)>::requires().into_untyped(&mut world);
+        assert_ne!(
+            world
+                .archetype_invariants
+                .add(enabled.clone(), &world.components),
+            id
+        );
+        assert_eq!(
+            world
+                .archetype_invariants
+                .add_disabled(enabled, &world.components),
+            id
+        );
+        world.clear_archetype_invariants();
+
+        let id =
+            world.add_disabled_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        // Built up over several steps, leaving the `A` archetype empty
+        let entity = world.spawn(A).id();
+        world.entity_mut(entity).insert(B);
+        let missing = world.spawn((A, C)).id();
+        assert!(world.set_archetype_invariant_enabled(id, true));
+        let violations: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .map(|event| event.violation.entities)
+            .collect();
+        assert_eq!(violations, [vec![missing]]);
+
+        // The emptied archetype is checked once it has entities again
+        let late = world.spawn(A).id();
+        let violations: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .map(|event| event.violation.entities)
+            .collect();
+        assert_eq!(violations, [vec![late]]);
+    }
+
+    #[test]
+    fn complexity() {
+        let [a, b, c] = [
+            ComponentId::new(0),
+            ComponentId::new(1),
+            ComponentId::new(2),
+        ];
+        assert_eq!(UntypedArchetypeStatement::Always.complexity(), 1);
+        assert_eq!(
+            UntypedArchetypeStatement::AllOf(set(&[a, b])).complexity(),
+            3
+        );
+        assert_eq!(
+            UntypedArchetypeStatement::SubsetOf {
+                relevant: set(&[a, b]),
+                allowed: set(&[c]),
+            }
+            .complexity(),
+            4
+        );
+        let nested = UntypedArchetypeStatement::And(vec![
+            UntypedArchetypeStatement::Or(vec![
+                UntypedArchetypeStatement::AllOf(set(&[a])),
+                UntypedArchetypeStatement::Not(Box::new(UntypedArchetypeStatement::NoneOf(set(
+                    &[b, c],
+                )))),
+            ]),
+            UntypedArchetypeStatement::ResourcePresent(c),
+            UntypedArchetypeStatement::And(Vec::new()),
+        ]);
+        // `And`, `Or`, `AllOf` (2), `Not`, `NoneOf` (3), `ResourcePresent` (2) and the empty `And`
+        assert_eq!(nested.complexity(), 11);
+
+        let invariant = UntypedArchetypeInvariant {
+            predicate: nested,
+            consequence: UntypedArchetypeStatement::AtMostOneOf(set(&[a, b, c])),
+            severity: Severity::Error,
+        };
+        assert_eq!(invariant.complexity(), 15);
+
+        let mut world = World::new();
+        let id = world.add_untyped_archetype_invariant(invariant.clone());
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        assert_eq!(world.archetype_invariants.complexity(), 15 + 4);
+        world.remove_archetype_invariant(id);
+        assert_eq!(world.archetype_invariants.complexity(), 4);
+        world.clear_archetype_invariants();
+        assert_eq!(world.archetype_invariants.complexity(), 0);
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "archetype_invariants",
+        feature = "archetype_invariant_logging"
+    ))]
+    fn complexity_warning() {
+        use std::sync::Arc;
+
+        let counter = Arc::new(EventCounter::new(Level::WARN));
+        with_default(counter.clone(), || {
+            let mut world = World::new();
+            let archetypes = world.archetypes().len();
+            let expensive = UntypedArchetypeInvariant {
+                predicate: UntypedArchetypeStatement::all_of_ids(
+                    (0..ArchetypeInvariants::COMPLEXITY_WARNING_THRESHOLD / (archetypes + 2))
+                        .map(ComponentId::new),
+                ),
+                consequence: UntypedArchetypeStatement::Always,
+                severity: Severity::Error,
+            };
+            world.add_untyped_archetype_invariant(expensive);
+            world.spawn(A);
+            assert_eq!(counter.count(), 0);
+
+            // The estimate grows with the number of archetypes as well as with the invariants
+            world.spawn(B);
+            assert_eq!(world.archetypes().len(), archetypes + 2);
+            assert_eq!(counter.count(), 1);
+
+            // The warning is only logged when the threshold is crossed
+            world.spawn(C);
+            world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::forbids());
+            assert_eq!(counter.count(), 1);
+        });
+    }
+
+    #[test]
+    fn panic_with_all_violations() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        use crate::archetype_invariants::ViolationPolicy;
+
+        let mut world = World::new();
+        world.spawn(A);
+        world.spawn((A, C));
+        world.spawn((A, B));
+        world.spawn((A, D));
+        world.set_archetype_invariant_policy(ViolationPolicy::PanicWithAllViolations);
+        let invariants = [
+            ArchetypeInvariant::<(A,), (B,)>::requires().into_untyped(&mut world),
+            ArchetypeInvariant::<(A,), (D,)>::forbids().into_untyped(&mut world),
+        ];
+        let panic = catch_unwind(AssertUnwindSafe(|| {
+            world.add_archetype_invariants(invariants);
+        }))
+        .unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        let lines: Vec<_> = message.lines().collect();
+        assert_eq!(lines[0], "4 archetype invariant violation(s) were found:");
+        assert_eq!(lines.len(), 5);
+        for components in ["[A]", "[A, C]", "[A, D] but invariant AllOf(A) => AllOf(B)"] {
+            assert!(lines.iter().any(|line| line.contains(components)));
+        }
+        assert!(lines
+            .iter()
+            .any(|line| line.contains("[A, D] but invariant AllOf(A) => NoneOf(D)")));
+    }
+
+    #[test]
+    fn spawn_batch_violations_sample_entities() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        use crate::{
+            archetype_invariants::{ArchetypeInvariantViolated, ViolationPolicy},
+            event::Events,
+        };
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Event);
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        let entities: Vec<_> = world.spawn_batch((0..3).map(|_| (A, C))).collect();
+        let violations: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .collect();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].violation.entities, entities);
+        assert!(!violations[0]
+            .violation
+            .to_string()
+            .contains("There are no entities currently"));
+
+        world.set_archetype_invariant_policy(ViolationPolicy::PanicWithAllViolations);
+        let panic = catch_unwind(AssertUnwindSafe(|| {
+            world.spawn_batch((0..3).map(|_| (A, D))).for_each(drop);
+        }))
+        .unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.contains("Affected entities include"));
+        assert!(!message.contains("There are no entities currently"));
+    }
+
+    #[test]
+    fn any_of_bundles() {
+        let mut world = World::new();
+        let statement = any_of!((A, B), (C,), (B, D)).into_untyped(&mut world);
+        let [a, b, c, d] = [
+            world.init_component::<A>(),
+            world.init_component::<B>(),
+            world.init_component::<C>(),
+            world.init_component::<D>(),
+        ];
+        assert_eq!(
+            statement,
+            UntypedArchetypeStatement::Or(vec![
+                UntypedArchetypeStatement::AllOf(set(&[a, b])),
+                UntypedArchetypeStatement::AllOf(set(&[c])),
+                UntypedArchetypeStatement::AllOf(set(&[b, d])),
+            ])
+        );
+        // Each alternative matches on its own
+        assert!(statement.evaluate(&set(&[a, b])));
+        assert!(statement.evaluate(&set(&[c])));
+        assert!(statement.evaluate(&set(&[b, d])));
+        assert!(statement.evaluate(&set(&[a, b, c, d])));
+        // Partial alternatives do not
+        assert!(!statement.evaluate(&set(&[a, d])));
+        assert!(!statement.evaluate(&set(&[b])));
+        assert!(!statement.evaluate(&set(&[])));
+
+        let single = any_of!((A, B)).into_untyped(&mut world);
+        assert!(single.evaluate(&set(&[a, b])));
+        assert!(!single.evaluate(&set(&[a])));
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        use crate::{
+            archetype_invariants::{ArchetypeInvariantViolated, ViolationPolicy},
+            event::Events,
+        };
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Event);
+        let requires = world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        let forbids = world
+            .add_archetype_invariant_labeled("plugin", ArchetypeInvariant::<(C,), (D,)>::forbids());
+        world.set_archetype_invariant_enabled(forbids, false);
+        let snapshot = world.archetype_invariants_snapshot();
+        let original: Vec<_> = world.archetype_invariants().cloned().collect();
+        assert_eq!(
+            snapshot.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            [requires, forbids]
+        );
+
+        // An archetype that only violates the removed invariant is created while it is absent
+        world.remove_archetype_invariant(requires);
+        world.add_archetype_invariant(ArchetypeInvariant::<(D,), (A,)>::requires());
+        let violating = world.spawn(A).id();
+        assert!(!world.contains_resource::<Events<ArchetypeInvariantViolated>>());
+
+        world.restore_archetype_invariants(snapshot);
+        assert_eq!(
+            world.archetype_invariants().cloned().collect::<Vec<_>>(),
+            original
+        );
+        assert_eq!(world.archetype_invariants.complexity(), 8);
+        assert_eq!(world.is_archetype_invariant_enabled(forbids), Some(false));
+        // Restoring checks every archetype against the restored invariants
+        let violations: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .map(|event| event.violation.entities)
+            .collect();
+        assert_eq!(violations, [vec![violating]]);
+
+        // Ids and labels are preserved, and new ids are not reused
+        assert_eq!(
+            world.remove_archetype_invariants_by_label("plugin").len(),
+            1
+        );
+        assert!(world.remove_archetype_invariant(requires).is_some());
+        let new = world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        assert_ne!(new, requires);
+        assert_ne!(new, forbids);
+    }
+
+    #[test]
+    fn evaluate_against_archetype_matches_evaluate() {
+        #[derive(Component)]
+        #[component(storage = "SparseSet")]
+        struct S;
+
+        let mut world = World::new();
+        world.spawn((A, B));
+        world.spawn((A, S));
+        world.spawn((B, C, D, S));
+        world.spawn_empty();
+        let [a, b, c, s] = [
+            world.init_component::<A>(),
+            world.init_component::<B>(),
+            world.init_component::<C>(),
+            world.init_component::<S>(),
+        ];
+
+        let statements = [
+            UntypedArchetypeStatement::all_of_ids([a, s]),
+            UntypedArchetypeStatement::AtLeastOneOf(set(&[c, s])),
+            UntypedArchetypeStatement::none_of_ids([b]),
+            UntypedArchetypeStatement::ExactlyOneOf(set(&[a, b])),
+            UntypedArchetypeStatement::AtMostNOf(set(&[b, c, s]), 2),
+            UntypedArchetypeStatement::AtLeastNOf(set(&[a, b, s]), 2),
+            UntypedArchetypeStatement::Exactly(set(&[a, s])),
+            UntypedArchetypeStatement::SubsetOf {
+                relevant: set(&[a, b, s]),
+                allowed: set(&[a]),
+            },
+            UntypedArchetypeStatement::ComponentCount {
+                min: Some(2),
+                max: Some(3),
+            },
+            UntypedArchetypeStatement::Empty,
+            UntypedArchetypeStatement::Not(Box::new(UntypedArchetypeStatement::Or(vec![
+                UntypedArchetypeStatement::all_of_ids([c]),
+                UntypedArchetypeStatement::Exactly(set(&[a, b])),
+            ]))),
+        ];
+        for archetype in world.archetypes().iter() {
+            let component_ids: ComponentIdSet = archetype.components().collect();
+            for statement in &statements {
+                assert_eq!(
+                    statement.evaluate_against_archetype(archetype),
+                    statement.evaluate(&component_ids),
+                    "{statement:?} disagrees for {component_ids:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn forbid_bundle() {
+        use crate::archetype_invariants::ViolationPolicy;
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Warn);
+        world.add_archetype_invariant(ArchetypeInvariant::<(C, D)>::forbid_bundle());
+
+        world.spawn_empty();
+        world.spawn((A, B));
+        assert!(world.check_archetype_invariants().is_empty());
+
+        let entity = world.spawn((A, D)).id();
+        let violations = world.check_archetype_invariants();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].archetype_id,
+            world.entity(entity).location().archetype_id
+        );
+        assert_eq!(
+            violations[0].invariant,
+            ArchetypeInvariant::<(C, D)>::forbid_bundle().into_untyped(&mut world)
+        );
+    }
+
+    #[test]
+    fn exempt_entity_joined_by_unexempt_entity() {
+        use crate::{
+            archetype_invariants::{ArchetypeInvariantViolated, ViolationPolicy},
+            event::Events,
+        };
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Event);
+        let id = world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+
+        let exempt = world.spawn_empty().id();
+        assert!(world.exempt_entity_from_invariant(exempt, id));
+        world.entity_mut(exempt).insert(A);
+        assert!(!world.contains_resource::<Events<ArchetypeInvariantViolated>>());
+        assert!(world.check_archetype_invariants().is_empty());
+        assert!(world
+            .archetype_invariant_violations_for_entity(exempt)
+            .is_empty());
+
+        // The archetype is checked again once an entity that is not exempt joins it
+        let unexempt = world.spawn(A).id();
+        let violations: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .collect();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].violation.entities, [unexempt]);
+        let requires = ArchetypeInvariant::<(A,), (B,)>::requires().into_untyped(&mut world);
+        assert_eq!(
+            world.archetype_invariant_violations_for_entity(unexempt),
+            [&requires]
+        );
+
+        world.spawn(A);
+        assert!(world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn exempt_entity_joins_violating_archetype() {
+        use crate::archetype_invariants::ViolationPolicy;
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Warn);
+        let id = world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+
+        let unexempt = world.spawn(A).id();
+        let exempt = world.spawn_empty().id();
+        world.exempt_entity_from_invariant(exempt, id);
+        world.entity_mut(exempt).insert(A);
+        assert!(world.archetype_invariants.is_entity_exempt(id, exempt));
+        assert!(!world.archetype_invariants.is_entity_exempt(id, unexempt));
+
+        let violations = world.check_archetype_invariants();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].entities, [unexempt]);
+        assert!(world
+            .archetype_invariant_violations_for_entity(exempt)
+            .is_empty());
+        assert_eq!(
+            world
+                .archetype_invariant_violations_for_entity(unexempt)
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn parse() {
+        #[derive(Resource)]
+        struct R;
+
+        let mut world = World::new();
+        let [a, b, c, d] = [
+            world.init_component::<A>(),
+            world.init_component::<B>(),
+            world.init_component::<C>(),
+            world.init_component::<D>(),
+        ];
+        world.insert_resource(R);
+        let r = world
+            .components()
+            .get_resource_id(std::any::TypeId::of::<R>())
+            .unwrap();
+
+        let invariant = world
+            .parse_archetype_invariant("AllOf(A,B) => NoneOf(C)")
+            .unwrap();
+        assert_eq!(
+            invariant,
+            ArchetypeInvariant::<(A, B), (C,)>::forbids().into_untyped(&mut world)
+        );
+
+        let full_name = std::any::type_name::<D>();
+        let invariant = world
+            .parse_archetype_invariant(&format!(
+                "And(ExactlyNOf(1, [A, B]), Not(Empty), ResourcePresent(R)) => \
+                 Or(SubsetOf([A, {full_name}], [A]), ComponentCount(..=3), Always, Exactly())"
+            ))
+            .unwrap();
+        assert_eq!(
+            invariant.predicate,
+            UntypedArchetypeStatement::And(vec![
+                UntypedArchetypeStatement::ExactlyNOf(set(&[a, b]), 1),
+                UntypedArchetypeStatement::Not(Box::new(UntypedArchetypeStatement::Empty)),
+                UntypedArchetypeStatement::ResourcePresent(r),
+            ])
+        );
+        assert_eq!(
+            invariant.consequence,
+            UntypedArchetypeStatement::Or(vec![
+                UntypedArchetypeStatement::SubsetOf {
+                    relevant: set(&[a, d]),
+                    allowed: set(&[a]),
+                },
+                UntypedArchetypeStatement::ComponentCount {
+                    min: None,
+                    max: Some(3),
+                },
+                UntypedArchetypeStatement::Always,
+                UntypedArchetypeStatement::Exactly(set(&[])),
+            ])
+        );
+
+        // Displayed invariants can be parsed again
+        let invariant = UntypedArchetypeInvariant {
+            predicate: UntypedArchetypeStatement::AtLeastNOf(set(&[a, b, c]), 2),
+            consequence: UntypedArchetypeStatement::And(vec![
+                UntypedArchetypeStatement::ComponentCount {
+                    min: Some(2),
+                    max: None,
+                },
+                UntypedArchetypeStatement::storage_type_ids(
+                    [c],
+                    StorageType::SparseSet,
+                    &world.components,
+                ),
+            ]),
+            severity: Severity::Error,
+        };
+        let displayed = invariant.display(&world).to_string();
+        assert_eq!(
+            world.parse_archetype_invariant(&displayed).unwrap(),
+            invariant
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let mut world = World::new();
+        world.init_component::<A>();
+        world.init_component::<B>();
+
+        let error = |src: &str| {
+            let error = world.parse_archetype_invariant(src).unwrap_err();
+            (error.offset, error.message)
+        };
+        assert_eq!(
+            error("AllOf(A) => NoneOf(Z)"),
+            (
+                19,
+                "no component is registered with the name `Z`".to_string()
+            )
+        );
+        assert_eq!(
+            error("AllOf(A) => SomeOf(B)"),
+            (12, "unknown statement `SomeOf`".to_string())
+        );
+        assert_eq!(
+            error("AllOf(A) -> AllOf(B)"),
+            (9, "unexpected character `-`".to_string())
+        );
+        assert_eq!(
+            error("AllOf(A B) => AllOf(B)"),
+            (8, "unexpected `B`, expected `,` or `)`".to_string())
+        );
+        assert_eq!(
+            error("AllOf(A) => AllOf(B"),
+            (
+                19,
+                "unexpected end of input, expected `,` or `)`".to_string()
+            )
+        );
+        assert_eq!(
+            error("AllOf(A)"),
+            (8, "unexpected end of input, expected `=>`".to_string())
+        );
+        assert_eq!(
+            error("AllOf(A) => AllOf(B) => AllOf(A)"),
+            (21, "unexpected `=>` after the invariant".to_string())
+        );
+        assert_eq!(
+            error("AtMostNOf(A, [B]) => Always"),
+            (10, "unexpected `A`, expected a number".to_string())
+        );
+        assert_eq!(
+            world
+                .parse_archetype_invariant("AllOf(A) => NoneOf(Z)")
+                .unwrap_err()
+                .to_string(),
+            "no component is registered with the name `Z` at offset 19"
+        );
+    }
+
+    #[test]
+    fn dynamic_components() {
+        use crate::{
+            archetype_invariants::{
+                ArchetypeInvariantViolated, ArchetypeStatementKind, ViolationPolicy,
+            },
+            component::ComponentDescriptor,
+            event::Events,
+            storage::TableId,
+        };
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Event);
+        let [health, poisoned] = ["scripting::Health", "scripting::Poisoned"].map(|name| {
+            // SAFETY: the layout has no drop function, and no values of the component are ever created
+            let descriptor = unsafe {
+                ComponentDescriptor::new_with_layout(
+                    name,
+                    StorageType::SparseSet,
+                    std::alloc::Layout::new::<u8>(),
+                    None,
+                )
+            };
+            world.init_component_with_descriptor(descriptor)
+        });
+
+        let invariant = UntypedArchetypeInvariant {
+            predicate: UntypedArchetypeStatement::all_of_ids([poisoned]),
+            consequence: UntypedArchetypeStatement::all_of_ids([health]),
+            severity: Severity::Error,
+        };
+        assert_eq!(
+            world
+                .parse_archetype_invariant("AllOf(Poisoned) => AllOf(scripting::Health)")
+                .unwrap(),
+            invariant
+        );
+        let by_name = world
+            .add_archetype_invariant_by_name(
+                (ArchetypeStatementKind::AllOf, &["scripting::Poisoned"]),
+                (ArchetypeStatementKind::AllOf, &["scripting::Health"]),
+            )
+            .unwrap();
+        assert_eq!(
+            world.archetype_invariants().next(),
+            Some(&invariant),
+            "{by_name:?} was resolved to other components"
+        );
+
+        // There is no typed API to insert these components, so their archetypes are created directly
+        world
+            .archetypes
+            .get_id_or_insert(TableId::empty(), Vec::new(), vec![health, poisoned]);
+        world.check_new_archetypes();
+        assert!(!world.contains_resource::<Events<ArchetypeInvariantViolated>>());
+
+        let violating =
+            world
+                .archetypes
+                .get_id_or_insert(TableId::empty(), Vec::new(), vec![poisoned]);
+        world.check_new_archetypes();
+        let violations: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .map(|event| event.violation)
+            .collect();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].archetype_id, violating);
+        assert_eq!(
+            violations[0].to_string(),
+            format!(
+                "Entity in archetype {} has components [Poisoned] but invariant AllOf(Poisoned) => AllOf(Health) was violated. \
+                There are no entities currently, but the archetype shape is forbidden. The invariant was registered at {}:{}.",
+                violating.index(),
+                file!(),
+                violations[0].location.unwrap().line()
+            )
+        );
+        assert_eq!(
+            invariant.explain(&world),
+            "For every entity that has all of [Poisoned], it must also have all of [Health]."
+        );
+    }
+
+    #[test]
+    fn iter_violating_archetypes() {
+        use crate::archetype_invariants::ViolationPolicy;
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Warn);
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (C,)>::forbids());
+        world.spawn(A);
+        world.spawn((A, B));
+        world.spawn((A, C));
+        world.spawn((A, B, C));
+
+        let expected: Vec<_> = world
+            .check_archetype_invariants()
+            .into_iter()
+            .map(|violation| (violation.archetype_id, violation.invariant))
+            .collect();
+        assert_eq!(expected.len(), 4);
+        let violations: Vec<_> = world
+            .iter_violating_archetypes()
+            .map(|(archetype_id, invariant)| (archetype_id, invariant.clone()))
+            .collect();
+        assert_eq!(violations, expected);
+
+        // Taking the first violation does not check the archetypes after it
+        let mut iter = world
+            .archetype_invariants
+            .iter_violating_archetypes(&world.archetypes);
+        assert_eq!(
+            iter.by_ref()
+                .take(1)
+                .map(|(archetype_id, _)| archetype_id)
+                .collect::<Vec<_>>(),
+            [expected[0].0]
+        );
+        assert_eq!(iter.archetype_index, expected[0].0.index());
+        assert!(iter.archetype_index < world.archetypes.len() - 1);
+    }
+
+    #[test]
+    fn with_capacity() {
+        let archetype_invariants = ArchetypeInvariants::with_capacity(64);
+        assert!(archetype_invariants.raw_list.capacity() >= 64);
+        assert!(archetype_invariants.is_empty());
+
+        let mut world = World::new();
+        world.spawn((A, B));
+        let mut archetype_invariants = ArchetypeInvariants::new();
+        for archetype_invariants in [
+            &mut archetype_invariants,
+            &mut ArchetypeInvariants::with_capacity(1),
+        ] {
+            assert_eq!(archetype_invariants.last_checked_archetype_index, 0);
+            archetype_invariants.set_policy(crate::archetype_invariants::ViolationPolicy::Warn);
+            archetype_invariants.add(
+                ArchetypeInvariant::<(A,), (B,)>::forbids().into_untyped(&mut world),
+                &world.components,
+            );
+            // Every archetype of the world is checked the first time
+            assert_eq!(
+                archetype_invariants.check_new_archetypes(&world.archetypes, &world.components),
+                world.archetypes.len() - 1
+            );
+        }
+    }
+
+    #[test]
+    fn paired_requires() {
+        use crate::{
+            archetype_invariants::{ArchetypeInvariantViolated, ViolationPolicy},
+            event::Events,
+        };
+
+        #[derive(Component)]
+        struct WeaponA;
+        #[derive(Component)]
+        struct AmmoA;
+        #[derive(Component)]
+        struct WeaponB;
+        #[derive(Component)]
+        struct AmmoB;
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Event);
+        let pairs = [
+            (
+                world.init_component::<WeaponA>(),
+                world.init_component::<AmmoA>(),
+            ),
+            (
+                world.init_component::<WeaponB>(),
+                world.init_component::<AmmoB>(),
+            ),
+        ];
+        let invariants = UntypedArchetypeInvariant::paired_requires(&pairs);
+        assert_eq!(
+            invariants[1],
+            ArchetypeInvariant::<(WeaponB,), (AmmoB,)>::requires().into_untyped(&mut world)
+        );
+        world.add_archetype_invariants(invariants);
+
+        world.spawn((WeaponA, AmmoA));
+        world.spawn((WeaponB, AmmoB));
+        world.spawn((WeaponA, AmmoA, WeaponB, AmmoB));
+        world.spawn(AmmoA);
+        assert!(!world.contains_resource::<Events<ArchetypeInvariantViolated>>());
+
+        world.spawn((WeaponA, WeaponB, AmmoB));
+        let messages: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .map(|event| event.violation.to_string())
+            .collect();
+        assert_eq!(messages.len(), 1);
+        assert!(
+            messages[0].contains("but invariant AllOf(WeaponA) => AllOf(AmmoA) was violated"),
+            "{}",
+            messages[0]
+        );
+    }
+
+    #[test]
+    fn manual_checks() {
+        use crate::{
+            archetype_invariants::{
+                check_archetype_invariants_system, ArchetypeInvariantViolated, ViolationPolicy,
+            },
+            event::Events,
+        };
+
+        let mut world = World::new();
+        world.set_automatic_archetype_invariant_checks(false);
+        world.set_check_archetype_invariant_removals(true);
+        world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        // Neither the new archetype nor the removal is checked under the panicking default policy
+        world.spawn(A);
+        let entity = world.spawn((A, B)).id();
+        world.entity_mut(entity).remove::<B>();
+        assert_eq!(world.check_archetype_invariants().len(), 1);
+
+        world.set_archetype_invariant_policy(ViolationPolicy::Event);
+        check_archetype_invariants_system(&mut world);
+        let violated: Vec<_> = world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .collect();
+        assert_eq!(violated.len(), 1);
+        assert_eq!(violated[0].violation.entities.len(), 2);
+
+        // Everything was checked, so running the system again reports nothing new
+        check_archetype_invariants_system(&mut world);
+        assert!(world
+            .resource_mut::<Events<ArchetypeInvariantViolated>>()
+            .drain()
+            .next()
+            .is_none());
+
+        world.set_automatic_archetype_invariant_checks(true);
+        world.spawn((A, C));
+        assert_eq!(
+            world
+                .resource_mut::<Events<ArchetypeInvariantViolated>>()
+                .drain()
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn spec() {
+        use crate::archetype_invariants::{
+            ArchetypeInvariantSpec, ArchetypeStatementKind, ArchetypeStatementSpec,
+            UnknownComponentNames,
+        };
+        #[derive(Resource)]
+        struct R;
+
+        let mut world = World::new();
+        world.insert_resource(R);
+        let [a, b, c] = [
+            world.init_component::<A>(),
+            world.init_component::<B>(),
+            world.init_component::<C>(),
+        ];
+        let name = |name: &str| format!("bevy_ecs::archetype_invariants::tests::{name}");
+
+        let spec = ArchetypeInvariantSpec::new(
+            ArchetypeStatementSpec::And(vec![
+                ArchetypeStatementSpec::components(ArchetypeStatementKind::AllOf, [name("A")]),
+                ArchetypeStatementSpec::Not(Box::new(ArchetypeStatementSpec::ResourcePresent(
+                    std::any::type_name::<R>().to_string(),
+                ))),
+            ]),
+            ArchetypeStatementSpec::Or(vec![
+                ArchetypeStatementSpec::components(
+                    ArchetypeStatementKind::ExactlyNOf(1),
+                    [name("B"), name("C")],
+                ),
+                ArchetypeStatementSpec::GroupAtLeastOneOf("weapons".to_string()),
+            ]),
+        );
+        let invariant = spec.compile(&mut world).unwrap();
+        // The unknown group was registered without members
+        let weapons = world
+            .archetype_invariants
+            .component_group("weapons")
+            .unwrap();
+        assert!(world
+            .archetype_invariants
+            .component_group_members(weapons)
+            .unwrap()
+            .is_empty());
+        let r = world
+            .components()
+            .get_resource_id(std::any::TypeId::of::<R>())
+            .unwrap();
+        assert_eq!(
+            invariant,
+            UntypedArchetypeInvariant {
+                predicate: UntypedArchetypeStatement::And(vec![
+                    UntypedArchetypeStatement::all_of_ids([a]),
+                    UntypedArchetypeStatement::Not(Box::new(
+                        UntypedArchetypeStatement::ResourcePresent(r)
+                    )),
+                ]),
+                consequence: UntypedArchetypeStatement::Or(vec![
+                    UntypedArchetypeStatement::ExactlyNOf(set(&[b, c]), 1),
+                    UntypedArchetypeStatement::GroupAtLeastOneOf(weapons),
+                ]),
+                severity: Severity::Error,
+            }
+        );
+        assert_eq!(invariant.to_spec(&world), spec);
+        assert_eq!(invariant.to_spec(&world).compile(&mut world), Ok(invariant));
+
+        let spec = ArchetypeInvariant::<(A,), (B, D)>::requires().into_spec(&mut world);
+        assert_eq!(
+            spec.consequence,
+            ArchetypeStatementSpec::components(
+                ArchetypeStatementKind::AllOf,
+                [name("B"), name("D")]
+            )
+        );
+        assert_eq!(
+            spec.compile(&mut world),
+            Ok(ArchetypeInvariant::<(A,), (B, D)>::requires().into_untyped(&mut world))
+        );
+
+        let groups = world.archetype_invariants.component_groups.len();
+        let spec = ArchetypeInvariantSpec::new(
+            ArchetypeStatementSpec::GroupAllOf("armor".to_string()),
+            ArchetypeStatementSpec::SubsetOf {
+                relevant: vec![name("A"), "Missing".to_string()],
+                allowed: vec!["AlsoMissing".to_string()],
+            },
+        );
+        assert_eq!(
+            spec.compile(&mut world),
+            Err(UnknownComponentNames {
+                names: vec!["Missing".to_string(), "AlsoMissing".to_string()]
+            })
+        );
+        // Nothing is registered when compiling fails
+        assert_eq!(world.archetype_invariants.component_groups.len(), groups);
+    }
+
+    #[test]
+    fn registration_location() {
+        use crate::archetype_invariants::ViolationPolicy;
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Warn);
+        let line = line!() + 1;
+        let id = world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+        let location = world.archetype_invariant_location(id).unwrap();
+        assert_eq!((location.file(), location.line()), (file!(), line));
+
+        // Every invariant added at once is registered at the same call
+        let invariants = [
+            ArchetypeInvariant::<(C,), (D,)>::forbids().into_untyped(&mut world),
+            ArchetypeInvariant::<(D,), (C,)>::forbids().into_untyped(&mut world),
+        ];
+        let batch_line = line!() + 1;
+        let ids = world.add_archetype_invariants(invariants);
+        for batch_id in ids {
+            assert_eq!(
+                world.archetype_invariant_location(batch_id).unwrap().line(),
+                batch_line
+            );
+        }
+
+        // The location is kept by snapshots
+        let snapshot = world.archetype_invariants_snapshot();
+        world.clear_archetype_invariants();
+        assert!(world.archetype_invariant_location(id).is_none());
+        world.restore_archetype_invariants(snapshot);
+        assert_eq!(world.archetype_invariant_location(id), Some(location));
+
+        world.spawn(A);
+        let violation = world.check_archetype_invariants().remove(0);
+        assert_eq!(violation.location, Some(location));
+        assert!(violation.to_string().ends_with(&format!(
+            " The invariant was registered at {}:{line}.",
+            file!()
+        )));
+
+        let unregistered = ArchetypeInvariantViolation::new(
+            violation.archetype_id,
+            violation.component_ids.clone(),
+            Vec::new(),
+            violation.invariant.clone(),
+            world.components(),
+        );
+        assert_eq!(unregistered.location, None);
+        assert!(!unregistered.to_string().contains("registered at"));
+    }
+
+    #[test]
+    fn at_most_one_of_many_components() {
+        use crate::archetype_invariants::ViolationPolicy;
+
+        #[derive(Component)]
+        struct Layer<const N: usize>;
+
+        let mut world = World::new();
+        macro_rules! init_layers {
+            ($($n:literal)*) => {
+                [$(world.init_component::<Layer<$n>>()),*]
+            };
+        }
+        let layers = init_layers!(
+            0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+        );
+        let range = ComponentIdSet::range(layers[0]..=layers[31]);
+        assert_eq!(range.len(), 32);
+        assert_eq!(range, layers.into_iter().collect());
+
+        world.set_archetype_invariant_policy(ViolationPolicy::Warn);
+        world.add_untyped_archetype_invariant(UntypedArchetypeInvariant {
+            predicate: UntypedArchetypeStatement::Always,
+            consequence: UntypedArchetypeStatement::at_most_one_of_ids(layers),
+            severity: Severity::Error,
+        });
+        world.spawn((A, Layer::<0>));
+        world.spawn(Layer::<17>);
+        world.spawn(Layer::<31>);
+        assert!(world.check_archetype_invariants().is_empty());
+
+        let entity = world.spawn((Layer::<5>, Layer::<31>)).id();
+        let violations = world.check_archetype_invariants();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].entities, [entity]);
+    }
+
+    #[test]
+    fn subsumes() {
+        use crate::archetype_invariants::GroupId;
+        use UntypedArchetypeStatement as S;
+
+        let [a, b, c, d] = [0, 1, 2, 3].map(ComponentId::new);
+        let implies = |x: &S, y: &S| {
+            // Every decided implication must hold for every archetype made of these components
+            if x.subsumes(y) {
+                for bits in 0..16 {
+                    let archetype: ComponentIdSet = [a, b, c, d]
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(i, _)| bits & (1 << i) != 0)
+                        .map(|(_, id)| id)
+                        .collect();
+                    assert!(
+                        !x.evaluate(&archetype) || y.evaluate(&archetype),
+                        "{x:?} does not imply {y:?} for {archetype:?}"
+                    );
+                }
+                true
+            } else {
+                false
+            }
+        };
+
+        assert!(implies(
+            &S::all_of_ids([a, b]),
+            &S::at_least_one_of_ids([a, b])
+        ));
+        assert!(!implies(
+            &S::at_least_one_of_ids([a, b]),
+            &S::all_of_ids([a, b])
+        ));
+        assert!(implies(&S::all_of_ids([a, b, c]), &S::all_of_ids([a, c])));
+        assert!(implies(
+            &S::all_of_ids([a, b]),
+            &S::at_least_n_of_ids([a, b, c], 2)
+        ));
+        assert!(!implies(
+            &S::all_of_ids([a]),
+            &S::at_least_n_of_ids([a, b, c], 2)
+        ));
+        assert!(implies(&S::none_of_ids([a, b]), &S::none_of_ids([b])));
+        assert!(implies(
+            &S::none_of_ids([a, b]),
+            &S::at_most_one_of_ids([a, b, c])
+        ));
+        assert!(!implies(
+            &S::none_of_ids([a]),
+            &S::at_most_one_of_ids([a, b, c])
+        ));
+        assert!(implies(
+            &S::exactly_one_of_ids([a, b]),
+            &S::at_least_one_of_ids([a, b, c])
+        ));
+        assert!(implies(
+            &S::exactly_one_of_ids([a, b, c]),
+            &S::at_most_one_of_ids([a, b])
+        ));
+        assert!(implies(
+            &S::at_least_one_of_ids([a]),
+            &S::at_least_one_of_ids([a, b])
+        ));
+        assert!(implies(
+            &S::AtLeastNOf(set(&[a, b, c]), 3),
+            &S::all_of_ids([a, b, c])
+        ));
+        assert!(!implies(
+            &S::exactly_one_of_ids([a, b]),
+            &S::exactly_one_of_ids([a, b, c])
+        ));
+
+        // Statements that forbid components are compared as `NoneOf`
+        let subset_of = S::subset_of_ids([a, b], [a]);
+        assert!(implies(&subset_of, &S::none_of_ids([b])));
+        assert!(implies(&S::none_of_ids([b, c]), &subset_of));
+
+        // A single archetype against anything
+        assert!(implies(
+            &S::exactly_ids([a, b]),
+            &S::Or(vec![S::all_of_ids([c]), S::exactly_one_of_ids([b, c])])
+        ));
+        assert!(implies(&S::Empty, &S::none_of_ids([a, b, c, d])));
+        assert!(!implies(&S::Empty, &S::all_of_ids([a])));
+        assert!(implies(
+            &S::ComponentCount {
+                min: Some(2),
+                max: Some(3)
+            },
+            &S::ComponentCount {
+                min: Some(1),
+                max: None
+            }
+        ));
+
+        // Combinators
+        assert!(implies(&S::all_of_ids([a]), &S::Always));
+        assert!(implies(&S::Or(Vec::new()), &S::all_of_ids([a])));
+        assert!(implies(
+            &S::And(vec![S::all_of_ids([a]), S::none_of_ids([b])]),
+            &S::none_of_ids([b])
+        ));
+        assert!(implies(
+            &S::all_of_ids([a, b]),
+            &S::And(vec![S::all_of_ids([a]), S::at_least_one_of_ids([b, c])])
+        ));
+        assert!(implies(
+            &S::Or(vec![S::all_of_ids([a]), S::all_of_ids([b])]),
+            &S::at_least_one_of_ids([a, b])
+        ));
+        assert!(implies(
+            &S::Not(Box::new(S::at_least_one_of_ids([a, b]))),
+            &S::Not(Box::new(S::all_of_ids([a])))
+        ));
+        assert!(!implies(
+            &S::GroupAllOf(GroupId::new(0)),
+            &S::GroupAtLeastOneOf(GroupId::new(0))
+        ));
+
+        // An invariant that requires more from more entities makes the weaker one redundant
+        let strong = UntypedArchetypeInvariant {
+            predicate: S::at_least_one_of_ids([a, b]),
+            consequence: S::all_of_ids([c, d]),
+            severity: Severity::Error,
+        };
+        let weak = UntypedArchetypeInvariant {
+            predicate: S::all_of_ids([a]),
+            consequence: S::at_least_one_of_ids([c]),
+            severity: Severity::Error,
+        };
+        assert!(strong.subsumes(&weak));
+        assert!(!weak.subsumes(&strong));
+    }
+
+    #[test]
+    fn hierarchy_statements() {
+        use crate::archetype_invariants::ViolationPolicy;
+
+        let mut world = World::new();
+        world.set_archetype_invariant_policy(ViolationPolicy::Warn);
+        assert!(UntypedArchetypeStatement::has_parent(&world).is_none());
+        assert!(UntypedArchetypeStatement::has_children(&world).is_none());
+
+        // `A` stands in for the parent component and `B` for the children component
+        world.set_hierarchy_components::<A, B>();
+        let [a, b, c] = [
+            world.init_component::<A>(),
+            world.init_component::<B>(),
+            world.init_component::<C>(),
+        ];
+        let has_parent = UntypedArchetypeStatement::has_parent(&world).unwrap();
+        assert_eq!(has_parent, UntypedArchetypeStatement::HasParent(a));
+        assert_eq!(
+            UntypedArchetypeStatement::has_children(&world),
+            Some(UntypedArchetypeStatement::HasChildren(b))
+        );
+        assert!(has_parent.evaluate(&set(&[a, c])));
+        assert!(!has_parent.evaluate(&set(&[b, c])));
+        assert!(has_parent.subsumes(&UntypedArchetypeStatement::at_least_one_of_ids([a, c])));
+
+        let invariant = UntypedArchetypeInvariant {
+            predicate: UntypedArchetypeStatement::all_of_ids([c]),
+            consequence: has_parent,
+            severity: Severity::Error,
+        };
+        assert!(invariant
+            .explain(&world)
+            .ends_with("it must also have a parent."));
+        let displayed = invariant.display(&world).to_string();
+        assert!(displayed.contains("HasParent("));
+        assert_eq!(
+            world.parse_archetype_invariant(&displayed),
+            Ok(invariant.clone())
+        );
+        let spec = invariant.to_spec(&world);
+        assert_eq!(spec.compile(&mut world).unwrap(), invariant);
+
+        world.add_untyped_archetype_invariant(invariant);
+        world.spawn((C, A));
+        assert!(world.check_archetype_invariants().is_empty());
+        world.spawn(C);
+        let violations = world.check_archetype_invariants();
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].to_string().contains("HasParent("));
+    }
+
+    #[test]
+    fn shrink_to_fit() {
+        let mut world = World::new();
+        let ids = world.add_archetype_invariants((0..64).map(|i| UntypedArchetypeInvariant {
+            predicate: UntypedArchetypeStatement::all_of_ids((0..8).map(ComponentId::new)),
+            consequence: UntypedArchetypeStatement::at_least_one_of_ids(
+                (8 + i..16 + i).map(ComponentId::new),
+            ),
+            severity: Severity::Error,
+        }));
+        let full = world.archetype_invariant_memory_usage();
+        for &id in &ids[1..] {
+            world.remove_archetype_invariant(id);
+        }
+        // Removing invariants keeps the capacity of the list
+        assert!(world.archetype_invariants.raw_list.capacity() >= 64);
+        let removed = world.archetype_invariant_memory_usage();
+        assert!(removed < full);
+
+        world.shrink_archetype_invariants_to_fit();
+        assert_eq!(world.archetype_invariants.raw_list.capacity(), 1);
+        let shrunk = world.archetype_invariant_memory_usage();
+        assert!(shrunk < removed);
+        assert!(shrunk >= std::mem::size_of::<ArchetypeInvariants>());
+    }
+}
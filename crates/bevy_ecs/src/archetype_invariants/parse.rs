@@ -0,0 +1,398 @@
+//! Parsing of archetype invariants from the compact form they are displayed in, such as `AllOf(A, B) => NoneOf(C)`.
+
+use std::fmt;
+
+use bevy_utils::get_short_name;
+
+use super::{ComponentIdSet, Severity, UntypedArchetypeInvariant, UntypedArchetypeStatement};
+use crate::component::{ComponentId, Components, StorageType};
+
+/// The keywords of the statements that contain a plain list of components.
+const LIST_KEYWORDS: [&str; 6] = [
+    "AllOf",
+    "AtLeastOneOf",
+    "NoneOf",
+    "ExactlyOneOf",
+    "AtMostOneOf",
+    "Exactly",
+];
+
+impl UntypedArchetypeInvariant {
+    /// Parses an invariant from the form it is [displayed](UntypedArchetypeInvariant::display) in,
+    /// such as `AllOf(A, B) => NoneOf(C)`, resolving each component name against `components`.
+    ///
+    /// Every kind of [`UntypedArchetypeStatement`] is supported apart from the statements about component groups,
+    /// using the same keywords as its variant: `ExactlyNOf(2, [A, B, C])`, `SubsetOf([A, B], [A])`,
+    /// `ComponentCount(1..=3)`, `ResourcePresent(Time)`, `HasParent(Parent)`, `HasChildren(Children)`, `StorageType(SparseSet, [A])`, `Always`, `Empty`,
+    /// and the combinators `And(..)`, `Or(..)` and `Not(..)`.
+    /// Components may be named by their full type name, or by their short name if it is unambiguous.
+    /// The parsed invariant has [`Severity::Error`].
+    ///
+    /// This is intended for quick experiments and tests.
+    /// See [`World::parse_archetype_invariant`](crate::world::World::parse_archetype_invariant) for an example.
+    pub fn parse(
+        src: &str,
+        components: &Components,
+    ) -> Result<UntypedArchetypeInvariant, ArchetypeInvariantParseError> {
+        let mut parser = Parser {
+            tokens: tokenize(src)?,
+            position: 0,
+            end: src.len(),
+            components,
+        };
+        let predicate = parser.statement()?;
+        parser.expect(TokenKind::Arrow, "`=>`")?;
+        let consequence = parser.statement()?;
+        if let Some(token) = parser.peek() {
+            return Err(ArchetypeInvariantParseError::new(
+                token.offset,
+                format!("unexpected {} after the invariant", token.kind),
+            ));
+        }
+        Ok(UntypedArchetypeInvariant {
+            predicate,
+            consequence,
+            severity: Severity::Error,
+        })
+    }
+}
+
+/// An error returned by [`UntypedArchetypeInvariant::parse`] and
+/// [`World::parse_archetype_invariant`](crate::world::World::parse_archetype_invariant).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchetypeInvariantParseError {
+    /// The byte offset in the parsed string at which the error was found.
+    ///
+    /// This is the length of the string if it ended too early.
+    pub offset: usize,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl ArchetypeInvariantParseError {
+    fn new(offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::error::Error for ArchetypeInvariantParseError {}
+
+impl fmt::Display for ArchetypeInvariantParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TokenKind<'a> {
+    /// A keyword or a component name, which may contain `::` and generic arguments.
+    Name(&'a str),
+    Number(usize),
+    OpenParen,
+    CloseParen,
+    OpenBracket,
+    CloseBracket,
+    Comma,
+    Arrow,
+    DotDot,
+    Equals,
+}
+
+impl<'a> fmt::Display for TokenKind<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::Name(name) => write!(f, "`{name}`"),
+            TokenKind::Number(n) => write!(f, "`{n}`"),
+            TokenKind::OpenParen => write!(f, "`(`"),
+            TokenKind::CloseParen => write!(f, "`)`"),
+            TokenKind::OpenBracket => write!(f, "`[`"),
+            TokenKind::CloseBracket => write!(f, "`]`"),
+            TokenKind::Comma => write!(f, "`,`"),
+            TokenKind::Arrow => write!(f, "`=>`"),
+            TokenKind::DotDot => write!(f, "`..`"),
+            TokenKind::Equals => write!(f, "`=`"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Token<'a> {
+    offset: usize,
+    kind: TokenKind<'a>,
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | ':' | '<' | '>')
+}
+
+fn tokenize(src: &str) -> Result<Vec<Token<'_>>, ArchetypeInvariantParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = src.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        let kind = match c {
+            c if c.is_whitespace() => continue,
+            '(' => TokenKind::OpenParen,
+            ')' => TokenKind::CloseParen,
+            '[' => TokenKind::OpenBracket,
+            ']' => TokenKind::CloseBracket,
+            ',' => TokenKind::Comma,
+            '=' if src[offset..].starts_with("=>") => {
+                chars.next();
+                TokenKind::Arrow
+            }
+            '=' => TokenKind::Equals,
+            '.' if src[offset..].starts_with("..") => {
+                chars.next();
+                TokenKind::DotDot
+            }
+            c if is_name_char(c) => {
+                let mut end = offset + c.len_utf8();
+                while let Some(&(next_offset, next)) = chars.peek() {
+                    if !is_name_char(next) {
+                        break;
+                    }
+                    end = next_offset + next.len_utf8();
+                    chars.next();
+                }
+                let text = &src[offset..end];
+                match text.parse() {
+                    Ok(n) => TokenKind::Number(n),
+                    Err(_) => TokenKind::Name(text),
+                }
+            }
+            c => {
+                return Err(ArchetypeInvariantParseError::new(
+                    offset,
+                    format!("unexpected character `{c}`"),
+                ))
+            }
+        };
+        tokens.push(Token { offset, kind });
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    position: usize,
+    /// The length of the parsed string, which is the offset of errors about it ending too early.
+    end: usize,
+    components: &'a Components,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.position).copied()
+    }
+
+    /// Returns the next token, or an error describing what was `expected` at the end of the string.
+    fn next(&mut self, expected: &str) -> Result<Token<'a>, ArchetypeInvariantParseError> {
+        let token = self.peek().ok_or_else(|| {
+            ArchetypeInvariantParseError::new(
+                self.end,
+                format!("unexpected end of input, expected {expected}"),
+            )
+        })?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(
+        &mut self,
+        kind: TokenKind,
+        expected: &str,
+    ) -> Result<(), ArchetypeInvariantParseError> {
+        let token = self.next(expected)?;
+        if token.kind == kind {
+            Ok(())
+        } else {
+            Err(unexpected(token, expected))
+        }
+    }
+
+    /// Consumes the next token if it is of the given kind, returning whether it was.
+    fn eat(&mut self, kind: TokenKind) -> bool {
+        let found = matches!(self.peek(), Some(token) if token.kind == kind);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn number(&mut self) -> Result<usize, ArchetypeInvariantParseError> {
+        let token = self.next("a number")?;
+        match token.kind {
+            TokenKind::Number(n) => Ok(n),
+            _ => Err(unexpected(token, "a number")),
+        }
+    }
+
+    fn statement(&mut self) -> Result<UntypedArchetypeStatement, ArchetypeInvariantParseError> {
+        let token = self.next("a statement")?;
+        let keyword = match token.kind {
+            TokenKind::Name(keyword) => keyword,
+            _ => return Err(unexpected(token, "a statement")),
+        };
+        let statement = match keyword {
+            "Always" => return Ok(UntypedArchetypeStatement::Always),
+            "Empty" => return Ok(UntypedArchetypeStatement::Empty),
+            _ if LIST_KEYWORDS.contains(&keyword) => {
+                self.expect(TokenKind::OpenParen, "`(`")?;
+                let set = self.names(TokenKind::CloseParen)?;
+                return Ok(match keyword {
+                    "AllOf" => UntypedArchetypeStatement::AllOf(set),
+                    "AtLeastOneOf" => UntypedArchetypeStatement::AtLeastOneOf(set),
+                    "NoneOf" => UntypedArchetypeStatement::NoneOf(set),
+                    "ExactlyOneOf" => UntypedArchetypeStatement::ExactlyOneOf(set),
+                    "AtMostOneOf" => UntypedArchetypeStatement::AtMostOneOf(set),
+                    _ => UntypedArchetypeStatement::Exactly(set),
+                });
+            }
+            "ExactlyNOf" | "AtLeastNOf" | "AtMostNOf" => {
+                self.expect(TokenKind::OpenParen, "`(`")?;
+                let n = self.number()?;
+                self.expect(TokenKind::Comma, "`,`")?;
+                self.expect(TokenKind::OpenBracket, "`[`")?;
+                let set = self.names(TokenKind::CloseBracket)?;
+                match keyword {
+                    "ExactlyNOf" => UntypedArchetypeStatement::ExactlyNOf(set, n),
+                    "AtLeastNOf" => UntypedArchetypeStatement::AtLeastNOf(set, n),
+                    _ => UntypedArchetypeStatement::AtMostNOf(set, n),
+                }
+            }
+            "SubsetOf" => {
+                self.expect(TokenKind::OpenParen, "`(`")?;
+                self.expect(TokenKind::OpenBracket, "`[`")?;
+                let relevant = self.names(TokenKind::CloseBracket)?;
+                self.expect(TokenKind::Comma, "`,`")?;
+                self.expect(TokenKind::OpenBracket, "`[`")?;
+                let allowed = self.names(TokenKind::CloseBracket)?;
+                UntypedArchetypeStatement::SubsetOf { relevant, allowed }
+            }
+            "ComponentCount" => {
+                self.expect(TokenKind::OpenParen, "`(`")?;
+                let min = match self.peek() {
+                    Some(Token {
+                        kind: TokenKind::Number(_),
+                        ..
+                    }) => Some(self.number()?),
+                    _ => None,
+                };
+                self.expect(TokenKind::DotDot, "`..`")?;
+                let max = if self.eat(TokenKind::Equals) {
+                    Some(self.number()?)
+                } else {
+                    None
+                };
+                UntypedArchetypeStatement::ComponentCount { min, max }
+            }
+            "ResourcePresent" => {
+                self.expect(TokenKind::OpenParen, "`(`")?;
+                UntypedArchetypeStatement::ResourcePresent(self.name()?)
+            }
+            "HasParent" => {
+                self.expect(TokenKind::OpenParen, "`(`")?;
+                UntypedArchetypeStatement::HasParent(self.name()?)
+            }
+            "HasChildren" => {
+                self.expect(TokenKind::OpenParen, "`(`")?;
+                UntypedArchetypeStatement::HasChildren(self.name()?)
+            }
+            "StorageType" => {
+                self.expect(TokenKind::OpenParen, "`(`")?;
+                let token = self.next("a storage type")?;
+                let storage = match token.kind {
+                    TokenKind::Name("Table") => StorageType::Table,
+                    TokenKind::Name("SparseSet") => StorageType::SparseSet,
+                    _ => return Err(unexpected(token, "`Table` or `SparseSet`")),
+                };
+                self.expect(TokenKind::Comma, "`,`")?;
+                self.expect(TokenKind::OpenBracket, "`[`")?;
+                let set = self.names(TokenKind::CloseBracket)?;
+                UntypedArchetypeStatement::storage_type_ids(set, storage, self.components)
+            }
+            "And" | "Or" => {
+                self.expect(TokenKind::OpenParen, "`(`")?;
+                let mut statements = Vec::new();
+                if !self.eat(TokenKind::CloseParen) {
+                    loop {
+                        statements.push(self.statement()?);
+                        if self.eat(TokenKind::CloseParen) {
+                            break;
+                        }
+                        self.expect(TokenKind::Comma, "`,` or `)`")?;
+                    }
+                }
+                return Ok(if keyword == "And" {
+                    UntypedArchetypeStatement::And(statements)
+                } else {
+                    UntypedArchetypeStatement::Or(statements)
+                });
+            }
+            "Not" => {
+                self.expect(TokenKind::OpenParen, "`(`")?;
+                UntypedArchetypeStatement::Not(Box::new(self.statement()?))
+            }
+            _ => {
+                return Err(ArchetypeInvariantParseError::new(
+                    token.offset,
+                    format!("unknown statement `{keyword}`"),
+                ))
+            }
+        };
+        self.expect(TokenKind::CloseParen, "`)`")?;
+        Ok(statement)
+    }
+
+    /// Parses a comma-separated list of component names, followed by the `close` token.
+    fn names(&mut self, close: TokenKind) -> Result<ComponentIdSet, ArchetypeInvariantParseError> {
+        let mut set = ComponentIdSet::new();
+        if self.eat(close) {
+            return Ok(set);
+        }
+        loop {
+            set.insert(self.name()?);
+            if self.eat(close) {
+                return Ok(set);
+            }
+            self.expect(TokenKind::Comma, &format!("`,` or {close}"))?;
+        }
+    }
+
+    /// Parses the name of a component, and resolves it to its [`ComponentId`].
+    fn name(&mut self) -> Result<ComponentId, ArchetypeInvariantParseError> {
+        let token = self.next("a component name")?;
+        let name = match token.kind {
+            TokenKind::Name(name) => name,
+            _ => return Err(unexpected(token, "a component name")),
+        };
+        if let Some(id) = self.components.get_id_by_name(name) {
+            return Ok(id);
+        }
+        let mut matches = self
+            .components
+            .iter()
+            .filter(|info| get_short_name(info.name()) == name);
+        match (matches.next(), matches.next()) {
+            (Some(info), None) => Ok(info.id()),
+            (Some(_), Some(_)) => Err(ArchetypeInvariantParseError::new(
+                token.offset,
+                format!("the component name `{name}` is ambiguous, use the full type name instead"),
+            )),
+            (None, _) => Err(ArchetypeInvariantParseError::new(
+                token.offset,
+                format!("no component is registered with the name `{name}`"),
+            )),
+        }
+    }
+}
+
+fn unexpected(token: Token, expected: &str) -> ArchetypeInvariantParseError {
+    ArchetypeInvariantParseError::new(
+        token.offset,
+        format!("unexpected {}, expected {expected}", token.kind),
+    )
+}
@@ -0,0 +1,226 @@
+//! Randomized tests comparing [`UntypedArchetypeStatement::evaluate_with_resources`]
+//! against a naive reference implementation built on [`HashSet`]s,
+//! and checking the implications found by [`UntypedArchetypeStatement::subsumes`] against it.
+//!
+//! These run thousands of cases, so they are only compiled with the `archetype_invariant_property_tests` feature.
+
+use bevy_utils::HashSet;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    archetype_invariants::{
+        ComponentIdSet, GroupId, Severity, UntypedArchetypeInvariant, UntypedArchetypeStatement,
+    },
+    component::{ComponentId, StorageType},
+};
+
+/// The number of distinct components that random statements and archetypes are made of.
+///
+/// This is small enough that the random sets overlap often, so that every statement is regularly true and false.
+const COMPONENT_COUNT: usize = 12;
+const CASES: usize = 10_000;
+
+/// Evaluates `statement` by directly following the documentation of each variant.
+fn reference_evaluate(
+    statement: &UntypedArchetypeStatement,
+    component_ids: &HashSet<ComponentId>,
+    resources: &HashSet<ComponentId>,
+) -> bool {
+    let count = |set: &ComponentIdSet| set.iter().filter(|id| component_ids.contains(id)).count();
+    match statement {
+        UntypedArchetypeStatement::AllOf(set) => set.iter().all(|id| component_ids.contains(&id)),
+        UntypedArchetypeStatement::AtLeastOneOf(set) => {
+            set.iter().any(|id| component_ids.contains(&id))
+        }
+        UntypedArchetypeStatement::NoneOf(set) => !set.iter().any(|id| component_ids.contains(&id)),
+        UntypedArchetypeStatement::ExactlyOneOf(set) => count(set) == 1,
+        UntypedArchetypeStatement::AtMostOneOf(set) => count(set) <= 1,
+        UntypedArchetypeStatement::ExactlyNOf(set, n) => count(set) == *n,
+        UntypedArchetypeStatement::AtLeastNOf(set, n) => count(set) >= *n,
+        UntypedArchetypeStatement::AtMostNOf(set, n) => count(set) <= *n,
+        UntypedArchetypeStatement::Exactly(set) => {
+            set.iter().collect::<HashSet<_>>() == *component_ids
+        }
+        UntypedArchetypeStatement::SubsetOf { relevant, allowed } => component_ids
+            .iter()
+            .filter(|&&id| relevant.contains(id))
+            .all(|&id| allowed.contains(id)),
+        UntypedArchetypeStatement::ComponentCount { min, max } => {
+            !matches!(min, Some(min) if component_ids.len() < *min)
+                && !matches!(max, Some(max) if component_ids.len() > *max)
+        }
+        UntypedArchetypeStatement::ResourcePresent(resource) => resources.contains(resource),
+        UntypedArchetypeStatement::HasParent(id) | UntypedArchetypeStatement::HasChildren(id) => {
+            component_ids.contains(id)
+        }
+        UntypedArchetypeStatement::StorageType { mismatched, .. } => {
+            !mismatched.iter().any(|id| component_ids.contains(&id))
+        }
+        // Unresolved groups are treated as empty
+        UntypedArchetypeStatement::GroupAllOf(_) => true,
+        UntypedArchetypeStatement::GroupAtLeastOneOf(_) => false,
+        UntypedArchetypeStatement::Always => true,
+        UntypedArchetypeStatement::Empty => component_ids.is_empty(),
+        UntypedArchetypeStatement::And(statements) => statements
+            .iter()
+            .all(|statement| reference_evaluate(statement, component_ids, resources)),
+        UntypedArchetypeStatement::Or(statements) => statements
+            .iter()
+            .any(|statement| reference_evaluate(statement, component_ids, resources)),
+        UntypedArchetypeStatement::Not(statement) => {
+            !reference_evaluate(statement, component_ids, resources)
+        }
+    }
+}
+
+fn random_ids(rng: &mut StdRng, max_len: usize) -> HashSet<ComponentId> {
+    let len = rng.gen_range(0..=max_len);
+    (0..len)
+        .map(|_| ComponentId::new(rng.gen_range(0..COMPONENT_COUNT)))
+        .collect()
+}
+
+fn random_set(rng: &mut StdRng) -> ComponentIdSet {
+    random_ids(rng, 5).into()
+}
+
+fn random_bound(rng: &mut StdRng) -> Option<usize> {
+    rng.gen_bool(0.7).then(|| rng.gen_range(0..6))
+}
+
+/// Generates a random statement of any kind, nesting combinators at most `depth` levels deep.
+fn random_statement(rng: &mut StdRng, depth: usize) -> UntypedArchetypeStatement {
+    let kinds = if depth == 0 { 18 } else { 21 };
+    match rng.gen_range(0..kinds) {
+        0 => UntypedArchetypeStatement::AllOf(random_set(rng)),
+        1 => UntypedArchetypeStatement::AtLeastOneOf(random_set(rng)),
+        2 => UntypedArchetypeStatement::NoneOf(random_set(rng)),
+        3 => UntypedArchetypeStatement::ExactlyOneOf(random_set(rng)),
+        4 => UntypedArchetypeStatement::AtMostOneOf(random_set(rng)),
+        5 => UntypedArchetypeStatement::ExactlyNOf(random_set(rng), rng.gen_range(0..5)),
+        6 => UntypedArchetypeStatement::AtLeastNOf(random_set(rng), rng.gen_range(0..5)),
+        7 => UntypedArchetypeStatement::AtMostNOf(random_set(rng), rng.gen_range(0..5)),
+        8 => UntypedArchetypeStatement::Exactly(random_set(rng)),
+        9 => UntypedArchetypeStatement::SubsetOf {
+            relevant: random_set(rng),
+            allowed: random_set(rng),
+        },
+        10 => UntypedArchetypeStatement::ComponentCount {
+            min: random_bound(rng),
+            max: random_bound(rng),
+        },
+        11 => UntypedArchetypeStatement::ResourcePresent(ComponentId::new(
+            rng.gen_range(0..COMPONENT_COUNT),
+        )),
+        12 => {
+            let components = random_set(rng);
+            let mismatched = components.iter().filter(|_| rng.gen_bool(0.5)).collect();
+            UntypedArchetypeStatement::StorageType {
+                components,
+                storage: StorageType::SparseSet,
+                mismatched,
+            }
+        }
+        13 => UntypedArchetypeStatement::GroupAllOf(GroupId::new(0)),
+        14 => UntypedArchetypeStatement::GroupAtLeastOneOf(GroupId::new(0)),
+        15 => UntypedArchetypeStatement::Always,
+        16 => UntypedArchetypeStatement::Empty,
+        17 => {
+            let id = ComponentId::new(rng.gen_range(0..COMPONENT_COUNT));
+            if rng.gen_bool(0.5) {
+                UntypedArchetypeStatement::HasParent(id)
+            } else {
+                UntypedArchetypeStatement::HasChildren(id)
+            }
+        }
+        18 => UntypedArchetypeStatement::And(random_statements(rng, depth - 1)),
+        19 => UntypedArchetypeStatement::Or(random_statements(rng, depth - 1)),
+        _ => UntypedArchetypeStatement::Not(Box::new(random_statement(rng, depth - 1))),
+    }
+}
+
+fn random_statements(rng: &mut StdRng, depth: usize) -> Vec<UntypedArchetypeStatement> {
+    let len = rng.gen_range(0..4);
+    (0..len).map(|_| random_statement(rng, depth)).collect()
+}
+
+/// Evaluates `invariant` against a random archetype with both implementations,
+/// asserting that they agree, and returns whether the archetype upholds the invariant.
+fn matches_random_archetype(invariant: &UntypedArchetypeInvariant, rng: &mut StdRng) -> bool {
+    let component_ids = random_ids(rng, 8);
+    let resources = random_ids(rng, 2);
+    let reference = !reference_evaluate(&invariant.predicate, &component_ids, &resources)
+        || reference_evaluate(&invariant.consequence, &component_ids, &resources);
+    let evaluated =
+        invariant.evaluate_with_resources(&component_ids.clone().into(), &resources.into());
+    assert_eq!(
+        evaluated, reference,
+        "{invariant:?} disagrees with the reference implementation for {component_ids:?}"
+    );
+    evaluated
+}
+
+#[test]
+fn evaluate_matches_reference() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let (mut true_count, mut false_count) = (0, 0);
+    for _ in 0..CASES {
+        let statement = random_statement(&mut rng, 2);
+        let component_ids = random_ids(&mut rng, 8);
+        let resources = random_ids(&mut rng, 2);
+        let evaluated = statement
+            .evaluate_with_resources(&component_ids.clone().into(), &resources.clone().into());
+        assert_eq!(
+            evaluated,
+            reference_evaluate(&statement, &component_ids, &resources),
+            "{statement:?} disagrees with the reference implementation for {component_ids:?}"
+        );
+        if evaluated {
+            true_count += 1;
+        } else {
+            false_count += 1;
+        }
+    }
+    // Guard against generators that only ever produce trivial statements
+    assert!(true_count > CASES / 10 && false_count > CASES / 10);
+}
+
+#[test]
+fn invariants_match_random_archetypes() {
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..CASES / 10 {
+        let invariant = UntypedArchetypeInvariant {
+            predicate: random_statement(&mut rng, 1),
+            consequence: random_statement(&mut rng, 1),
+            severity: Severity::Error,
+        };
+        for _ in 0..10 {
+            matches_random_archetype(&invariant, &mut rng);
+        }
+    }
+}
+
+#[test]
+fn subsumes_is_sound() {
+    let mut rng = StdRng::seed_from_u64(97);
+    let mut decided = 0;
+    for _ in 0..CASES {
+        let statement = random_statement(&mut rng, 1);
+        let other = random_statement(&mut rng, 1);
+        if !statement.subsumes(&other) {
+            continue;
+        }
+        decided += 1;
+        for _ in 0..10 {
+            let component_ids = random_ids(&mut rng, 8);
+            let resources = random_ids(&mut rng, 2);
+            assert!(
+                !reference_evaluate(&statement, &component_ids, &resources)
+                    || reference_evaluate(&other, &component_ids, &resources),
+                "{statement:?} does not imply {other:?} for {component_ids:?}"
+            );
+        }
+    }
+    // Guard against an implementation that never decides anything
+    assert!(decided > CASES / 100);
+}
@@ -0,0 +1,377 @@
+use std::fmt;
+
+use serde::{de::DeserializeSeed, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    archetype_invariants::{
+        ComponentIdSet, GroupId, Severity, UntypedArchetypeInvariant, UntypedArchetypeStatement,
+    },
+    component::{ComponentId, Components, StorageType},
+};
+
+/// Serializes an [`UntypedArchetypeInvariant`], identifying each component by its name.
+///
+/// [`ComponentId`]s are only meaningful within a single [`World`](crate::world::World),
+/// so the serialized form refers to components by their full type name instead.
+/// Use [`ArchetypeInvariantDeserializer`] or
+/// [`World::deserialize_archetype_invariant`](crate::world::World::deserialize_archetype_invariant)
+/// to load it again.
+pub struct ArchetypeInvariantSerializer<'a> {
+    pub invariant: &'a UntypedArchetypeInvariant,
+    pub components: &'a Components,
+}
+
+impl<'a> ArchetypeInvariantSerializer<'a> {
+    pub fn new(invariant: &'a UntypedArchetypeInvariant, components: &'a Components) -> Self {
+        ArchetypeInvariantSerializer {
+            invariant,
+            components,
+        }
+    }
+}
+
+impl<'a> Serialize for ArchetypeInvariantSerializer<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let name_of = |id: ComponentId| {
+            self.components
+                .get_info(id)
+                .map(|info| info.name().to_string())
+                .ok_or_else(|| serde::ser::Error::custom(format!("{id:?} is not registered")))
+        };
+        NamedArchetypeInvariant {
+            predicate: NamedArchetypeStatement::new(&self.invariant.predicate, &name_of)?,
+            consequence: NamedArchetypeStatement::new(&self.invariant.consequence, &name_of)?,
+            severity: self.invariant.severity,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserializes an [`UntypedArchetypeInvariant`] that was serialized by [`ArchetypeInvariantSerializer`],
+/// resolving each component name to the [`ComponentId`] it has in `components`.
+///
+/// Deserialization fails if any of the named components has not been registered.
+pub struct ArchetypeInvariantDeserializer<'a> {
+    pub components: &'a Components,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for ArchetypeInvariantDeserializer<'a> {
+    type Value = UntypedArchetypeInvariant;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        NamedArchetypeInvariant::deserialize(deserializer)?
+            .resolve(self.components)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// An error that occurs when loading an archetype invariant with
+/// [`World::deserialize_archetype_invariant`](crate::world::World::deserialize_archetype_invariant).
+#[derive(Debug)]
+pub enum ArchetypeInvariantDeserializeError {
+    /// The input is not a valid RON representation of an archetype invariant.
+    Ron(ron::error::SpannedError),
+    /// A component with the given name is not registered in the world.
+    UnknownComponent(String),
+}
+
+impl fmt::Display for ArchetypeInvariantDeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchetypeInvariantDeserializeError::Ron(error) => {
+                write!(f, "invalid archetype invariant: {error}")
+            }
+            ArchetypeInvariantDeserializeError::UnknownComponent(name) => {
+                write!(f, "no component named `{name}` is registered")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArchetypeInvariantDeserializeError {}
+
+/// Parses an [`UntypedArchetypeInvariant`] from RON, resolving component names with `components`.
+pub(crate) fn deserialize_archetype_invariant(
+    ron: &str,
+    components: &Components,
+) -> Result<UntypedArchetypeInvariant, ArchetypeInvariantDeserializeError> {
+    ron::from_str::<NamedArchetypeInvariant>(ron)
+        .map_err(ArchetypeInvariantDeserializeError::Ron)?
+        .resolve(components)
+}
+
+/// The serialized form of an [`UntypedArchetypeInvariant`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "ArchetypeInvariant")]
+struct NamedArchetypeInvariant {
+    predicate: NamedArchetypeStatement,
+    consequence: NamedArchetypeStatement,
+    #[serde(default)]
+    severity: Severity,
+}
+
+impl NamedArchetypeInvariant {
+    fn resolve(
+        self,
+        components: &Components,
+    ) -> Result<UntypedArchetypeInvariant, ArchetypeInvariantDeserializeError> {
+        Ok(UntypedArchetypeInvariant {
+            predicate: self.predicate.resolve(components)?,
+            consequence: self.consequence.resolve(components)?,
+            severity: self.severity,
+        })
+    }
+}
+
+/// The serialized form of an [`UntypedArchetypeStatement`], with each component identified by its name.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "ArchetypeStatement")]
+enum NamedArchetypeStatement {
+    AllOf(Vec<String>),
+    AtLeastOneOf(Vec<String>),
+    NoneOf(Vec<String>),
+    ExactlyOneOf(Vec<String>),
+    AtMostOneOf(Vec<String>),
+    ExactlyNOf(Vec<String>, usize),
+    AtLeastNOf(Vec<String>, usize),
+    AtMostNOf(Vec<String>, usize),
+    Exactly(Vec<String>),
+    SubsetOf {
+        relevant: Vec<String>,
+        allowed: Vec<String>,
+    },
+    ComponentCount {
+        min: Option<usize>,
+        max: Option<usize>,
+    },
+    ResourcePresent(String),
+    HasParent(String),
+    HasChildren(String),
+    StorageType {
+        components: Vec<String>,
+        storage: StorageType,
+    },
+    // Groups are identified by their index, so they must be registered in the same order in the loading world
+    GroupAllOf(usize),
+    GroupAtLeastOneOf(usize),
+    Always,
+    Empty,
+    And(Vec<NamedArchetypeStatement>),
+    Or(Vec<NamedArchetypeStatement>),
+    Not(Box<NamedArchetypeStatement>),
+}
+
+impl NamedArchetypeStatement {
+    fn new<E>(
+        statement: &UntypedArchetypeStatement,
+        name_of: &dyn Fn(ComponentId) -> Result<String, E>,
+    ) -> Result<Self, E> {
+        let names = |set: &ComponentIdSet| set.iter().map(name_of).collect::<Result<_, _>>();
+        let statements = |statements: &[UntypedArchetypeStatement]| {
+            statements
+                .iter()
+                .map(|statement| Self::new(statement, name_of))
+                .collect::<Result<_, _>>()
+        };
+        Ok(match statement {
+            UntypedArchetypeStatement::AllOf(set) => Self::AllOf(names(set)?),
+            UntypedArchetypeStatement::AtLeastOneOf(set) => Self::AtLeastOneOf(names(set)?),
+            UntypedArchetypeStatement::NoneOf(set) => Self::NoneOf(names(set)?),
+            UntypedArchetypeStatement::ExactlyOneOf(set) => Self::ExactlyOneOf(names(set)?),
+            UntypedArchetypeStatement::AtMostOneOf(set) => Self::AtMostOneOf(names(set)?),
+            UntypedArchetypeStatement::ExactlyNOf(set, n) => Self::ExactlyNOf(names(set)?, *n),
+            UntypedArchetypeStatement::AtLeastNOf(set, n) => Self::AtLeastNOf(names(set)?, *n),
+            UntypedArchetypeStatement::AtMostNOf(set, n) => Self::AtMostNOf(names(set)?, *n),
+            UntypedArchetypeStatement::Exactly(set) => Self::Exactly(names(set)?),
+            UntypedArchetypeStatement::SubsetOf { relevant, allowed } => Self::SubsetOf {
+                relevant: names(relevant)?,
+                allowed: names(allowed)?,
+            },
+            UntypedArchetypeStatement::ComponentCount { min, max } => Self::ComponentCount {
+                min: *min,
+                max: *max,
+            },
+            UntypedArchetypeStatement::ResourcePresent(id) => Self::ResourcePresent(name_of(*id)?),
+            UntypedArchetypeStatement::HasParent(id) => Self::HasParent(name_of(*id)?),
+            UntypedArchetypeStatement::HasChildren(id) => Self::HasChildren(name_of(*id)?),
+            UntypedArchetypeStatement::StorageType {
+                components,
+                storage,
+                ..
+            } => Self::StorageType {
+                components: names(components)?,
+                storage: *storage,
+            },
+            UntypedArchetypeStatement::GroupAllOf(group) => Self::GroupAllOf(group.index()),
+            UntypedArchetypeStatement::GroupAtLeastOneOf(group) => {
+                Self::GroupAtLeastOneOf(group.index())
+            }
+            UntypedArchetypeStatement::Always => Self::Always,
+            UntypedArchetypeStatement::Empty => Self::Empty,
+            UntypedArchetypeStatement::And(inner) => Self::And(statements(inner)?),
+            UntypedArchetypeStatement::Or(inner) => Self::Or(statements(inner)?),
+            UntypedArchetypeStatement::Not(inner) => {
+                Self::Not(Box::new(Self::new(inner, name_of)?))
+            }
+        })
+    }
+
+    fn resolve(
+        self,
+        components: &Components,
+    ) -> Result<UntypedArchetypeStatement, ArchetypeInvariantDeserializeError> {
+        let id = |name: String| {
+            components
+                .get_id_by_name(&name)
+                .ok_or(ArchetypeInvariantDeserializeError::UnknownComponent(name))
+        };
+        let set = |names: Vec<String>| {
+            names
+                .into_iter()
+                .map(id)
+                .collect::<Result<ComponentIdSet, _>>()
+        };
+        let statements = |statements: Vec<NamedArchetypeStatement>| {
+            statements
+                .into_iter()
+                .map(|statement| statement.resolve(components))
+                .collect::<Result<_, _>>()
+        };
+        Ok(match self {
+            Self::AllOf(names) => UntypedArchetypeStatement::AllOf(set(names)?),
+            Self::AtLeastOneOf(names) => UntypedArchetypeStatement::AtLeastOneOf(set(names)?),
+            Self::NoneOf(names) => UntypedArchetypeStatement::NoneOf(set(names)?),
+            Self::ExactlyOneOf(names) => UntypedArchetypeStatement::ExactlyOneOf(set(names)?),
+            Self::AtMostOneOf(names) => UntypedArchetypeStatement::AtMostOneOf(set(names)?),
+            Self::ExactlyNOf(names, n) => UntypedArchetypeStatement::ExactlyNOf(set(names)?, n),
+            Self::AtLeastNOf(names, n) => UntypedArchetypeStatement::AtLeastNOf(set(names)?, n),
+            Self::AtMostNOf(names, n) => UntypedArchetypeStatement::AtMostNOf(set(names)?, n),
+            Self::Exactly(names) => UntypedArchetypeStatement::Exactly(set(names)?),
+            Self::SubsetOf { relevant, allowed } => UntypedArchetypeStatement::SubsetOf {
+                relevant: set(relevant)?,
+                allowed: set(allowed)?,
+            },
+            Self::ComponentCount { min, max } => {
+                UntypedArchetypeStatement::ComponentCount { min, max }
+            }
+            Self::ResourcePresent(name) => UntypedArchetypeStatement::ResourcePresent(id(name)?),
+            Self::HasParent(name) => UntypedArchetypeStatement::HasParent(id(name)?),
+            Self::HasChildren(name) => UntypedArchetypeStatement::HasChildren(id(name)?),
+            // The mismatched components are not serialized, since storage types may differ between worlds
+            Self::StorageType {
+                components: names,
+                storage,
+            } => UntypedArchetypeStatement::storage_type_ids(set(names)?, storage, components),
+            Self::GroupAllOf(index) => UntypedArchetypeStatement::GroupAllOf(GroupId::new(index)),
+            Self::GroupAtLeastOneOf(index) => {
+                UntypedArchetypeStatement::GroupAtLeastOneOf(GroupId::new(index))
+            }
+            Self::Always => UntypedArchetypeStatement::Always,
+            Self::Empty => UntypedArchetypeStatement::Empty,
+            Self::And(inner) => UntypedArchetypeStatement::And(statements(inner)?),
+            Self::Or(inner) => UntypedArchetypeStatement::Or(statements(inner)?),
+            Self::Not(inner) => {
+                UntypedArchetypeStatement::Not(Box::new(inner.resolve(components)?))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::DeserializeSeed;
+
+    use crate as bevy_ecs;
+    use crate::{
+        archetype_invariants::{
+            ArchetypeInvariant, ArchetypeInvariantDeserializeError, ArchetypeInvariantDeserializer,
+            ArchetypeInvariantSerializer, ArchetypeStatement,
+        },
+        component::Component,
+        world::World,
+    };
+
+    #[derive(Component)]
+    struct A;
+
+    #[derive(Component)]
+    struct B;
+
+    #[derive(Component)]
+    struct C;
+
+    #[test]
+    fn round_trip() {
+        let mut world = World::new();
+        let invariant = ArchetypeInvariant::<(A, B), (C,)> {
+            predicate: ArchetypeStatement::at_least_one_of()
+                .and(ArchetypeStatement::exactly_n_of(1)),
+            consequence: ArchetypeStatement::all_of()
+                .not()
+                .or(ArchetypeStatement::subset_of::<(A, C)>()),
+        }
+        .into_untyped(&mut world);
+
+        let ron = ron::to_string(&ArchetypeInvariantSerializer::new(
+            &invariant,
+            world.components(),
+        ))
+        .unwrap();
+        assert!(ron.contains(std::any::type_name::<C>()));
+
+        // Component ids differ between worlds, but names do not
+        let mut other_world = World::new();
+        other_world.init_component::<C>();
+        other_world.init_component::<B>();
+        other_world.init_component::<A>();
+        let deserialized = other_world.deserialize_archetype_invariant(&ron).unwrap();
+        let expected = ArchetypeInvariant::<(A, B), (C,)> {
+            predicate: ArchetypeStatement::at_least_one_of()
+                .and(ArchetypeStatement::exactly_n_of(1)),
+            consequence: ArchetypeStatement::all_of()
+                .not()
+                .or(ArchetypeStatement::subset_of::<(A, C)>()),
+        }
+        .into_untyped(&mut other_world);
+        assert_eq!(deserialized, expected);
+
+        let mut deserializer = ron::Deserializer::from_str(&ron).unwrap();
+        let seeded = ArchetypeInvariantDeserializer {
+            components: other_world.components(),
+        }
+        .deserialize(&mut deserializer)
+        .unwrap();
+        assert_eq!(seeded, expected);
+    }
+
+    #[test]
+    fn unknown_component() {
+        let mut world = World::new();
+        world.init_component::<A>();
+        let ron = format!(
+            "(predicate: AllOf([\"{}\"]), consequence: NoneOf([\"{}\"]))",
+            std::any::type_name::<A>(),
+            std::any::type_name::<B>(),
+        );
+
+        match world.deserialize_archetype_invariant(&ron) {
+            Err(ArchetypeInvariantDeserializeError::UnknownComponent(name)) => {
+                assert_eq!(name, std::any::type_name::<B>());
+            }
+            other => panic!("expected an unknown component error, got {other:?}"),
+        }
+
+        world.init_component::<B>();
+        assert!(world.deserialize_archetype_invariant(&ron).is_ok());
+        assert!(matches!(
+            world.deserialize_archetype_invariant("(predicate: AllOf(A))"),
+            Err(ArchetypeInvariantDeserializeError::Ron(_))
+        ));
+    }
+}
@@ -0,0 +1,378 @@
+//! Descriptions of archetype invariants in terms of component names, for tools that build invariants at runtime.
+
+use super::{
+    ArchetypeInvariant, ArchetypeStatementKind, ComponentIdSet, Severity, UnknownComponentNames,
+    UntypedArchetypeInvariant, UntypedArchetypeStatement,
+};
+use crate::{
+    bundle::Bundle,
+    component::{ComponentId, Components, StorageType},
+    world::World,
+};
+
+/// An archetype invariant described by the names of its components, rather than by [`Bundle`] types or [`ComponentId`]s.
+///
+/// This is meant for tools such as editors, which let users assemble invariants at runtime
+/// and cannot name component types in Rust. Every field is public, so a spec can be built and modified freely,
+/// then turned into an [`UntypedArchetypeInvariant`] for a particular world with [`ArchetypeInvariantSpec::compile`].
+/// Existing invariants can be turned back into a spec with [`UntypedArchetypeInvariant::to_spec`].
+///
+/// ```
+/// # use bevy_ecs::{archetype_invariants::*, prelude::*};
+/// #[derive(Component)]
+/// struct Player;
+/// #[derive(Component)]
+/// struct Health;
+///
+/// let mut world = World::new();
+/// world.init_component::<Player>();
+/// world.init_component::<Health>();
+///
+/// let spec = ArchetypeInvariantSpec::new(
+///     ArchetypeStatementSpec::components(ArchetypeStatementKind::AllOf, [std::any::type_name::<Player>()]),
+///     ArchetypeStatementSpec::components(ArchetypeStatementKind::AllOf, [std::any::type_name::<Health>()]),
+/// );
+/// let invariant = spec.compile(&mut world).unwrap();
+/// assert_eq!(
+///     invariant,
+///     ArchetypeInvariant::<(Player,), (Health,)>::requires().into_untyped(&mut world)
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchetypeInvariantSpec {
+    /// Defines which entities this invariant applies to.
+    pub predicate: ArchetypeStatementSpec,
+    /// Defines what must be true for the entities that this invariant applies to.
+    pub consequence: ArchetypeStatementSpec,
+    /// How violations of the compiled invariant are reported.
+    pub severity: Severity,
+}
+
+impl ArchetypeInvariantSpec {
+    /// Creates a new spec from a predicate and a consequence, with [`Severity::Error`].
+    pub fn new(predicate: ArchetypeStatementSpec, consequence: ArchetypeStatementSpec) -> Self {
+        ArchetypeInvariantSpec {
+            predicate,
+            consequence,
+            severity: Severity::Error,
+        }
+    }
+
+    /// Resolves the names in this spec against `world`, returning the [`UntypedArchetypeInvariant`] it describes.
+    ///
+    /// Components and resources must be named by [`ComponentInfo::name`](crate::component::ComponentInfo::name) exactly.
+    /// If any of them is not registered, nothing is changed and an error listing every unknown name is returned.
+    /// Component groups that have not been registered yet are registered without any members,
+    /// since their members can be added at any time.
+    pub fn compile(
+        &self,
+        world: &mut World,
+    ) -> Result<UntypedArchetypeInvariant, UnknownComponentNames> {
+        let mut unknown = Vec::new();
+        self.predicate
+            .find_unknown_names(world.components(), &mut unknown);
+        self.consequence
+            .find_unknown_names(world.components(), &mut unknown);
+        if !unknown.is_empty() {
+            return Err(UnknownComponentNames { names: unknown });
+        }
+        Ok(UntypedArchetypeInvariant {
+            predicate: self.predicate.lower(world),
+            consequence: self.consequence.lower(world),
+            severity: self.severity,
+        })
+    }
+}
+
+/// An archetype statement described by the names of its components, used by [`ArchetypeInvariantSpec`].
+///
+/// Each variant describes the [`UntypedArchetypeStatement`] of the same name,
+/// apart from [`ArchetypeStatementSpec::Components`], which describes any statement about a single list of components.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArchetypeStatementSpec {
+    /// A statement of the given kind about the components with the given names.
+    Components(ArchetypeStatementKind, Vec<String>),
+    /// Describes an [`UntypedArchetypeStatement::SubsetOf`].
+    SubsetOf {
+        /// The names of the components that are restricted.
+        relevant: Vec<String>,
+        /// The names of the restricted components that are allowed.
+        allowed: Vec<String>,
+    },
+    /// Describes an [`UntypedArchetypeStatement::ComponentCount`].
+    ComponentCount {
+        /// The minimum number of components, if any.
+        min: Option<usize>,
+        /// The maximum number of components, if any.
+        max: Option<usize>,
+    },
+    /// Describes an [`UntypedArchetypeStatement::ResourcePresent`] for the resource with the given name.
+    ResourcePresent(String),
+    /// Describes an [`UntypedArchetypeStatement::HasParent`] for the parent component with the given name.
+    HasParent(String),
+    /// Describes an [`UntypedArchetypeStatement::HasChildren`] for the children component with the given name.
+    HasChildren(String),
+    /// Describes an [`UntypedArchetypeStatement::StorageType`].
+    StorageType {
+        /// The names of the components whose storage type is checked.
+        components: Vec<String>,
+        /// The storage type these components must use.
+        storage: StorageType,
+    },
+    /// Describes an [`UntypedArchetypeStatement::GroupAllOf`] for the component group with the given name.
+    GroupAllOf(String),
+    /// Describes an [`UntypedArchetypeStatement::GroupAtLeastOneOf`] for the component group with the given name.
+    GroupAtLeastOneOf(String),
+    /// Describes an [`UntypedArchetypeStatement::Always`].
+    Always,
+    /// Describes an [`UntypedArchetypeStatement::Empty`].
+    Empty,
+    /// Describes an [`UntypedArchetypeStatement::And`].
+    And(Vec<ArchetypeStatementSpec>),
+    /// Describes an [`UntypedArchetypeStatement::Or`].
+    Or(Vec<ArchetypeStatementSpec>),
+    /// Describes an [`UntypedArchetypeStatement::Not`].
+    Not(Box<ArchetypeStatementSpec>),
+}
+
+impl ArchetypeStatementSpec {
+    /// Creates a statement of the given kind about the components with the given names.
+    pub fn components(
+        kind: ArchetypeStatementKind,
+        names: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        ArchetypeStatementSpec::Components(kind, names.into_iter().map(Into::into).collect())
+    }
+
+    /// Pushes every component or resource name in this statement that is not registered in `components` to `unknown`.
+    fn find_unknown_names(&self, components: &Components, unknown: &mut Vec<String>) {
+        let check = |names: &[String], unknown: &mut Vec<String>| {
+            unknown.extend(
+                names
+                    .iter()
+                    .filter(|name| components.get_id_by_name(name).is_none())
+                    .cloned(),
+            );
+        };
+        match self {
+            ArchetypeStatementSpec::Components(_, names)
+            | ArchetypeStatementSpec::StorageType {
+                components: names, ..
+            } => check(names, unknown),
+            ArchetypeStatementSpec::SubsetOf { relevant, allowed } => {
+                check(relevant, unknown);
+                check(allowed, unknown);
+            }
+            ArchetypeStatementSpec::ResourcePresent(name)
+            | ArchetypeStatementSpec::HasParent(name)
+            | ArchetypeStatementSpec::HasChildren(name) => {
+                check(std::slice::from_ref(name), unknown)
+            }
+            ArchetypeStatementSpec::And(statements) | ArchetypeStatementSpec::Or(statements) => {
+                for statement in statements {
+                    statement.find_unknown_names(components, unknown);
+                }
+            }
+            ArchetypeStatementSpec::Not(statement) => {
+                statement.find_unknown_names(components, unknown);
+            }
+            ArchetypeStatementSpec::ComponentCount { .. }
+            | ArchetypeStatementSpec::GroupAllOf(_)
+            | ArchetypeStatementSpec::GroupAtLeastOneOf(_)
+            | ArchetypeStatementSpec::Always
+            | ArchetypeStatementSpec::Empty => {}
+        }
+    }
+
+    /// Converts this statement into an [`UntypedArchetypeStatement`],
+    /// registering any component groups that do not exist yet.
+    ///
+    /// Every name must have been checked with [`ArchetypeStatementSpec::find_unknown_names`] first.
+    fn lower(&self, world: &mut World) -> UntypedArchetypeStatement {
+        let ids = |world: &World, names: &[String]| -> ComponentIdSet {
+            names
+                .iter()
+                .filter_map(|name| world.components().get_id_by_name(name))
+                .collect()
+        };
+        let group = |world: &mut World, name: &str| match world
+            .archetype_invariants
+            .component_group(name)
+        {
+            Some(group) => group,
+            None => world
+                .archetype_invariants
+                .register_component_group(name.to_string(), std::iter::empty()),
+        };
+        match self {
+            ArchetypeStatementSpec::Components(kind, names) => kind.with_ids(ids(world, names)),
+            ArchetypeStatementSpec::SubsetOf { relevant, allowed } => {
+                UntypedArchetypeStatement::SubsetOf {
+                    relevant: ids(world, relevant),
+                    allowed: ids(world, allowed),
+                }
+            }
+            ArchetypeStatementSpec::ComponentCount { min, max } => {
+                UntypedArchetypeStatement::ComponentCount {
+                    min: *min,
+                    max: *max,
+                }
+            }
+            ArchetypeStatementSpec::ResourcePresent(name) => {
+                let id = world.components().get_id_by_name(name).unwrap();
+                UntypedArchetypeStatement::ResourcePresent(id)
+            }
+            ArchetypeStatementSpec::HasParent(name) => {
+                let id = world.components().get_id_by_name(name).unwrap();
+                UntypedArchetypeStatement::HasParent(id)
+            }
+            ArchetypeStatementSpec::HasChildren(name) => {
+                let id = world.components().get_id_by_name(name).unwrap();
+                UntypedArchetypeStatement::HasChildren(id)
+            }
+            ArchetypeStatementSpec::StorageType {
+                components,
+                storage,
+            } => UntypedArchetypeStatement::storage_type_ids(
+                ids(world, components),
+                *storage,
+                world.components(),
+            ),
+            ArchetypeStatementSpec::GroupAllOf(name) => {
+                UntypedArchetypeStatement::GroupAllOf(group(world, name))
+            }
+            ArchetypeStatementSpec::GroupAtLeastOneOf(name) => {
+                UntypedArchetypeStatement::GroupAtLeastOneOf(group(world, name))
+            }
+            ArchetypeStatementSpec::Always => UntypedArchetypeStatement::Always,
+            ArchetypeStatementSpec::Empty => UntypedArchetypeStatement::Empty,
+            ArchetypeStatementSpec::And(statements) => UntypedArchetypeStatement::And(
+                statements
+                    .iter()
+                    .map(|statement| statement.lower(world))
+                    .collect(),
+            ),
+            ArchetypeStatementSpec::Or(statements) => UntypedArchetypeStatement::Or(
+                statements
+                    .iter()
+                    .map(|statement| statement.lower(world))
+                    .collect(),
+            ),
+            ArchetypeStatementSpec::Not(statement) => {
+                UntypedArchetypeStatement::Not(Box::new(statement.lower(world)))
+            }
+        }
+    }
+}
+
+impl<B1: Bundle, B2: Bundle> ArchetypeInvariant<B1, B2> {
+    /// Describes this invariant by the names of its components, registering them in `world` if needed.
+    ///
+    /// See [`ArchetypeInvariantSpec`].
+    pub fn into_spec(self, world: &mut World) -> ArchetypeInvariantSpec {
+        self.into_untyped(world).to_spec(world)
+    }
+}
+
+impl UntypedArchetypeInvariant {
+    /// Describes this invariant by the names of its components and component groups, as registered in `world`.
+    ///
+    /// Compiling the result with [`ArchetypeInvariantSpec::compile`] in the same world returns an equal invariant.
+    /// Components that are not registered in `world` are named by their [`ComponentId`], and cannot be compiled again.
+    pub fn to_spec(&self, world: &World) -> ArchetypeInvariantSpec {
+        ArchetypeInvariantSpec {
+            predicate: statement_spec(&self.predicate, world),
+            consequence: statement_spec(&self.consequence, world),
+            severity: self.severity,
+        }
+    }
+}
+
+/// Describes `statement` by the names of its components and component groups, as registered in `world`.
+fn statement_spec(statement: &UntypedArchetypeStatement, world: &World) -> ArchetypeStatementSpec {
+    let name = |id: ComponentId| match world.components().get_info(id) {
+        Some(info) => info.name().to_string(),
+        None => format!("{id:?}"),
+    };
+    let names = |set: &ComponentIdSet| set.iter().map(name).collect();
+    let group_name = |group| {
+        world
+            .archetype_invariants
+            .component_group_name(group)
+            .map_or_else(|| format!("#{}", group.index()), str::to_string)
+    };
+    let list = |kind, set: &ComponentIdSet| ArchetypeStatementSpec::Components(kind, names(set));
+    let specs = |statements: &[UntypedArchetypeStatement]| {
+        statements
+            .iter()
+            .map(|statement| statement_spec(statement, world))
+            .collect()
+    };
+    match statement {
+        UntypedArchetypeStatement::AllOf(set) => list(ArchetypeStatementKind::AllOf, set),
+        UntypedArchetypeStatement::AtLeastOneOf(set) => {
+            list(ArchetypeStatementKind::AtLeastOneOf, set)
+        }
+        UntypedArchetypeStatement::NoneOf(set) => list(ArchetypeStatementKind::NoneOf, set),
+        UntypedArchetypeStatement::ExactlyOneOf(set) => {
+            list(ArchetypeStatementKind::ExactlyOneOf, set)
+        }
+        UntypedArchetypeStatement::AtMostOneOf(set) => {
+            list(ArchetypeStatementKind::AtMostOneOf, set)
+        }
+        UntypedArchetypeStatement::ExactlyNOf(set, n) => {
+            list(ArchetypeStatementKind::ExactlyNOf(*n), set)
+        }
+        UntypedArchetypeStatement::AtLeastNOf(set, n) => {
+            list(ArchetypeStatementKind::AtLeastNOf(*n), set)
+        }
+        UntypedArchetypeStatement::AtMostNOf(set, n) => {
+            list(ArchetypeStatementKind::AtMostNOf(*n), set)
+        }
+        UntypedArchetypeStatement::Exactly(set) => list(ArchetypeStatementKind::Exactly, set),
+        UntypedArchetypeStatement::SubsetOf { relevant, allowed } => {
+            ArchetypeStatementSpec::SubsetOf {
+                relevant: names(relevant),
+                allowed: names(allowed),
+            }
+        }
+        UntypedArchetypeStatement::ComponentCount { min, max } => {
+            ArchetypeStatementSpec::ComponentCount {
+                min: *min,
+                max: *max,
+            }
+        }
+        UntypedArchetypeStatement::ResourcePresent(resource) => {
+            ArchetypeStatementSpec::ResourcePresent(name(*resource))
+        }
+        UntypedArchetypeStatement::HasParent(parent) => {
+            ArchetypeStatementSpec::HasParent(name(*parent))
+        }
+        UntypedArchetypeStatement::HasChildren(children) => {
+            ArchetypeStatementSpec::HasChildren(name(*children))
+        }
+        UntypedArchetypeStatement::StorageType {
+            components,
+            storage,
+            ..
+        } => ArchetypeStatementSpec::StorageType {
+            components: names(components),
+            storage: *storage,
+        },
+        UntypedArchetypeStatement::GroupAllOf(group) => {
+            ArchetypeStatementSpec::GroupAllOf(group_name(*group))
+        }
+        UntypedArchetypeStatement::GroupAtLeastOneOf(group) => {
+            ArchetypeStatementSpec::GroupAtLeastOneOf(group_name(*group))
+        }
+        UntypedArchetypeStatement::Always => ArchetypeStatementSpec::Always,
+        UntypedArchetypeStatement::Empty => ArchetypeStatementSpec::Empty,
+        UntypedArchetypeStatement::And(statements) => {
+            ArchetypeStatementSpec::And(specs(statements))
+        }
+        UntypedArchetypeStatement::Or(statements) => ArchetypeStatementSpec::Or(specs(statements)),
+        UntypedArchetypeStatement::Not(statement) => {
+            ArchetypeStatementSpec::Not(Box::new(statement_spec(statement, world)))
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! Deciding whether one archetype statement implies another, used to find redundant invariants.
+
+use super::{ComponentIdSet, UntypedArchetypeInvariant, UntypedArchetypeStatement};
+
+/// A statement that bounds how many components of a set an archetype has,
+/// which every statement about a single set of components can be written as.
+struct CountBounds<'a> {
+    set: &'a ComponentIdSet,
+    min: usize,
+    max: usize,
+}
+
+impl<'a> CountBounds<'a> {
+    fn of(statement: &'a UntypedArchetypeStatement) -> Option<Self> {
+        let (set, min, max) = match statement {
+            UntypedArchetypeStatement::AllOf(set) => (set, set.len(), set.len()),
+            UntypedArchetypeStatement::AtLeastOneOf(set) => (set, 1, usize::MAX),
+            UntypedArchetypeStatement::NoneOf(set) => (set, 0, 0),
+            UntypedArchetypeStatement::ExactlyOneOf(set) => (set, 1, 1),
+            UntypedArchetypeStatement::AtMostOneOf(set) => (set, 0, 1),
+            UntypedArchetypeStatement::ExactlyNOf(set, n) => (set, *n, *n),
+            UntypedArchetypeStatement::AtLeastNOf(set, n) => (set, *n, usize::MAX),
+            UntypedArchetypeStatement::AtMostNOf(set, n) => (set, 0, *n),
+            _ => return None,
+        };
+        Some(CountBounds { set, min, max })
+    }
+
+    /// Returns `true` if every archetype within these bounds is also within the `other` bounds.
+    fn implies(&self, other: &CountBounds) -> bool {
+        // No archetype is within these bounds at all
+        if self.min > self.max || self.min > self.set.len() {
+            return true;
+        }
+        let common = self.set.count_common(other.set, usize::MAX);
+        let only_self = self.set.len() - common;
+        let only_other = other.set.len() - common;
+        // The components outside of `other.set` can make up at most `only_self` of the required count,
+        // while the components outside of `self.set` are unconstrained.
+        let fewest = self.min.saturating_sub(only_self);
+        let most = common.min(self.max) + only_other;
+        fewest >= other.min && most <= other.max
+    }
+}
+
+/// Rewrites the statements that forbid a fixed set of components as [`UntypedArchetypeStatement::NoneOf`],
+/// and the statements about the hierarchy as [`UntypedArchetypeStatement::AllOf`] their component.
+fn normalize(statement: &UntypedArchetypeStatement) -> Option<UntypedArchetypeStatement> {
+    match statement {
+        UntypedArchetypeStatement::HasParent(id) | UntypedArchetypeStatement::HasChildren(id) => {
+            Some(UntypedArchetypeStatement::all_of_ids([*id]))
+        }
+        UntypedArchetypeStatement::SubsetOf { relevant, allowed } => {
+            Some(UntypedArchetypeStatement::NoneOf(
+                relevant
+                    .iter()
+                    .filter(|&id| !allowed.contains(id))
+                    .collect(),
+            ))
+        }
+        UntypedArchetypeStatement::StorageType { mismatched, .. } => {
+            Some(UntypedArchetypeStatement::NoneOf(mismatched.clone()))
+        }
+        _ => None,
+    }
+}
+
+impl UntypedArchetypeStatement {
+    /// Returns `true` if every archetype that satisfies this statement also satisfies `other`,
+    /// in which case `other` adds nothing when combined with this statement in an [`UntypedArchetypeStatement::And`].
+    ///
+    /// This is conservative: a result of `false` only means that the implication could not be shown.
+    /// The following cases are decided exactly:
+    /// - any two statements about a single set of components, from [`UntypedArchetypeStatement::AllOf`]
+    ///   to [`UntypedArchetypeStatement::AtMostNOf`]. [`UntypedArchetypeStatement::SubsetOf`] and
+    ///   [`UntypedArchetypeStatement::StorageType`] are treated as the components they forbid,
+    ///   and [`UntypedArchetypeStatement::HasParent`] and [`UntypedArchetypeStatement::HasChildren`]
+    ///   as the single component they require.
+    /// - [`UntypedArchetypeStatement::Exactly`] and [`UntypedArchetypeStatement::Empty`] against any statement
+    ///   that does not depend on resources or component groups, since they describe a single archetype.
+    /// - two [`UntypedArchetypeStatement::ComponentCount`]s.
+    /// - [`UntypedArchetypeStatement::Always`] or an empty [`UntypedArchetypeStatement::And`] as `other`,
+    ///   and an empty [`UntypedArchetypeStatement::Or`] as this statement.
+    /// - an [`UntypedArchetypeStatement::Or`] as this statement, or an [`UntypedArchetypeStatement::And`] as `other`,
+    ///   as long as each of their statements is decided.
+    ///
+    /// An [`UntypedArchetypeStatement::And`] as this statement implies `other` if one of its statements does,
+    /// and this statement implies an [`UntypedArchetypeStatement::Or`] if it implies one of its statements.
+    /// [`UntypedArchetypeStatement::Not`] statements imply each other by contraposition.
+    /// Otherwise, statements about resources, component groups or anything else only imply statements equal to them.
+    ///
+    /// ```
+    /// # use bevy_ecs::{archetype_invariants::*, component::ComponentId};
+    /// let [a, b, c] = [0, 1, 2].map(ComponentId::new);
+    /// let all_of = UntypedArchetypeStatement::all_of_ids([a, b]);
+    /// assert!(all_of.subsumes(&UntypedArchetypeStatement::at_least_one_of_ids([a, b])));
+    /// assert!(all_of.subsumes(&UntypedArchetypeStatement::at_least_one_of_ids([a, c])));
+    /// assert!(!all_of.subsumes(&UntypedArchetypeStatement::all_of_ids([a, c])));
+    /// ```
+    pub fn subsumes(&self, other: &UntypedArchetypeStatement) -> bool {
+        if self == other {
+            return true;
+        }
+        if let Some(statement) = normalize(self) {
+            return statement.subsumes(other);
+        }
+        if let Some(statement) = normalize(other) {
+            return self.subsumes(&statement);
+        }
+        match (self, other) {
+            (_, UntypedArchetypeStatement::Always) => return true,
+            (UntypedArchetypeStatement::Or(statements), _) => {
+                return statements.iter().all(|statement| statement.subsumes(other))
+            }
+            (_, UntypedArchetypeStatement::And(statements)) => {
+                return statements.iter().all(|statement| self.subsumes(statement))
+            }
+            (UntypedArchetypeStatement::Not(statement), UntypedArchetypeStatement::Not(other)) => {
+                return other.subsumes(statement)
+            }
+            _ => {}
+        }
+
+        if let (Some(bounds), Some(other_bounds)) = (CountBounds::of(self), CountBounds::of(other))
+        {
+            return bounds.implies(&other_bounds);
+        }
+        match (self, other) {
+            (UntypedArchetypeStatement::Exactly(set), _)
+                if !other.uses_resources() && !other.uses_groups() =>
+            {
+                other.evaluate(set)
+            }
+            (UntypedArchetypeStatement::Empty, _)
+                if !other.uses_resources() && !other.uses_groups() =>
+            {
+                other.evaluate(&ComponentIdSet::new())
+            }
+            (
+                UntypedArchetypeStatement::ComponentCount { min, max },
+                UntypedArchetypeStatement::ComponentCount {
+                    min: other_min,
+                    max: other_max,
+                },
+            ) => {
+                min.unwrap_or(0) >= other_min.unwrap_or(0)
+                    && max.unwrap_or(usize::MAX) <= other_max.unwrap_or(usize::MAX)
+            }
+            (UntypedArchetypeStatement::And(statements), _) => {
+                statements.iter().any(|statement| statement.subsumes(other))
+            }
+            (_, UntypedArchetypeStatement::Or(statements)) => {
+                statements.iter().any(|statement| self.subsumes(statement))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl UntypedArchetypeInvariant {
+    /// Returns `true` if every archetype that upholds this invariant also upholds `other`,
+    /// so that `other` is redundant next to this invariant.
+    ///
+    /// This is the case if the predicate of `other` implies the predicate of this invariant,
+    /// and the consequence of this invariant implies the consequence of `other`,
+    /// as decided by [`UntypedArchetypeStatement::subsumes`]. The severities of the invariants are not compared.
+    pub fn subsumes(&self, other: &UntypedArchetypeInvariant) -> bool {
+        other.predicate.subsumes(&self.predicate) && self.consequence.subsumes(&other.consequence)
+    }
+}
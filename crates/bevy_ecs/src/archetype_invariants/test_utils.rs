@@ -0,0 +1,101 @@
+//! Helpers for testing that archetype invariants are violated when expected.
+
+use crate::{
+    archetype_invariants::{
+        ArchetypeInvariantViolated, ArchetypeInvariantViolation, ViolationPolicy,
+    },
+    event::Events,
+    world::World,
+};
+
+/// Runs `f` on a new [`World`] and returns the first archetype invariant violation it caused.
+///
+/// The world's [`ViolationPolicy`] is set to [`ViolationPolicy::Event`] before `f` runs,
+/// so violations are collected instead of panicking, and can be inspected afterwards.
+/// Only violations of invariants with [`Severity::Error`](super::Severity::Error) are collected.
+///
+/// # Panics
+///
+/// Panics if `f` did not cause any archetype invariant to be violated,
+/// or if `f` changed the world's [`ViolationPolicy`].
+///
+/// ```
+/// use bevy_ecs::{
+///     archetype_invariants::{test_utils::expect_archetype_invariant_violation, ArchetypeInvariant},
+///     prelude::*,
+/// };
+///
+/// #[derive(Component)]
+/// struct Player;
+/// #[derive(Component)]
+/// struct Enemy;
+///
+/// let violation = expect_archetype_invariant_violation(|world| {
+///     world.add_archetype_invariant(ArchetypeInvariant::<(Player,), (Enemy,)>::forbids());
+///     world.spawn((Player, Enemy));
+/// });
+/// assert_eq!(violation.entities.len(), 1);
+/// assert_eq!(violation.component_ids.len(), 2);
+/// ```
+#[track_caller]
+pub fn expect_archetype_invariant_violation(
+    f: impl FnOnce(&mut World),
+) -> ArchetypeInvariantViolation {
+    let mut world = World::new();
+    world.set_archetype_invariant_policy(ViolationPolicy::Event);
+    f(&mut world);
+    assert_eq!(
+        world.archetype_invariant_policy(),
+        ViolationPolicy::Event,
+        "the archetype invariant violation policy was changed while expecting a violation"
+    );
+
+    world
+        .get_resource_mut::<Events<ArchetypeInvariantViolated>>()
+        .and_then(|mut events| events.drain().next())
+        .map(|event| event.violation)
+        .expect("no archetype invariant was violated")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_ecs;
+    use crate::{
+        archetype_invariants::{ArchetypeInvariant, UntypedArchetypeStatement},
+        component::Component,
+    };
+
+    use super::expect_archetype_invariant_violation;
+
+    #[derive(Component)]
+    struct A;
+
+    #[derive(Component)]
+    struct B;
+
+    #[test]
+    fn returns_first_violation() {
+        let violation = expect_archetype_invariant_violation(|world| {
+            world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+            world.spawn((A, B));
+            world.spawn(A);
+            world.spawn(B);
+        });
+        let a = violation.invariant.predicate.component_ids();
+        assert_eq!(violation.component_ids, a.iter().collect::<Vec<_>>());
+        assert!(matches!(
+            violation.invariant.consequence,
+            UntypedArchetypeStatement::AllOf(_)
+        ));
+        assert_eq!(violation.entities.len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "no archetype invariant was violated")]
+    fn panics_without_violation() {
+        expect_archetype_invariant_violation(|world| {
+            world.add_archetype_invariant(ArchetypeInvariant::<(A,), (B,)>::requires());
+            world.spawn((A, B));
+        });
+    }
+}
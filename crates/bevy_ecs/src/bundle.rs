@@ -133,6 +133,11 @@ pub unsafe trait Bundle: Send + Sync + 'static {
         ids: &mut impl FnMut(ComponentId),
     );
 
+    /// Gets the [`TypeId`] and type name of each of this [`Bundle`]'s [`Component`]s,
+    /// in the same order as [`Bundle::component_ids`], without registering them
+    #[doc(hidden)]
+    fn component_type_ids(ids: &mut impl FnMut(TypeId, &'static str));
+
     /// Calls `func`, which should return data for each component in the bundle, in the order of
     /// this bundle's [`Component`]s
     ///
@@ -165,6 +170,10 @@ unsafe impl<C: Component> Bundle for C {
         ids(components.init_component::<C>(storages));
     }
 
+    fn component_type_ids(ids: &mut impl FnMut(TypeId, &'static str)) {
+        ids(TypeId::of::<C>(), std::any::type_name::<C>());
+    }
+
     unsafe fn from_components<T, F>(ctx: &mut T, func: &mut F) -> Self
     where
         // Ensure that the `OwningPtr` is used correctly
@@ -192,6 +201,11 @@ macro_rules! tuple_impl {
                 $(<$name as Bundle>::component_ids(components, storages, ids);)*
             }
 
+            #[allow(unused_variables)]
+            fn component_type_ids(ids: &mut impl FnMut(TypeId, &'static str)) {
+                $(<$name as Bundle>::component_type_ids(ids);)*
+            }
+
             #[allow(unused_variables, unused_mut)]
             #[allow(clippy::unused_unit)]
             unsafe fn from_components<T, F>(ctx: &mut T, func: &mut F) -> Self
@@ -4,6 +4,7 @@ use crate::{
     change_detection::MAX_CHANGE_AGE,
     storage::{SparseSetIndex, Storages},
     system::Resource,
+    world::World,
 };
 pub use bevy_ecs_macros::Component;
 use bevy_ptr::OwningPtr;
@@ -84,6 +85,36 @@ use std::{
 /// [`Table`]: crate::storage::Table
 /// [`SparseSet`]: crate::storage::SparseSet
 ///
+/// # Declaring archetype invariants
+///
+/// Components that only make sense together with, or never together with, other components
+/// can declare this with the `#[component(requires(...))]` and `#[component(forbids(...))]` attributes.
+/// Each listed component becomes an [`ArchetypeInvariant::requires`] or [`ArchetypeInvariant::forbids`] invariant,
+/// which is added to a world by [`World::register_component_invariants`].
+///
+/// ```should_panic
+/// # use bevy_ecs::prelude::*;
+/// #
+/// #[derive(Component)]
+/// struct Transform;
+/// #[derive(Component)]
+/// struct Disabled;
+///
+/// #[derive(Component)]
+/// #[component(requires(Transform), forbids(Disabled))]
+/// struct Collider;
+///
+/// let mut world = World::new();
+/// world.register_component_invariants::<Collider>();
+/// world.spawn((Collider, Transform));
+///
+/// // Panics, since `Collider` requires `Transform`
+/// world.spawn(Collider);
+/// ```
+///
+/// [`ArchetypeInvariant::requires`]: crate::archetype_invariants::ArchetypeInvariant::requires
+/// [`ArchetypeInvariant::forbids`]: crate::archetype_invariants::ArchetypeInvariant::forbids
+///
 /// # Implementing the trait for foreign types
 ///
 /// As a consequence of the [orphan rule], it is not possible to separate into two different crates the implementation of `Component` from the definition of a type.
@@ -111,6 +142,12 @@ use std::{
 /// [newtype pattern]: https://doc.rust-lang.org/book/ch19-03-advanced-traits.html#using-the-newtype-pattern-to-implement-external-traits-on-external-types
 pub trait Component: Send + Sync + 'static {
     type Storage: ComponentStorage;
+
+    /// Adds the archetype invariants declared for this component to `world`.
+    ///
+    /// This is implemented by `#[derive(Component)]` for the `requires` and `forbids` attributes,
+    /// and does nothing by default. Use [`World::register_component_invariants`] to call it.
+    fn register_archetype_invariants(_world: &mut World) {}
 }
 
 pub struct TableStorage;
@@ -145,7 +182,11 @@ mod sealed {
 /// #[component(storage = "SparseSet")]
 /// struct A;
 /// ```
-#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(::serde::Serialize, ::serde::Deserialize)
+)]
 pub enum StorageType {
     /// Provides fast and cache-friendly iteration, but slower addition and removal of components.
     /// This is the default storage type.
@@ -367,6 +408,7 @@ pub struct Components {
     components: Vec<ComponentInfo>,
     indices: std::collections::HashMap<TypeId, usize, fxhash::FxBuildHasher>,
     resource_indices: std::collections::HashMap<TypeId, usize, fxhash::FxBuildHasher>,
+    names: std::collections::HashMap<Cow<'static, str>, ComponentId, fxhash::FxBuildHasher>,
 }
 
 impl Components {
@@ -377,10 +419,16 @@ impl Components {
         let Components {
             indices,
             components,
+            names,
             ..
         } = self;
         let index = indices.entry(type_id).or_insert_with(|| {
-            Components::init_component_inner(components, storages, ComponentDescriptor::new::<T>())
+            Components::init_component_inner(
+                components,
+                names,
+                storages,
+                ComponentDescriptor::new::<T>(),
+            )
         });
         ComponentId(*index)
     }
@@ -390,17 +438,31 @@ impl Components {
         storages: &mut Storages,
         descriptor: ComponentDescriptor,
     ) -> ComponentId {
-        let index = Components::init_component_inner(&mut self.components, storages, descriptor);
+        let index = Components::init_component_inner(
+            &mut self.components,
+            &mut self.names,
+            storages,
+            descriptor,
+        );
         ComponentId(index)
     }
 
     #[inline]
     fn init_component_inner(
         components: &mut Vec<ComponentInfo>,
+        names: &mut std::collections::HashMap<
+            Cow<'static, str>,
+            ComponentId,
+            fxhash::FxBuildHasher,
+        >,
         storages: &mut Storages,
         descriptor: ComponentDescriptor,
     ) -> usize {
         let index = components.len();
+        // The first component registered under a name keeps it
+        names
+            .entry(descriptor.name.clone())
+            .or_insert(ComponentId(index));
         let info = ComponentInfo::new(ComponentId(index), descriptor);
         if info.descriptor.storage_type == StorageType::SparseSet {
             storages.sparse_sets.get_or_insert(&info);
@@ -439,6 +501,19 @@ impl Components {
         self.indices.get(&type_id).map(|index| ComponentId(*index))
     }
 
+    /// Returns the [`ComponentId`] of the component or resource with the given name.
+    ///
+    /// The name must match [`ComponentInfo::name`] exactly,
+    /// which for Rust types is the full path as returned by [`std::any::type_name`].
+    /// Short names are not accepted, since types in different modules may share them.
+    ///
+    /// If several components or resources were registered under the same name,
+    /// which can happen with [`ComponentDescriptor`]s or a type used as both, the first one registered is returned.
+    #[inline]
+    pub fn get_id_by_name(&self, name: &str) -> Option<ComponentId> {
+        self.names.get(name).copied()
+    }
+
     /// Returns the [`ComponentId`] of the given [`Component`] type `T`.
     ///
     /// The returned `ComponentId` is specific to the `Components` instance
@@ -502,9 +577,13 @@ impl Components {
         func: impl FnOnce() -> ComponentDescriptor,
     ) -> ComponentId {
         let components = &mut self.components;
+        let names = &mut self.names;
         let index = self.resource_indices.entry(type_id).or_insert_with(|| {
             let descriptor = func();
             let index = components.len();
+            names
+                .entry(descriptor.name.clone())
+                .or_insert(ComponentId(index));
             components.push(ComponentInfo::new(ComponentId(index), descriptor));
             index
         });
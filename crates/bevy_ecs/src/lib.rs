@@ -5,6 +5,7 @@
 compile_error!("bevy_ecs cannot safely compile for a 16-bit platform.");
 
 pub mod archetype;
+pub mod archetype_invariants;
 pub mod bundle;
 pub mod change_detection;
 pub mod component;
@@ -27,6 +28,8 @@ pub mod prelude {
     pub use crate::reflect::{ReflectComponent, ReflectResource};
     #[doc(hidden)]
     pub use crate::{
+        archetype_invariant,
+        archetype_invariants::{ArchetypeInvariant, ArchetypeStatement},
         bundle::Bundle,
         change_detection::DetectChanges,
         component::Component,
@@ -1,16 +1,19 @@
 //! Types that enable reflection support.
 
 use crate::{
+    archetype_invariants::{ComponentIdSet, GroupId, UntypedArchetypeStatement},
     change_detection::Mut,
-    component::Component,
+    component::{Component, ComponentId, StorageType},
     entity::{Entity, EntityMap, MapEntities, MapEntitiesError},
     system::Resource,
     world::{FromWorld, World},
 };
 use bevy_reflect::{
-    impl_from_reflect_value, impl_reflect_value, FromType, Reflect, ReflectDeserialize,
-    ReflectSerialize,
+    impl_from_reflect_value, impl_reflect_value, FromReflect, FromType, Reflect,
+    ReflectDeserialize, ReflectMut, ReflectRef, ReflectSerialize, TypeInfo, Typed,
 };
+use smallvec::SmallVec;
+use std::any::Any;
 
 /// A struct used to operate on reflected [`Component`] of a type.
 ///
@@ -292,6 +295,101 @@ impl<C: Resource + Reflect + FromWorld> FromType<C> for ReflectResource {
 impl_reflect_value!(Entity(Hash, PartialEq, Serialize, Deserialize));
 impl_from_reflect_value!(Entity);
 
+impl_reflect_value!(ComponentId(Debug, Hash, PartialEq));
+impl_from_reflect_value!(ComponentId);
+
+impl FromReflect for ComponentIdSet {
+    fn from_reflect(reflect: &dyn Reflect) -> Option<Self> {
+        match reflect.reflect_ref() {
+            // Collecting the components sorts them, in case they were reflected out of order
+            ReflectRef::TupleStruct(set) => {
+                let component_ids = SmallVec::<[ComponentId; 4]>::from_reflect(set.field(0)?)?;
+                Some(component_ids.into_iter().collect())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl_reflect_value!(StorageType(Debug, Hash, PartialEq));
+impl_from_reflect_value!(StorageType);
+
+impl_reflect_value!(GroupId(Debug, Hash, PartialEq));
+impl_from_reflect_value!(GroupId);
+
+// Reflects the negated statement of `UntypedArchetypeStatement::Not` as the statement itself,
+// so that it can be inspected like any other statement.
+impl Reflect for Box<UntypedArchetypeStatement> {
+    fn type_name(&self) -> &str {
+        (**self).type_name()
+    }
+
+    fn get_type_info(&self) -> &'static TypeInfo {
+        (**self).get_type_info()
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        *self
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        &**self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        &mut **self
+    }
+
+    fn as_reflect(&self) -> &dyn Reflect {
+        &**self
+    }
+
+    fn as_reflect_mut(&mut self) -> &mut dyn Reflect {
+        &mut **self
+    }
+
+    fn apply(&mut self, value: &dyn Reflect) {
+        (**self).apply(value);
+    }
+
+    fn set(&mut self, value: Box<dyn Reflect>) -> Result<(), Box<dyn Reflect>> {
+        **self = value.take()?;
+        Ok(())
+    }
+
+    fn reflect_ref(&self) -> ReflectRef<'_> {
+        (**self).reflect_ref()
+    }
+
+    fn reflect_mut(&mut self) -> ReflectMut<'_> {
+        (**self).reflect_mut()
+    }
+
+    fn clone_value(&self) -> Box<dyn Reflect> {
+        (**self).clone_value()
+    }
+
+    fn reflect_partial_eq(&self, value: &dyn Reflect) -> Option<bool> {
+        (**self).reflect_partial_eq(value)
+    }
+
+    fn debug(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        (**self).debug(f)
+    }
+}
+
+impl Typed for Box<UntypedArchetypeStatement> {
+    fn type_info() -> &'static TypeInfo {
+        UntypedArchetypeStatement::type_info()
+    }
+}
+
+impl FromReflect for Box<UntypedArchetypeStatement> {
+    fn from_reflect(reflect: &dyn Reflect) -> Option<Self> {
+        UntypedArchetypeStatement::from_reflect(reflect).map(Box::new)
+    }
+}
+
 #[derive(Clone)]
 pub struct ReflectMapEntities {
     map_entities: fn(&mut World, &EntityMap) -> Result<(), MapEntitiesError>,
@@ -96,6 +96,8 @@ impl CommandQueue {
                 (meta.func)(self.bytes.as_mut_ptr().add(meta.offset), world);
             }
         }
+        // Commands that spawn or change entities leave their archetype invariant handlers pending
+        world.run_pending_archetype_invariant_handlers();
     }
 }
 
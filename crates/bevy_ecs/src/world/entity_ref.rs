@@ -264,6 +264,8 @@ impl<'w> EntityMut<'w> {
         unsafe {
             self.location = bundle_inserter.insert(self.entity, self.location.index, bundle);
         }
+        // Archetype invariant handlers may despawn this entity, so they are left pending until after this `EntityMut`
+        self.world.check_new_archetypes_deferring_handlers();
 
         self
     }
@@ -340,6 +342,13 @@ impl<'w> EntityMut<'w> {
                 new_archetype_id,
             );
         }
+        self.world.check_archetype_invariant_removal(
+            entity,
+            old_location.archetype_id,
+            new_archetype_id,
+        );
+        // Archetype invariant handlers may despawn this entity, so they are left pending until after this `EntityMut`
+        self.world.check_new_archetypes_deferring_handlers();
 
         Some(result)
     }
@@ -474,6 +483,13 @@ impl<'w> EntityMut<'w> {
                 new_archetype_id,
             );
         }
+        self.world.check_archetype_invariant_removal(
+            entity,
+            old_location.archetype_id,
+            new_archetype_id,
+        );
+        // Archetype invariant handlers may despawn this entity, so they are left pending until after this `EntityMut`
+        self.world.check_new_archetypes_deferring_handlers();
     }
 
     pub fn despawn(self) {
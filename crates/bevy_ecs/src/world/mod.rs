@@ -8,7 +8,14 @@ pub use spawn_batch::*;
 pub use world_cell::*;
 
 use crate::{
-    archetype::{ArchetypeComponentId, ArchetypeComponentInfo, ArchetypeId, Archetypes},
+    archetype::{Archetype, ArchetypeComponentId, ArchetypeComponentInfo, ArchetypeId, Archetypes},
+    archetype_invariants::{
+        present_resources, ArchetypeInvariant, ArchetypeInvariantHandler, ArchetypeInvariantId,
+        ArchetypeInvariantStats, ArchetypeInvariantViolation, ArchetypeInvariants,
+        ArchetypeInvariantsSnapshot, ArchetypeStatementKind, ComponentIdSet, GroupId,
+        ImplicationClosure, Severity, UnknownComponentNames, UntypedArchetypeInvariant,
+        UntypedArchetypeStatement, ViolatingArchetypes, ViolationPolicy,
+    },
     bundle::{Bundle, BundleInserter, BundleSpawner, Bundles},
     change_detection::{MutUntyped, Ticks},
     component::{
@@ -24,6 +31,7 @@ use bevy_ptr::{OwningPtr, Ptr, UnsafeCellDeref};
 use bevy_utils::tracing::debug;
 use std::{
     any::TypeId,
+    borrow::Cow,
     fmt,
     sync::atomic::{AtomicU32, Ordering},
 };
@@ -55,6 +63,11 @@ pub struct World {
     pub(crate) entities: Entities,
     pub(crate) components: Components,
     pub(crate) archetypes: Archetypes,
+    pub(crate) archetype_invariants: ArchetypeInvariants,
+    /// Callbacks registered with [`World::on_new_archetype`].
+    new_archetype_observers: Vec<NewArchetypeObserver>,
+    /// The number of archetypes that the [`World::on_new_archetype`] callbacks have been notified of.
+    last_observed_archetype_index: usize,
     pub(crate) storages: Storages,
     pub(crate) bundles: Bundles,
     pub(crate) removed_components: SparseSet<ComponentId, Vec<Entity>>,
@@ -65,6 +78,9 @@ pub struct World {
     pub(crate) last_change_tick: u32,
 }
 
+/// A callback registered with [`World::on_new_archetype`].
+type NewArchetypeObserver = Box<dyn FnMut(ArchetypeId, &[ComponentId]) + Send + Sync>;
+
 impl Default for World {
     fn default() -> Self {
         Self {
@@ -72,6 +88,9 @@ impl Default for World {
             entities: Default::default(),
             components: Default::default(),
             archetypes: Default::default(),
+            archetype_invariants: Default::default(),
+            new_archetype_observers: Vec::new(),
+            last_observed_archetype_index: 0,
             storages: Default::default(),
             bundles: Default::default(),
             removed_components: Default::default(),
@@ -197,6 +216,1137 @@ impl World {
         self.components.component_id::<T>()
     }
 
+    /// Inserts a new [`ArchetypeInvariant`] into the world, returning an [`ArchetypeInvariantId`]
+    /// that can be passed to [`World::remove_archetype_invariant`].
+    ///
+    /// The invariant is converted into an [`UntypedArchetypeInvariant`],
+    /// which initializes any components in `B1` and `B2` that have not been seen before.
+    ///
+    /// All existing archetypes are checked against the new invariant immediately,
+    /// and all archetypes created afterwards are checked as they appear.
+    /// These automatic checks only happen if the `archetype_invariants` feature is enabled, which it is by default.
+    /// Without it, invariants are still stored, but are only checked by [`World::check_archetype_invariants`].
+    ///
+    /// # Panics
+    /// Panics if any archetype in the world violates the new archetype invariant.
+    ///
+    /// ```rust
+    /// use bevy_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position;
+    /// #[derive(Component)]
+    /// struct Velocity;
+    ///
+    /// let mut world = World::new();
+    ///
+    /// // Any entity with a `Position` or a `Velocity` must have both.
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(Position, Velocity)>::full_bundle());
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn add_archetype_invariant<B1: Bundle, B2: Bundle>(
+        &mut self,
+        archetype_invariant: ArchetypeInvariant<B1, B2>,
+    ) -> ArchetypeInvariantId {
+        let untyped_invariant = archetype_invariant.into_untyped(self);
+        self.add_untyped_archetype_invariant(untyped_invariant)
+    }
+
+    /// Adds the archetype invariants declared by the component `C` to the world.
+    ///
+    /// These are declared with the `#[component(requires(...))]` and `#[component(forbids(...))]`
+    /// attributes of `#[derive(Component)]`. See [`Component`] for an example.
+    ///
+    /// # Panics
+    /// Panics if any archetype in the world violates the new archetype invariants.
+    pub fn register_component_invariants<C: Component>(&mut self) {
+        C::register_archetype_invariants(self);
+    }
+
+    /// Inserts a new [`ArchetypeInvariant`] into the world with the given [`Severity`],
+    /// returning an [`ArchetypeInvariantId`] that can be passed to [`World::remove_archetype_invariant`].
+    ///
+    /// Invariants with [`Severity::Warning`] log a warning when they are violated instead of panicking,
+    /// which is useful for checks that should be noticed during development, but are not worth crashing over.
+    /// See [`World::add_archetype_invariant`] for more details.
+    ///
+    /// # Panics
+    /// Panics if `severity` is [`Severity::Error`] and any archetype in the world violates the new archetype invariant.
+    #[track_caller]
+    pub fn add_archetype_invariant_with_severity<B1: Bundle, B2: Bundle>(
+        &mut self,
+        archetype_invariant: ArchetypeInvariant<B1, B2>,
+        severity: Severity,
+    ) -> ArchetypeInvariantId {
+        let untyped_invariant = UntypedArchetypeInvariant {
+            severity,
+            ..archetype_invariant.into_untyped(self)
+        };
+        self.add_untyped_archetype_invariant(untyped_invariant)
+    }
+
+    /// Inserts a new [`ArchetypeInvariant`] into the world, whose violations are passed to `handler`,
+    /// returning an [`ArchetypeInvariantId`] that can be passed to [`World::remove_archetype_invariant`].
+    ///
+    /// Instead of panicking, logging or sending an event, the handler is run for each entity in a violating archetype,
+    /// with mutable access to the world, as soon as the violation is found, apart from the exception below.
+    /// This allows recovering from the violation, for example by removing the offending component.
+    /// The handler is skipped for entities that no longer exist or no longer violate the invariant
+    /// by the time it would run, for example because an earlier handler already fixed them.
+    ///
+    /// # Re-entrancy
+    ///
+    /// Changing the archetype of an entity from inside a handler triggers the usual archetype invariant checks,
+    /// so a handler may cause more violations, whose handlers run before it returns,
+    /// or right after it returns if the violations were caused through an [`EntityMut`].
+    /// To guard against infinite recursion, handlers are not run more than
+    /// [`ArchetypeInvariants::MAX_HANDLER_DEPTH`] levels deep: beyond that, an error is logged instead.
+    ///
+    /// If the violation was caused by [`World::spawn`] or by an [`EntityMut`], the handler does not run
+    /// while that [`EntityMut`] may still refer to the entity, so that it is free to despawn it.
+    /// Instead, it is left pending until the next operation that checks archetype invariants
+    /// outside of an [`EntityMut`], such as adding an invariant or spawning another entity,
+    /// or until [`Commands`](crate::system::Commands) are applied or
+    /// [`World::run_pending_archetype_invariant_handlers`] is called.
+    ///
+    /// Handlers only run if the `archetype_invariants` feature is enabled.
+    ///
+    /// ```rust
+    /// use bevy_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Ghost;
+    ///
+    /// let mut world = World::new();
+    /// let player = world.spawn((Player, Ghost)).id();
+    ///
+    /// // Players can not be ghosts, so any ghostly players are despawned.
+    /// world.add_archetype_invariant_with_handler(
+    ///     ArchetypeInvariant::<(Player,), (Ghost,)>::forbids(),
+    ///     Box::new(|world, entity, _| {
+    ///         world.despawn(entity);
+    ///     }),
+    /// );
+    /// assert!(world.get_entity(player).is_none());
+    /// ```
+    #[track_caller]
+    pub fn add_archetype_invariant_with_handler<B1: Bundle, B2: Bundle>(
+        &mut self,
+        archetype_invariant: ArchetypeInvariant<B1, B2>,
+        handler: ArchetypeInvariantHandler,
+    ) -> ArchetypeInvariantId {
+        let untyped_invariant = archetype_invariant.into_untyped(self);
+        let id = self.archetype_invariants.add_with_handler(
+            untyped_invariant,
+            handler,
+            &self.components,
+        );
+        self.check_new_archetypes();
+        id
+    }
+
+    /// Inserts a new [`ArchetypeInvariant`] into the world, which is only checked when
+    /// [`World::check_deferred_archetype_invariants`] is called,
+    /// returning an [`ArchetypeInvariantId`] that can be passed to [`World::remove_archetype_invariant`].
+    ///
+    /// Regular invariants are checked against every new archetype as soon as it is created,
+    /// even if it only ever holds an entity briefly, such as while components are inserted one at a time.
+    /// Deferred invariants are instead checked in a single batch, against the archetypes that currently contain entities.
+    /// This bounds the cost of checking them, and ignores transient archetypes,
+    /// but a violation is only caught once the check runs, so the world may contain violating entities until then.
+    ///
+    /// ```
+    /// use bevy_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Position;
+    /// #[derive(Component)]
+    /// struct Velocity;
+    ///
+    /// let mut world = World::new();
+    /// world.add_deferred_archetype_invariant(ArchetypeInvariant::<(Velocity,), (Position,)>::requires());
+    ///
+    /// // The entity briefly has a `Velocity` without a `Position`, which does not panic.
+    /// world.spawn(Velocity).insert(Position);
+    /// world.check_deferred_archetype_invariants();
+    /// ```
+    #[track_caller]
+    pub fn add_deferred_archetype_invariant<B1: Bundle, B2: Bundle>(
+        &mut self,
+        archetype_invariant: ArchetypeInvariant<B1, B2>,
+    ) -> ArchetypeInvariantId {
+        let untyped_invariant = archetype_invariant.into_untyped(self);
+        self.archetype_invariants
+            .add_deferred(untyped_invariant, &self.components)
+    }
+
+    /// Checks every archetype that currently contains entities against the invariants
+    /// added with [`World::add_deferred_archetype_invariant`].
+    /// Violations are handled according to the world's [`ViolationPolicy`], like those found by the automatic checks.
+    ///
+    /// This can be run once per frame as an exclusive system,
+    /// `|world: &mut World| world.check_deferred_archetype_invariants()`.
+    /// Like the automatic checks, this does nothing if the `archetype_invariants` feature is disabled.
+    ///
+    /// # Panics
+    /// Panics if any of these archetypes violates a deferred archetype invariant and the policy is [`ViolationPolicy::Panic`].
+    pub fn check_deferred_archetype_invariants(&mut self) {
+        #[cfg(feature = "archetype_invariants")]
+        {
+            self.archetype_invariants
+                .check_deferred(&self.archetypes, &self.components);
+            self.send_archetype_invariant_violations();
+            self.run_archetype_invariant_handlers();
+        }
+    }
+
+    /// Inserts a new [`UntypedArchetypeInvariant`] into the world.
+    ///
+    /// Whenever possible, [`World::add_archetype_invariant`] should be used instead.
+    /// This method should only be used when the components involved have no Rust type,
+    /// such as components defined at runtime via [`World::init_component_with_descriptor`].
+    ///
+    /// # Panics
+    /// Panics if any archetype in the world violates the new archetype invariant.
+    #[inline]
+    #[track_caller]
+    pub fn add_untyped_archetype_invariant(
+        &mut self,
+        archetype_invariant: UntypedArchetypeInvariant,
+    ) -> ArchetypeInvariantId {
+        let id = self
+            .archetype_invariants
+            .add(archetype_invariant, &self.components);
+        self.check_new_archetypes();
+        id
+    }
+
+    /// Inserts a new [`ArchetypeInvariant`] into the world as part of the group `label`,
+    /// returning an [`ArchetypeInvariantId`] that can be passed to [`World::remove_archetype_invariant`].
+    ///
+    /// All archetype invariants with the same label can be removed at once
+    /// using [`World::remove_archetype_invariants_by_label`],
+    /// for example when the plugin that added them is no longer in use.
+    ///
+    /// See [`World::add_archetype_invariant`] for more details.
+    ///
+    /// # Panics
+    /// Panics if any archetype in the world violates the new archetype invariant.
+    #[track_caller]
+    pub fn add_archetype_invariant_labeled<B1: Bundle, B2: Bundle>(
+        &mut self,
+        label: impl Into<Cow<'static, str>>,
+        archetype_invariant: ArchetypeInvariant<B1, B2>,
+    ) -> ArchetypeInvariantId {
+        let untyped_invariant = archetype_invariant.into_untyped(self);
+        let id = self
+            .archetype_invariants
+            .add_labeled(untyped_invariant, label, &self.components);
+        self.check_new_archetypes();
+        id
+    }
+
+    /// Inserts a new archetype invariant into the world, identifying its components by name,
+    /// and returns an [`ArchetypeInvariantId`] that can be passed to [`World::remove_archetype_invariant`].
+    ///
+    /// The predicate and consequence are each given as an [`ArchetypeStatementKind`] with a list of component names,
+    /// which must match [`ComponentInfo::name`] exactly. This allows data-driven tools to add invariants
+    /// without access to the component types.
+    /// If any of the names does not belong to a registered component, no invariant is added,
+    /// and an error listing every unknown name is returned.
+    ///
+    /// # Panics
+    /// Panics if any archetype in the world violates the new archetype invariant.
+    #[track_caller]
+    pub fn add_archetype_invariant_by_name(
+        &mut self,
+        predicate: (ArchetypeStatementKind, &[&str]),
+        consequence: (ArchetypeStatementKind, &[&str]),
+    ) -> Result<ArchetypeInvariantId, UnknownComponentNames> {
+        let predicate = predicate.0.resolve(predicate.1, &self.components);
+        let consequence = consequence.0.resolve(consequence.1, &self.components);
+        let (predicate, consequence) = match (predicate, consequence) {
+            (Ok(predicate), Ok(consequence)) => (predicate, consequence),
+            (Err(mut error), Err(consequence_error)) => {
+                error.names.extend(consequence_error.names);
+                return Err(error);
+            }
+            (Err(error), _) | (_, Err(error)) => return Err(error),
+        };
+        Ok(
+            self.add_untyped_archetype_invariant(UntypedArchetypeInvariant {
+                predicate,
+                consequence,
+                severity: Severity::Error,
+            }),
+        )
+    }
+
+    /// Inserts several [`UntypedArchetypeInvariant`]s into the world at once,
+    /// returning an [`ArchetypeInvariantId`] for each of them, in order.
+    ///
+    /// Existing archetypes are checked against all of the new invariants in a single pass,
+    /// which is faster than calling [`World::add_untyped_archetype_invariant`] for each of them.
+    ///
+    /// # Panics
+    /// Panics if any archetype in the world violates one of the new archetype invariants.
+    #[track_caller]
+    pub fn add_archetype_invariants(
+        &mut self,
+        archetype_invariants: impl IntoIterator<Item = UntypedArchetypeInvariant>,
+    ) -> Vec<ArchetypeInvariantId> {
+        // A closure would lose the location of the caller
+        let mut ids = Vec::new();
+        for archetype_invariant in archetype_invariants {
+            ids.push(
+                self.archetype_invariants
+                    .add(archetype_invariant, &self.components),
+            );
+        }
+        self.check_new_archetypes();
+        ids
+    }
+
+    /// Parses an [`UntypedArchetypeInvariant`] from its RON representation,
+    /// as produced by [`ArchetypeInvariantSerializer`](crate::archetype_invariants::ArchetypeInvariantSerializer).
+    ///
+    /// Components are identified by their full type name, and are resolved to the [`ComponentId`]s
+    /// they have in this world. The invariant is not added to the world;
+    /// pass it to [`World::add_untyped_archetype_invariant`] to do so.
+    ///
+    /// Returns an error if the input is malformed, or if any named component has not been registered.
+    #[cfg(feature = "serialize")]
+    pub fn deserialize_archetype_invariant(
+        &self,
+        ron: &str,
+    ) -> Result<
+        UntypedArchetypeInvariant,
+        crate::archetype_invariants::ArchetypeInvariantDeserializeError,
+    > {
+        crate::archetype_invariants::deserialize_archetype_invariant(ron, &self.components)
+    }
+
+    /// Parses an [`UntypedArchetypeInvariant`] from the compact form it is displayed in, such as `AllOf(A, B) => NoneOf(C)`,
+    /// resolving each component name to the [`ComponentId`] it has in this world.
+    ///
+    /// The invariant is not added to the world; pass it to [`World::add_untyped_archetype_invariant`] to do so.
+    /// Returns an error with the offset of the problem if the input is malformed, or if any named component has not been registered.
+    /// See [`UntypedArchetypeInvariant::parse`] for the supported statements.
+    ///
+    /// ```
+    /// use bevy_ecs::{archetype_invariants::ArchetypeInvariant, prelude::*};
+    ///
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Enemy;
+    ///
+    /// let mut world = World::new();
+    /// world.init_component::<Player>();
+    /// world.init_component::<Enemy>();
+    ///
+    /// let invariant = world.parse_archetype_invariant("AllOf(Player) => NoneOf(Enemy)").unwrap();
+    /// assert_eq!(
+    ///     invariant,
+    ///     ArchetypeInvariant::<(Player,), (Enemy,)>::forbids().into_untyped(&mut world)
+    /// );
+    ///
+    /// let error = world.parse_archetype_invariant("AllOf(Player) => NoneOf(Ghost)").unwrap_err();
+    /// assert_eq!(error.offset, 24);
+    /// ```
+    pub fn parse_archetype_invariant(
+        &self,
+        src: &str,
+    ) -> Result<UntypedArchetypeInvariant, crate::archetype_invariants::ArchetypeInvariantParseError>
+    {
+        UntypedArchetypeInvariant::parse(src, &self.components)
+    }
+
+    /// Removes the archetype invariant with the given [`ArchetypeInvariantId`] from the world, returning it.
+    ///
+    /// Returns `None` if no such invariant exists, for example because it was already removed.
+    #[inline]
+    pub fn remove_archetype_invariant(
+        &mut self,
+        id: ArchetypeInvariantId,
+    ) -> Option<UntypedArchetypeInvariant> {
+        self.archetype_invariants.remove(id)
+    }
+
+    /// Enables or disables the archetype invariant with the given [`ArchetypeInvariantId`],
+    /// returning `false` if no such invariant exists.
+    ///
+    /// A disabled invariant is not enforced, but keeps its id so it can be enabled again later.
+    /// Enabling an invariant immediately re-checks every existing archetype against it.
+    /// See [`ArchetypeInvariants::set_enabled`] for more details.
+    pub fn set_archetype_invariant_enabled(
+        &mut self,
+        id: ArchetypeInvariantId,
+        enabled: bool,
+    ) -> bool {
+        let found = self.archetype_invariants.set_enabled(id, enabled);
+        self.check_new_archetypes();
+        found
+    }
+
+    /// Returns where in the source code the archetype invariant with the given [`ArchetypeInvariantId`] was added,
+    /// or `None` if no such invariant exists.
+    ///
+    /// ```
+    /// use bevy_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Health;
+    ///
+    /// let mut world = World::new();
+    /// let id = world.add_archetype_invariant(ArchetypeInvariant::<(Player,), (Health,)>::requires());
+    /// let location = world.archetype_invariant_location(id).unwrap();
+    /// assert_eq!(location.line(), line!() - 2);
+    /// ```
+    #[inline]
+    pub fn archetype_invariant_location(
+        &self,
+        id: ArchetypeInvariantId,
+    ) -> Option<&'static std::panic::Location<'static>> {
+        self.archetype_invariants.location(id)
+    }
+
+    /// Returns whether the archetype invariant with the given [`ArchetypeInvariantId`] is enabled,
+    /// or `None` if no such invariant exists.
+    #[inline]
+    pub fn is_archetype_invariant_enabled(&self, id: ArchetypeInvariantId) -> Option<bool> {
+        self.archetype_invariants.is_enabled(id)
+    }
+
+    /// Exempts `entity` from the archetype invariant with the given [`ArchetypeInvariantId`],
+    /// returning `false` if no such invariant exists.
+    ///
+    /// The invariant is still checked against the archetype of the entity, but violations are only reported
+    /// for the entities that are not exempt. Exempt the entity before giving it the components that violate the invariant,
+    /// as violations are reported as soon as the violating archetype is created.
+    /// See [`ArchetypeInvariants::exempt_entity`] for more details.
+    ///
+    /// ```
+    /// use bevy_ecs::{archetype_invariants::ArchetypeInvariant, prelude::*};
+    ///
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Health;
+    ///
+    /// let mut world = World::new();
+    /// let id = world.add_archetype_invariant(ArchetypeInvariant::<(Player,), (Health,)>::requires());
+    ///
+    /// let bookkeeping = world.spawn_empty().id();
+    /// world.exempt_entity_from_invariant(bookkeeping, id);
+    /// // Does not panic
+    /// world.entity_mut(bookkeeping).insert(Player);
+    /// assert!(world.archetype_invariant_violations_for_entity(bookkeeping).is_empty());
+    /// ```
+    pub fn exempt_entity_from_invariant(
+        &mut self,
+        entity: Entity,
+        invariant_id: ArchetypeInvariantId,
+    ) -> bool {
+        self.archetype_invariants
+            .exempt_entity(invariant_id, entity)
+    }
+
+    /// Inserts a new [`ArchetypeInvariant`] into the world without enforcing it,
+    /// returning an [`ArchetypeInvariantId`] that can be passed to [`World::set_archetype_invariant_enabled`].
+    ///
+    /// This is intended for invariants that only hold during some phase of an app,
+    /// such as rules that are temporarily broken while a level is spawned over several systems.
+    /// Add the invariant disabled, then enable it from a system that runs once that phase is over.
+    /// Enabling it checks every existing archetype against it.
+    ///
+    /// ```
+    /// # use bevy_ecs::{archetype_invariants::*, prelude::*};
+    /// #[derive(Component)]
+    /// struct Node;
+    /// #[derive(Component)]
+    /// struct Layout;
+    ///
+    /// #[derive(Resource)]
+    /// struct LayoutInvariant(ArchetypeInvariantId);
+    ///
+    /// fn spawn_ui(mut commands: Commands) {
+    ///     // The layout is only computed later
+    ///     commands.spawn(Node);
+    /// }
+    ///
+    /// fn compute_layout(mut commands: Commands, nodes: Query<Entity, (With<Node>, Without<Layout>)>) {
+    ///     for node in &nodes {
+    ///         commands.entity(node).insert(Layout);
+    ///     }
+    /// }
+    ///
+    /// fn enable_layout_invariant(world: &mut World) {
+    ///     let id = world.resource::<LayoutInvariant>().0;
+    ///     world.set_archetype_invariant_enabled(id, true);
+    /// }
+    ///
+    /// let mut world = World::new();
+    /// let id = world.add_disabled_archetype_invariant(ArchetypeInvariant::<(Node,), (Layout,)>::requires());
+    /// world.insert_resource(LayoutInvariant(id));
+    ///
+    /// let mut schedule = Schedule::default();
+    /// schedule.add_stage("spawn", SystemStage::single(spawn_ui));
+    /// schedule.add_stage("layout", SystemStage::single(compute_layout));
+    /// schedule.add_stage("after_setup", SystemStage::single(enable_layout_invariant));
+    /// // `Node` briefly exists without `Layout`, but the invariant is only enabled afterwards
+    /// schedule.run(&mut world);
+    /// assert_eq!(world.is_archetype_invariant_enabled(id), Some(true));
+    /// ```
+    #[track_caller]
+    pub fn add_disabled_archetype_invariant<B1: Bundle, B2: Bundle>(
+        &mut self,
+        archetype_invariant: ArchetypeInvariant<B1, B2>,
+    ) -> ArchetypeInvariantId {
+        let untyped_invariant = archetype_invariant.into_untyped(self);
+        let id = self
+            .archetype_invariants
+            .add_disabled(untyped_invariant, &self.components);
+        self.check_new_archetypes();
+        id
+    }
+
+    /// Returns an iterator over all archetype invariants in the world, in the order they were added.
+    #[inline]
+    pub fn archetype_invariants(&self) -> impl Iterator<Item = &UntypedArchetypeInvariant> {
+        self.archetype_invariants.iter()
+    }
+
+    /// Returns counters describing how much work the automatic archetype invariant checks of this world have done.
+    ///
+    /// See [`ArchetypeInvariants::stats`] for more details.
+    #[inline]
+    pub fn archetype_invariant_stats(&self) -> ArchetypeInvariantStats {
+        self.archetype_invariants.stats()
+    }
+
+    /// Removes all archetype invariants that were added to the world with the given label,
+    /// returning them in the order they were added.
+    ///
+    /// See [`World::add_archetype_invariant_labeled`].
+    pub fn remove_archetype_invariants_by_label(
+        &mut self,
+        label: &str,
+    ) -> Vec<UntypedArchetypeInvariant> {
+        self.archetype_invariants.remove_by_label(label)
+    }
+
+    /// Computes the transitive closure of this world's archetype invariants of the simple `AllOf => AllOf` form,
+    /// logging every implied invariant and every cycle that is found.
+    ///
+    /// If `register_derived` is `true`, the implied invariants are added to the world.
+    /// See [`ArchetypeInvariants::compute_implication_closure`] for more details.
+    #[track_caller]
+    pub fn compute_archetype_invariant_implication_closure(
+        &mut self,
+        register_derived: bool,
+    ) -> ImplicationClosure {
+        let closure = self
+            .archetype_invariants
+            .compute_implication_closure(register_derived, &self.components);
+        self.check_new_archetypes();
+        closure
+    }
+
+    /// Finds pairs of this world's archetype invariants that can never both hold
+    /// for an entity that matches both of their predicates.
+    ///
+    /// See [`ArchetypeInvariants::validate_consistency`] for more details.
+    #[inline]
+    pub fn validate_archetype_invariant_consistency(
+        &self,
+    ) -> Vec<(ArchetypeInvariantId, ArchetypeInvariantId)> {
+        self.archetype_invariants.validate_consistency()
+    }
+
+    /// Removes all archetype invariants from the world.
+    #[inline]
+    pub fn clear_archetype_invariants(&mut self) {
+        self.archetype_invariants.clear();
+    }
+
+    /// Returns an estimate of the number of bytes used by the archetype invariants of the world.
+    ///
+    /// See [`ArchetypeInvariants::memory_usage`] for what is included.
+    #[inline]
+    pub fn archetype_invariant_memory_usage(&self) -> usize {
+        self.archetype_invariants.memory_usage()
+    }
+
+    /// Releases the unused capacity held by the archetype invariants of the world,
+    /// for example after removing many of them.
+    ///
+    /// See [`ArchetypeInvariants::shrink_to_fit`].
+    #[inline]
+    pub fn shrink_archetype_invariants_to_fit(&mut self) {
+        self.archetype_invariants.shrink_to_fit();
+    }
+
+    /// Captures the archetype invariants of the world, so that they can be restored later
+    /// with [`World::restore_archetype_invariants`].
+    ///
+    /// See [`ArchetypeInvariants::snapshot`].
+    #[inline]
+    pub fn archetype_invariants_snapshot(&self) -> ArchetypeInvariantsSnapshot {
+        self.archetype_invariants.snapshot()
+    }
+
+    /// Replaces the archetype invariants of the world with the ones captured in `snapshot`,
+    /// keeping their [`ArchetypeInvariantId`]s and labels, and checks every archetype against them.
+    ///
+    /// ```
+    /// # use bevy_ecs::{archetype_invariants::ArchetypeInvariant, prelude::*};
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Health;
+    ///
+    /// let mut world = World::new();
+    /// let id = world.add_archetype_invariant(ArchetypeInvariant::<(Player,), (Health,)>::requires());
+    /// let snapshot = world.archetype_invariants_snapshot();
+    ///
+    /// world.clear_archetype_invariants();
+    /// world.restore_archetype_invariants(snapshot);
+    /// assert!(world.remove_archetype_invariant(id).is_some());
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if any archetype in the world violates one of the restored archetype invariants.
+    pub fn restore_archetype_invariants(&mut self, snapshot: ArchetypeInvariantsSnapshot) {
+        self.archetype_invariants.restore(snapshot);
+        self.check_new_archetypes();
+    }
+
+    /// Tests every archetype in the world against every archetype invariant,
+    /// returning all violations found.
+    ///
+    /// This is the read-only counterpart to the checks performed automatically whenever
+    /// archetypes are created or archetype invariants are added,
+    /// which handle violations according to the world's [`ViolationPolicy`].
+    /// It never panics and does not change which archetypes are considered already checked,
+    /// so it can be called repeatedly, for example to report problems to a user.
+    pub fn check_archetype_invariants(&self) -> Vec<ArchetypeInvariantViolation> {
+        self.archetype_invariants
+            .check_archetypes(&self.archetypes, &self.components)
+    }
+
+    /// Returns an iterator over every archetype in the world that violates an archetype invariant,
+    /// together with the invariant it violates.
+    ///
+    /// This is the lazy counterpart to [`World::check_archetype_invariants`]:
+    /// archetypes are only checked as the iterator advances.
+    /// See [`ArchetypeInvariants::iter_violating_archetypes`] for more details.
+    #[inline]
+    pub fn iter_violating_archetypes(&self) -> ViolatingArchetypes<'_> {
+        self.archetype_invariants
+            .iter_violating_archetypes(&self.archetypes)
+    }
+
+    /// Panics if any archetype in the world violates any archetype invariant,
+    /// listing every violation found.
+    ///
+    /// Like [`World::check_archetype_invariants`], this tests every archetype against every invariant,
+    /// regardless of which archetypes have already been checked automatically,
+    /// and regardless of the invariants' [`Severity`] and the world's [`ViolationPolicy`].
+    /// This makes it a deterministic checkpoint for tests.
+    ///
+    /// ```should_panic
+    /// use bevy_ecs::{archetype_invariants::{ArchetypeInvariant, ViolationPolicy}, prelude::*};
+    ///
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Enemy;
+    ///
+    /// let mut world = World::new();
+    /// world.set_archetype_invariant_policy(ViolationPolicy::Warn);
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(Player,), (Enemy,)>::forbids());
+    /// world.spawn((Player, Enemy));
+    ///
+    /// world.assert_archetype_invariants();
+    /// ```
+    #[track_caller]
+    pub fn assert_archetype_invariants(&self) {
+        let violations = self.check_archetype_invariants();
+        if violations.is_empty() {
+            return;
+        }
+
+        let mut message = format!(
+            "{} archetype invariant violation(s) found:",
+            violations.len()
+        );
+        for violation in &violations {
+            message.push_str("\n- ");
+            message.push_str(&violation.to_string());
+        }
+        panic!("{message}");
+    }
+
+    /// Returns a textual audit of this world's archetype invariants, for example to store as an artifact in CI.
+    ///
+    /// The report starts with a line counting the invariants, followed by one line per invariant in the order they were added,
+    /// starting with `invariant #` and its [`ArchetypeInvariantId`].
+    /// Each line [explains](UntypedArchetypeInvariant::explain) the invariant, and counts the archetypes its predicate matches,
+    /// including archetypes without entities. Disabled invariants are marked as such.
+    /// Then, a line counting the violations is followed by one line per violation, starting with `violation of invariant #`,
+    /// like the violations returned by [`World::check_archetype_invariants`] with a sample of the affected entities.
+    ///
+    /// The report only depends on the current contents of the world, so it is identical for identical worlds.
+    ///
+    /// ```
+    /// use bevy_ecs::{archetype_invariants::ArchetypeInvariant, prelude::*};
+    ///
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Health;
+    ///
+    /// let mut world = World::new();
+    /// world.spawn((Player, Health));
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(Player,), (Health,)>::requires());
+    ///
+    /// assert_eq!(
+    ///     world.archetype_invariant_report(),
+    ///     "archetype invariants: 1\n\
+    ///      invariant #0: For every entity that has all of [Player], it must also have all of [Health]. [matches 1 archetype(s)]\n\
+    ///      violations: 0\n"
+    /// );
+    /// ```
+    pub fn archetype_invariant_report(&self) -> String {
+        self.archetype_invariants.report(self)
+    }
+
+    /// Returns an iterator over every entity whose components satisfy `statement`.
+    ///
+    /// This evaluates the statement once per archetype, so it can be used as an ad-hoc filter
+    /// for conditions that are only known at runtime, such as statements about dynamic components.
+    /// Entities are yielded archetype by archetype.
+    ///
+    /// ```
+    /// use bevy_ecs::{archetype_invariants::ArchetypeStatement, prelude::*};
+    ///
+    /// #[derive(Component)]
+    /// struct A;
+    /// #[derive(Component)]
+    /// struct B;
+    ///
+    /// let mut world = World::new();
+    /// let a = world.spawn(A).id();
+    /// let b = world.spawn(B).id();
+    /// world.spawn_empty();
+    ///
+    /// let statement = ArchetypeStatement::<(A, B)>::at_least_one_of().into_untyped(&mut world);
+    /// let entities: Vec<Entity> = world.entities_matching_statement(&statement).collect();
+    /// assert_eq!(entities, [a, b]);
+    /// ```
+    pub fn entities_matching_statement<'a>(
+        &'a self,
+        statement: &'a UntypedArchetypeStatement,
+    ) -> impl Iterator<Item = Entity> + 'a {
+        let resources = present_resources(&self.archetypes);
+        self.archetypes
+            .iter()
+            .filter(move |archetype| {
+                statement.evaluate_with_resources(&archetype.components().collect(), &resources)
+            })
+            .flat_map(|archetype| archetype.entities().iter().copied())
+    }
+
+    /// Returns every enabled archetype invariant that the given `entity` currently violates,
+    /// apart from the ones it is [exempt](World::exempt_entity_from_invariant) from.
+    ///
+    /// Invariants are evaluated as the automatic checks evaluate them, so those that refer to component groups
+    /// are returned with every group replaced by its current members.
+    /// Returns an empty list if the entity does not exist.
+    /// Like [`World::check_archetype_invariants`], this never panics, regardless of the [`ViolationPolicy`].
+    pub fn archetype_invariant_violations_for_entity(
+        &self,
+        entity: Entity,
+    ) -> Vec<&UntypedArchetypeInvariant> {
+        let location = match self.entities.get(entity) {
+            Some(location) => location,
+            None => return Vec::new(),
+        };
+        let component_ids = self.archetypes[location.archetype_id]
+            .components()
+            .collect();
+        let resources = present_resources(&self.archetypes);
+        self.archetype_invariants
+            .violations_for_entity(entity, &component_ids, &resources)
+    }
+
+    /// Returns the [`ViolationPolicy`] that is applied when an archetype invariant is violated.
+    #[inline]
+    pub fn archetype_invariant_policy(&self) -> ViolationPolicy {
+        self.archetype_invariants.policy()
+    }
+
+    /// Sets what happens when the automatic checks find an archetype that violates an archetype invariant.
+    ///
+    /// By default, the world panics. Under [`ViolationPolicy::Warn`] and [`ViolationPolicy::Event`],
+    /// the operation that created the violating archetype completes as usual,
+    /// so the world may hold entities that violate its archetype invariants.
+    ///
+    /// ```
+    /// use bevy_ecs::{
+    ///     archetype_invariants::{ArchetypeInvariant, ArchetypeInvariantViolated, ViolationPolicy},
+    ///     event::Events,
+    ///     prelude::*,
+    /// };
+    ///
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Enemy;
+    ///
+    /// let mut world = World::new();
+    /// world.set_archetype_invariant_policy(ViolationPolicy::Event);
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(Player,), (Enemy,)>::forbids());
+    ///
+    /// // Does not panic
+    /// world.spawn((Player, Enemy));
+    ///
+    /// let events = world.resource::<Events<ArchetypeInvariantViolated>>();
+    /// assert_eq!(events.len(), 1);
+    /// ```
+    pub fn set_archetype_invariant_policy(&mut self, policy: ViolationPolicy) {
+        self.archetype_invariants.set_policy(policy);
+    }
+
+    /// Enables or disables warnings for archetype statements that are valid, but commonly misunderstood.
+    ///
+    /// See [`ArchetypeInvariants::set_strict`] for details.
+    #[inline]
+    pub fn set_strict_archetype_invariants(&mut self, strict: bool) {
+        self.archetype_invariants.set_strict(strict);
+    }
+
+    /// Registers `P` as the component that marks an entity as the child of another entity,
+    /// and `C` as the component that lists the children of an entity,
+    /// such as `Parent` and `Children` from `bevy_hierarchy`, which registers them in its `HierarchyPlugin`.
+    ///
+    /// See [`UntypedArchetypeStatement::has_parent`] and [`UntypedArchetypeStatement::has_children`].
+    pub fn set_hierarchy_components<P: Component, C: Component>(&mut self) {
+        let parent = self.init_component::<P>();
+        let children = self.init_component::<C>();
+        self.archetype_invariants
+            .set_hierarchy_components(parent, children);
+    }
+
+    /// Sets whether archetypes without entities are checked against this world's archetype invariants.
+    ///
+    /// This is `true` by default. See [`ArchetypeInvariants::set_check_empty_archetypes`] for details.
+    #[inline]
+    pub fn set_check_empty_archetypes(&mut self, check_empty_archetypes: bool) {
+        self.archetype_invariants
+            .set_check_empty_archetypes(check_empty_archetypes);
+    }
+
+    /// Sets the number of archetypes from which this world's automatic archetype invariant checks run in parallel.
+    ///
+    /// This is [`ArchetypeInvariants::DEFAULT_PARALLEL_CHECK_THRESHOLD`] by default.
+    /// See [`ArchetypeInvariants::set_parallel_check_threshold`] for details.
+    #[inline]
+    pub fn set_archetype_invariant_parallel_check_threshold(&mut self, threshold: usize) {
+        self.archetype_invariants
+            .set_parallel_check_threshold(threshold);
+    }
+
+    /// Registers a named group of components that this world's archetype invariants can refer to,
+    /// and re-checks the invariants referring to it if the group already existed.
+    ///
+    /// See [`ArchetypeInvariants::register_component_group`] for more details.
+    pub fn register_component_group(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        component_ids: &[ComponentId],
+    ) -> GroupId {
+        let id = self
+            .archetype_invariants
+            .register_component_group(name, component_ids.iter().copied());
+        self.check_new_archetypes();
+        id
+    }
+
+    /// Adds a component to one of this world's component groups,
+    /// and re-checks the archetype invariants referring to the group.
+    ///
+    /// See [`ArchetypeInvariants::add_to_component_group`] for more details.
+    pub fn add_to_component_group(&mut self, group: GroupId, component_id: ComponentId) -> bool {
+        let added = self
+            .archetype_invariants
+            .add_to_component_group(group, component_id);
+        self.check_new_archetypes();
+        added
+    }
+
+    /// Sets whether removals of components are checked against this world's paired archetype invariants,
+    /// reporting which components a removal left orphaned.
+    ///
+    /// This is `false` by default. See [`ArchetypeInvariants::set_check_removals`] for details.
+    #[inline]
+    pub fn set_check_archetype_invariant_removals(&mut self, check_removals: bool) {
+        self.archetype_invariants.set_check_removals(check_removals);
+    }
+
+    /// Checks that all archetypes created since the last check uphold this world's archetype invariants,
+    /// and that all other archetypes uphold any archetype invariants added since the last check.
+    /// Violations are handled according to the world's [`ViolationPolicy`].
+    ///
+    /// This should be called after any operation that may create new archetypes.
+    ///
+    /// Does nothing if the `archetype_invariants` feature is disabled.
+    ///
+    /// # Panics
+    /// Panics if any of these archetypes violates an archetype invariant and the policy is [`ViolationPolicy::Panic`].
+    #[inline]
+    pub(crate) fn check_new_archetypes(&mut self) {
+        self.check_new_archetypes_deferring_handlers();
+        #[cfg(feature = "archetype_invariants")]
+        self.run_archetype_invariant_handlers();
+    }
+
+    /// Like [`World::check_new_archetypes`], but leaves the handlers of any violations pending.
+    ///
+    /// This is used by [`World::spawn`] and [`EntityMut`], whose entity a handler may despawn,
+    /// see [`World::run_pending_archetype_invariant_handlers`].
+    #[inline]
+    pub(crate) fn check_new_archetypes_deferring_handlers(&mut self) {
+        if self.last_observed_archetype_index != self.archetypes.len() {
+            self.notify_new_archetype_observers();
+        }
+        #[cfg(feature = "archetype_invariants")]
+        if self.archetype_invariants.checks_automatically() {
+            self.find_pending_archetype_invariant_violations();
+        }
+    }
+
+    /// Checks that all archetypes created since the last check uphold this world's archetype invariants,
+    /// and that all other archetypes uphold any archetype invariants added since the last check.
+    /// Violations are handled according to the world's [`ViolationPolicy`].
+    ///
+    /// This happens automatically whenever an archetype is created, unless disabled with
+    /// [`World::set_automatic_archetype_invariant_checks`]. In that case, this must be called regularly instead,
+    /// for example by adding [`check_archetype_invariants_system`](crate::archetype_invariants::check_archetype_invariants_system) to a schedule.
+    ///
+    /// Does nothing if the `archetype_invariants` feature is disabled.
+    ///
+    /// # Panics
+    /// Panics if any of these archetypes violates an archetype invariant and the policy is [`ViolationPolicy::Panic`].
+    pub fn check_pending_archetype_invariants(&mut self) {
+        #[cfg(feature = "archetype_invariants")]
+        {
+            self.find_pending_archetype_invariant_violations();
+            self.run_archetype_invariant_handlers();
+        }
+    }
+
+    /// Runs the archetype invariant handlers that are still pending,
+    /// because their violations were caused by [`World::spawn`] or by an [`EntityMut`].
+    ///
+    /// These handlers run automatically with the next archetype invariant check outside of an [`EntityMut`],
+    /// and whenever [`Commands`](crate::system::Commands) are applied,
+    /// so this is only needed to run them right away.
+    /// See [`World::add_archetype_invariant_with_handler`].
+    ///
+    /// ```
+    /// use bevy_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Ghost;
+    ///
+    /// let mut world = World::new();
+    /// world.add_archetype_invariant_with_handler(
+    ///     ArchetypeInvariant::<(Player,), (Ghost,)>::forbids(),
+    ///     Box::new(|world, entity, _| {
+    ///         world.despawn(entity);
+    ///     }),
+    /// );
+    ///
+    /// let player = world.spawn(Player).insert(Ghost).id();
+    /// world.run_pending_archetype_invariant_handlers();
+    /// assert!(world.get_entity(player).is_none());
+    /// ```
+    pub fn run_pending_archetype_invariant_handlers(&mut self) {
+        #[cfg(feature = "archetype_invariants")]
+        self.run_archetype_invariant_handlers();
+    }
+
+    /// Checks the archetypes and invariants that were not checked yet, like [`World::check_pending_archetype_invariants`],
+    /// without running the handlers of the violations found.
+    #[cfg(feature = "archetype_invariants")]
+    fn find_pending_archetype_invariant_violations(&mut self) {
+        if self
+            .archetype_invariants
+            .has_unchecked_archetypes(&self.archetypes)
+        {
+            self.archetype_invariants
+                .check_new_archetypes(&self.archetypes, &self.components);
+            self.send_archetype_invariant_violations();
+        }
+    }
+
+    /// Sets whether this world's archetype invariants are checked as soon as an archetype is created.
+    ///
+    /// This is `true` by default. When disabled, [`World::check_pending_archetype_invariants`] must be called instead,
+    /// usually by adding [`check_archetype_invariants_system`](crate::archetype_invariants::check_archetype_invariants_system) to a schedule.
+    /// See [`ArchetypeInvariants::set_automatic_checks`] for details.
+    #[inline]
+    pub fn set_automatic_archetype_invariant_checks(&mut self, automatic_checks: bool) {
+        self.archetype_invariants
+            .set_automatic_checks(automatic_checks);
+    }
+
+    /// Registers a callback that is called with the id and components of every archetype created from now on.
+    ///
+    /// Archetypes are observed at the same moment they are checked against this world's archetype invariants:
+    /// after the operation that created them, such as [`World::spawn`] or [`EntityMut::insert`], has finished.
+    /// The callbacks are called in the order they were registered, and before the new archetypes are checked
+    /// against the invariants, so they see every archetype even if it violates an invariant under [`ViolationPolicy::Panic`].
+    /// Archetypes that only exist temporarily within an operation are not observed separately.
+    ///
+    /// Unlike archetype invariants, these callbacks are called even if the `archetype_invariants` feature is disabled.
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// # use std::sync::{Arc, Mutex};
+    /// #[derive(Component)]
+    /// struct Position;
+    ///
+    /// let mut world = World::new();
+    /// let created = Arc::new(Mutex::new(Vec::new()));
+    /// let log = created.clone();
+    /// world.on_new_archetype(move |id, components| log.lock().unwrap().push((id, components.len())));
+    ///
+    /// let entity = world.spawn(Position).id();
+    /// // Spawning into an existing archetype does not call the callback again
+    /// world.spawn(Position);
+    /// let archetype = world.entity(entity).archetype().id();
+    /// assert_eq!(*created.lock().unwrap(), [(archetype, 1)]);
+    /// ```
+    pub fn on_new_archetype(
+        &mut self,
+        observer: impl FnMut(ArchetypeId, &[ComponentId]) + Send + Sync + 'static,
+    ) {
+        // Archetypes created before the callback was registered are not reported to it
+        self.notify_new_archetype_observers();
+        self.new_archetype_observers.push(Box::new(observer));
+    }
+
+    /// Calls the [`World::on_new_archetype`] callbacks for every archetype they have not been notified of yet.
+    fn notify_new_archetype_observers(&mut self) {
+        let new_archetypes = self.last_observed_archetype_index..self.archetypes.len();
+        self.last_observed_archetype_index = self.archetypes.len();
+        if self.new_archetype_observers.is_empty() {
+            return;
+        }
+        for archetype in &self.archetypes.archetypes[new_archetypes] {
+            let component_ids: Vec<ComponentId> = archetype.components().collect();
+            for observer in &mut self.new_archetype_observers {
+                observer(archetype.id(), &component_ids);
+            }
+        }
+    }
+
+    /// Checks the removal of components that moved `entity` from the `old` archetype to the `new` one
+    /// against this world's paired archetype invariants, if enabled.
+    ///
+    /// This should be called after the entity has moved, and before [`World::check_new_archetypes`].
+    #[cfg_attr(not(feature = "archetype_invariants"), allow(unused_variables))]
+    pub(crate) fn check_archetype_invariant_removal(
+        &mut self,
+        entity: Entity,
+        old: ArchetypeId,
+        new: ArchetypeId,
+    ) {
+        #[cfg(feature = "archetype_invariants")]
+        if self.archetype_invariants.checks_removals()
+            && self.archetype_invariants.checks_automatically()
+        {
+            self.archetype_invariants.check_removal(
+                entity,
+                &self.archetypes[old],
+                &self.archetypes[new],
+                &self.components,
+            );
+            self.send_archetype_invariant_violations();
+        }
+    }
+
+    /// Runs the handlers of the invariants with an
+    /// [`ArchetypeInvariantHandler`](crate::archetype_invariants::ArchetypeInvariantHandler)
+    /// that were violated since the handlers last ran.
+    #[cfg(feature = "archetype_invariants")]
+    fn run_archetype_invariant_handlers(&mut self) {
+        let calls = self.archetype_invariants.take_pending_handler_calls();
+        if calls.is_empty() {
+            return;
+        }
+        if self.archetype_invariants.handler_depth >= ArchetypeInvariants::MAX_HANDLER_DEPTH {
+            bevy_utils::tracing::error!(
+                "Archetype invariant handlers kept causing new violations, {} levels deep. The handlers of {} more violations were not run.",
+                ArchetypeInvariants::MAX_HANDLER_DEPTH,
+                calls.len()
+            );
+            return;
+        }
+
+        self.archetype_invariants.handler_depth += 1;
+        for call in calls {
+            for entity in call.entities {
+                if let Some(location) = self.entities.get(entity) {
+                    let component_ids = self.archetypes[location.archetype_id]
+                        .components()
+                        .collect();
+                    let resources = present_resources(&self.archetypes);
+                    if !call
+                        .invariant
+                        .evaluate_with_resources(&component_ids, &resources)
+                    {
+                        (call.handler)(self, entity, &call.invariant);
+                        // The `EntityMut`s used by the handler are released, so the handlers they left pending can run
+                        self.run_archetype_invariant_handlers();
+                    }
+                }
+            }
+        }
+        self.archetype_invariants.handler_depth -= 1;
+    }
+
+    /// Sends the violations found under [`ViolationPolicy::Event`] as
+    /// [`ArchetypeInvariantViolated`](crate::archetype_invariants::ArchetypeInvariantViolated) events.
+    #[cfg(feature = "archetype_invariants")]
+    fn send_archetype_invariant_violations(&mut self) {
+        use crate::{archetype_invariants::ArchetypeInvariantViolated, event::Events};
+
+        if self.archetype_invariants.policy() != ViolationPolicy::Event {
+            return;
+        }
+
+        let violations: Vec<_> = self
+            .archetype_invariants
+            .drain_unreported_violations()
+            .map(|violation| ArchetypeInvariantViolated { violation })
+            .collect();
+        if !violations.is_empty() {
+            self.get_resource_or_insert_with(Events::<ArchetypeInvariantViolated>::default)
+                .extend(violations);
+        }
+    }
+
     /// Retrieves an [`EntityRef`] that exposes read-only operations for the given `entity`.
     /// This will panic if the `entity` does not exist. Use [`World::get_entity`] if you want
     /// to check for entity existence instead of implicitly panic-ing.
@@ -439,6 +1589,8 @@ impl World {
     /// ```
     pub fn spawn<B: Bundle>(&mut self, bundle: B) -> EntityMut {
         self.flush();
+        // No `EntityMut` can exist anymore, so the handlers left pending by one can run
+        self.run_pending_archetype_invariant_handlers();
         let entity = self.entities.alloc();
         let entity_location = {
             let bundle_info = self
@@ -455,11 +1607,74 @@ impl World {
             // SAFETY: bundle's type matches `bundle_info`, entity is allocated but non-existent
             unsafe { spawner.spawn_non_existent(entity, bundle) }
         };
+        // Archetype invariant handlers may despawn the entity, so they are left pending until after the returned `EntityMut`
+        self.check_new_archetypes_deferring_handlers();
 
         // SAFETY: entity and location are valid, as they were just created above
         unsafe { EntityMut::new(self, entity, entity_location) }
     }
 
+    /// Spawns a new entity with the given `bundle` like [`World::spawn`],
+    /// unless its components would violate an archetype invariant.
+    ///
+    /// The invariants are evaluated before the entity is created, so if the bundle is rejected,
+    /// the world is left unchanged, apart from the bundle's components being registered.
+    /// This makes it suitable for spawning entities from untrusted or generated data.
+    ///
+    /// Every archetype invariant with [`Severity::Error`] is evaluated, regardless of the [`ViolationPolicy`],
+    /// including deferred invariants and invariants with a handler.
+    /// The returned violation has no entities, and refers to [`ArchetypeId::INVALID`]
+    /// if no archetype with the bundle's components exists yet.
+    ///
+    /// ```
+    /// use bevy_ecs::prelude::*;
+    ///
+    /// #[derive(Component)]
+    /// struct Player;
+    /// #[derive(Component)]
+    /// struct Enemy;
+    ///
+    /// let mut world = World::new();
+    /// world.add_archetype_invariant(ArchetypeInvariant::<(Player,), (Enemy,)>::forbids());
+    ///
+    /// assert!(world.try_spawn(Player).is_ok());
+    /// assert!(world.try_spawn((Player, Enemy)).is_err());
+    /// assert_eq!(world.entities().len(), 1);
+    /// ```
+    // Violations are rare and are returned to be reported, so their size is not a concern.
+    #[allow(clippy::result_large_err)]
+    pub fn try_spawn<B: Bundle>(
+        &mut self,
+        bundle: B,
+    ) -> Result<Entity, ArchetypeInvariantViolation> {
+        let bundle_info = self
+            .bundles
+            .init_info::<B>(&mut self.components, &mut self.storages);
+        let component_ids: ComponentIdSet = bundle_info.components().iter().copied().collect();
+        let resources = present_resources(&self.archetypes);
+        if let Some(invariant) = self.archetype_invariants.iter_checked().find(|invariant| {
+            invariant.severity == Severity::Error
+                && !invariant.evaluate_with_resources(&component_ids, &resources)
+        }) {
+            let archetype_id = self
+                .archetypes
+                .iter()
+                .find(|archetype| {
+                    archetype.components().collect::<ComponentIdSet>() == component_ids
+                })
+                .map_or(ArchetypeId::INVALID, Archetype::id);
+            return Err(ArchetypeInvariantViolation::new(
+                archetype_id,
+                component_ids.iter().collect(),
+                Vec::new(),
+                invariant.clone(),
+                &self.components,
+            ));
+        }
+
+        Ok(self.spawn(bundle).id())
+    }
+
     /// # Safety
     /// must be called on an entity that was just allocated
     unsafe fn spawn_at_empty_internal(&mut self, entity: Entity) -> EntityMut {
@@ -483,6 +1698,9 @@ impl World {
     /// but it is limited to spawning entities with the same [Bundle] type, whereas spawning
     /// individually is more flexible.
     ///
+    /// The archetype of the batch is checked against the archetype invariants of the world
+    /// once the returned iterator has been dropped, which spawns any entities that have not been yet.
+    ///
     /// ```
     /// use bevy_ecs::{component::Component, entity::Entity, world::World};
     ///
@@ -796,7 +2014,9 @@ impl World {
         // ptr value / drop is called when R is dropped
         let (ptr, _) = unsafe { column.swap_remove_and_forget_unchecked(0) };
         // SAFETY: column is of type R
-        Some(unsafe { ptr.read::<R>() })
+        let resource = unsafe { ptr.read::<R>() };
+        self.check_resource_archetype_invariants();
+        Some(resource)
     }
 
     /// Returns `true` if a resource of type `R` exists. Otherwise returns `false`.
@@ -1120,6 +2340,8 @@ impl World {
             }
         }
 
+        self.check_new_archetypes();
+
         if invalid_entities.is_empty() {
             Ok(())
         } else {
@@ -1196,6 +2418,7 @@ impl World {
                 change_tick,
             },
         };
+        let archetype_checks = self.archetype_invariants.stats().archetype_checks;
         let result = f(self, value_mut);
         assert!(!self.contains_resource::<R>(),
             "Resource `{}` was inserted during a call to World::resource_scope.\n\
@@ -1214,6 +2437,10 @@ impl World {
                 column.push(ptr, ticks);
             }
         });
+        // Archetypes checked during `f` were checked as if the resource did not exist
+        if self.archetype_invariants.stats().archetype_checks != archetype_checks {
+            self.check_resource_archetype_invariants();
+        }
         result
     }
 
@@ -1315,11 +2542,20 @@ impl World {
         if column.is_empty() {
             // SAFETY: column is of type R and has been allocated above
             column.push(value, ComponentTicks::new(change_tick));
+            self.check_resource_archetype_invariants();
         } else {
             column.replace(0, value, change_tick);
         }
     }
 
+    /// Re-checks the archetype invariants that depend on which resources exist,
+    /// after a resource was inserted or removed.
+    #[inline]
+    fn check_resource_archetype_invariants(&mut self) {
+        self.archetype_invariants.resources_changed();
+        self.check_new_archetypes();
+    }
+
     /// # Safety
     /// `component_id` must be valid for this world
     #[inline]
@@ -1522,6 +2758,7 @@ impl World {
         }
         // SAFETY: if a resource column exists, row 0 exists as well
         unsafe { column.swap_remove_unchecked(0) };
+        self.check_resource_archetype_invariants();
 
         Some(())
     }
@@ -1907,4 +3144,80 @@ mod tests {
             [Some(baz_id)].into()
         );
     }
+
+    #[test]
+    fn get_component_id_by_name() {
+        let mut world = World::new();
+        let foo_id = world.init_component::<Foo>();
+        let bar_id = world.init_component::<Bar>();
+
+        let components = world.components();
+        assert_eq!(
+            components.get_id_by_name(std::any::type_name::<Foo>()),
+            Some(foo_id)
+        );
+        assert_eq!(
+            components.get_id_by_name(std::any::type_name::<Bar>()),
+            Some(bar_id)
+        );
+        // Only the full type name is accepted
+        assert_eq!(components.get_id_by_name("Foo"), None);
+
+        // A descriptor reusing a name does not take it over
+        // SAFETY: the drop function is valid for the layout and the data will be safe to access from any thread
+        let descriptor = unsafe {
+            ComponentDescriptor::new_with_layout(
+                std::any::type_name::<Foo>(),
+                StorageType::Table,
+                std::alloc::Layout::new::<u8>(),
+                None,
+            )
+        };
+        world.init_component_with_descriptor(descriptor);
+        assert_eq!(
+            world
+                .components()
+                .get_id_by_name(std::any::type_name::<Foo>()),
+            Some(foo_id)
+        );
+    }
+
+    #[test]
+    fn new_archetype_observers() {
+        let mut world = World::new();
+        world.spawn(Foo);
+        let created = Arc::new(Mutex::new(Vec::new()));
+        let log = created.clone();
+        world.on_new_archetype(move |id, components| {
+            log.lock().unwrap().push((id, components.to_vec()));
+        });
+
+        // Archetypes that existed before the callback was registered are not reported
+        world.spawn(Foo);
+        assert!(created.lock().unwrap().is_empty());
+
+        let entity = world.spawn((Foo, Bar)).id();
+        let foo = world.components().component_id::<Foo>().unwrap();
+        let bar = world.components().component_id::<Bar>().unwrap();
+        let archetype = world.entity(entity).archetype().id();
+        assert_eq!(*created.lock().unwrap(), [(archetype, vec![foo, bar])]);
+    }
+
+    #[cfg(feature = "archetype_invariants")]
+    #[test]
+    fn new_archetypes_are_observed_before_invariants_are_checked() {
+        use crate::archetype_invariants::ArchetypeInvariant;
+
+        let mut world = World::new();
+        world.add_archetype_invariant(ArchetypeInvariant::<(Foo,), (Bar,)>::requires());
+        let created = Arc::new(Mutex::new(0));
+        let count = created.clone();
+        world.on_new_archetype(move |_, _| *count.lock().unwrap() += 1);
+
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            world.spawn(Foo);
+        }));
+        assert!(result.is_err());
+        assert_eq!(*created.lock().unwrap(), 1);
+    }
 }
@@ -3,7 +3,7 @@ use crate::{
     entity::Entity,
     world::World,
 };
-use std::iter::FusedIterator;
+use std::{iter::FusedIterator, mem::ManuallyDrop};
 
 pub struct SpawnBatchIter<'w, I>
 where
@@ -11,7 +11,9 @@ where
     I::Item: Bundle,
 {
     inner: I,
-    spawner: BundleSpawner<'w, 'w>,
+    spawner: ManuallyDrop<BundleSpawner<'w, 'w>>,
+    // The world the spawner borrows from, used to check the batch's archetype once it has been spawned.
+    world: *mut World,
 }
 
 impl<'w, I> SpawnBatchIter<'w, I>
@@ -24,6 +26,10 @@ where
         // Ensure all entity allocations are accounted for so `self.entities` can realloc if
         // necessary
         world.flush();
+        let world_ptr: *mut World = world;
+        // SAFETY: `world_ptr` was just created from `world`, which is not used anymore,
+        // so that the pointer remains valid for as long as the reborrow is
+        let world: &'w mut World = unsafe { &mut *world_ptr };
 
         let (lower, upper) = iter.size_hint();
         let length = upper.unwrap_or(lower);
@@ -43,7 +49,8 @@ where
 
         Self {
             inner: iter,
-            spawner,
+            spawner: ManuallyDrop::new(spawner),
+            world: world_ptr,
         }
     }
 }
@@ -54,7 +61,19 @@ where
     I::Item: Bundle,
 {
     fn drop(&mut self) {
-        for _ in self {}
+        for _ in &mut *self {}
+
+        // SAFETY: the spawner is not used again after this
+        unsafe { ManuallyDrop::drop(&mut self.spawner) };
+        // The archetype of the batch is only checked against the world's archetype invariants now,
+        // so that its violations and their handlers include the spawned entities.
+        // Checking it while unwinding could panic again and abort instead.
+        if !std::thread::panicking() {
+            // SAFETY: this iterator borrows the world mutably for `'w`,
+            // and nothing borrows from it anymore now that the spawner has been dropped
+            let world = unsafe { &mut *self.world };
+            world.check_new_archetypes();
+        }
     }
 }
 
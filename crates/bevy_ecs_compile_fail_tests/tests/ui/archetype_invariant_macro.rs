@@ -0,0 +1,19 @@
+use bevy_ecs::prelude::*;
+
+#[derive(Component)]
+struct A;
+
+#[derive(Component)]
+struct B;
+
+fn main() {
+    // Unknown statement
+    let _ = archetype_invariant!(any_of(A) => all_of(B));
+    // Missing implication
+    let _ = archetype_invariant!(all_of(A) all_of(B));
+    // No components
+    let _ = archetype_invariant!(all_of() => none_of(B));
+
+    // Valid
+    let _ = archetype_invariant!(all_of(A) => none_of(B));
+}
@@ -0,0 +1,18 @@
+use bevy_ecs::prelude::*;
+
+#[derive(Component)]
+struct Transform;
+
+#[derive(Component)]
+#[component(requires("Transform"))]
+struct StringRequirement;
+
+#[derive(Component)]
+#[component(forbids(Transform = 1))]
+struct AssignedForbidden;
+
+#[derive(Component)]
+#[component(requires(Transform), forbids(Transform))]
+struct Valid;
+
+fn main() {}
@@ -38,5 +38,6 @@ impl Plugin for HierarchyPlugin {
         app.register_type::<Children>()
             .register_type::<Parent>()
             .add_event::<HierarchyEvent>();
+        app.world.set_hierarchy_components::<Parent, Children>();
     }
 }